@@ -1,8 +1,9 @@
+use crate::analysis::flow::flow_matrix;
 use crate::analysis::groups::{Group, GroupHealth, GroupSet, GroupSummary, GroupTrend};
 use crate::graph::graph::Graph;
 use crate::state::snapshot::Snapshot;
 
-fn calc_util(snapshot: &Snapshot, group: &Group, graph: &Graph, group_id: usize) -> f64 {
+pub fn calc_util(snapshot: &Snapshot, group: &Group, graph: &Graph, group_id: usize) -> f64 {
     let node_states = snapshot.node_states();
     let capacity_mod = snapshot.capacity_mod(group_id);
     let (agg_served, agg_capacity) = group
@@ -24,7 +25,21 @@ fn calc_util(snapshot: &Snapshot, group: &Group, graph: &Graph, group_id: usize)
     }
 }
 
-fn calc_health(snapshot: &Snapshot, group: &Group) -> f64 {
+/// Spend of the group in one turn: provisioned capacity times cost. Boosts add capacity that
+/// has to be paid for, a throttle only limits what is served and saves nothing.
+pub fn calc_cost(snapshot: &Snapshot, group: &Group, graph: &Graph, group_id: usize) -> f64 {
+    let factor = snapshot.capacity_mod(group_id).factor().max(1.0);
+    group
+        .nodes()
+        .iter()
+        .map(|id| {
+            let node = graph.node_by_id(*id);
+            node.capacity() * factor * node.cost()
+        })
+        .sum()
+}
+
+pub fn calc_health(snapshot: &Snapshot, group: &Group) -> f64 {
     let states = snapshot.node_states();
     let h = group
         .nodes()
@@ -46,6 +61,7 @@ pub fn aggregate_groups(
     graph: &Graph,
 ) -> Vec<GroupSummary> {
     let epsilon = 0.02;
+    let flows = flow_matrix(graph, group_set, current_snapshot);
     group_set
         .groups()
         .iter()
@@ -75,12 +91,7 @@ pub fn aggregate_groups(
                 GroupTrend::Flat
             };
 
-            let health = match curr_health {
-                n if n > 0.8 => GroupHealth::Ok,
-                n if n > 0.3 => GroupHealth::Degraded,
-                n if n > 0.0 => GroupHealth::Critical,
-                _ => GroupHealth::Failed,
-            };
+            let health = GroupHealth::classify(curr_health);
 
             let states = current_snapshot.node_states();
             let healthy_nodes = g
@@ -89,16 +100,7 @@ pub fn aggregate_groups(
                 .filter(|n_id| states[n_id.index()].is_healthy())
                 .count();
 
-            let mut pressure = vec![0.0; group_set.groups().len()];
-            g.nodes()
-                .iter()
-                .flat_map(|n_id| graph.incoming(*n_id))
-                .for_each(|e_id| {
-                    let load = current_snapshot.edge_load(*e_id, graph);
-                    let edge = graph.edge_by_id(*e_id);
-                    let source_group = group_set.group_by_node_id(edge.from().index());
-                    pressure[source_group] += load;
-                });
+            let pressure = flows.iter().map(|row| row[g_id]).collect();
 
             GroupSummary::new(
                 g.name().to_string(),
@@ -412,4 +414,32 @@ mod tests {
         assert_relative_eq!(40.0, pressure[1]);
         assert_relative_eq!(10.0, pressure[2]);
     }
+
+    #[test]
+    fn test_boost_costs_and_throttle_is_free() {
+        let api = Node::new(NodeId(0), "api".to_string(), 100.0, 1.0).with_cost(0.1);
+        let db = Node::new(NodeId(1), "db".to_string(), 50.0, 0.0).with_cost(0.4);
+        let graph = Graph::new(vec![api, db], vec![]);
+        let groupset = GroupSet::new(vec![
+            Group::new("api".to_string(), vec![NodeId(0)]),
+            Group::new("db".to_string(), vec![NodeId(1)]),
+        ]);
+
+        let mut snapshot = Snapshot::new(
+            1,
+            vec![
+                NodeState::new(0.0, 0.0, 0.0, 1.0),
+                NodeState::new(0.0, 0.0, 0.0, 0.0),
+            ],
+            vec![],
+            vec![CapacityModifier::new(); 2],
+        );
+        // failed nodes are still paid for
+        assert_relative_eq!(20.0, calc_cost(&snapshot, &groupset.groups()[1], &graph, 1));
+
+        snapshot.update_capacity(0, 0.5);
+        snapshot.update_capacity(1, 1.5);
+        assert_relative_eq!(10.0, calc_cost(&snapshot, &groupset.groups()[0], &graph, 0));
+        assert_relative_eq!(30.0, calc_cost(&snapshot, &groupset.groups()[1], &graph, 1));
+    }
 }
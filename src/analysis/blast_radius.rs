@@ -0,0 +1,236 @@
+use crate::analysis::bottleneck::unit_entry_load;
+use crate::analysis::groups::GroupSet;
+use crate::graph::graph::Graph;
+use crate::graph::node::NodeId;
+use std::cmp::Reverse;
+
+const EPSILON: f64 = 1e-9;
+const MAX_ITERATIONS: usize = 10_000;
+const TOLERANCE: f64 = 1e-9;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FailureTarget {
+    Node(NodeId),
+    Group(usize),
+}
+
+pub struct BlastRadius {
+    target: FailureTarget,
+    starved: Vec<NodeId>,
+    overloaded: Vec<NodeId>,
+}
+
+impl BlastRadius {
+    pub fn target(&self) -> FailureTarget {
+        self.target
+    }
+
+    /// nodes that lose all of their incoming load
+    pub fn starved(&self) -> &[NodeId] {
+        &self.starved
+    }
+
+    /// nodes pushed above pressure 1 once upstream nodes route around the failure
+    pub fn overloaded(&self) -> &[NodeId] {
+        &self.overloaded
+    }
+
+    pub fn size(&self) -> usize {
+        self.starved.len() + self.overloaded.len()
+    }
+}
+
+/// Like `edge_shares`, but upstream nodes split their load across the targets that are not
+/// `removed` only. A node whose targets are all removed forwards nothing.
+fn failover_shares(graph: &Graph, removed: &[bool]) -> Vec<f64> {
+    let mut total_weight = vec![0.0; graph.node_count()];
+    graph
+        .edges()
+        .iter()
+        .filter(|e| !removed[e.to().index()])
+        .for_each(|e| total_weight[e.from().index()] += e.weight());
+
+    graph
+        .edges()
+        .iter()
+        .map(|e| {
+            let total = total_weight[e.from().index()];
+            if removed[e.to().index()] || total == 0.0 {
+                0.0
+            } else {
+                graph.node_by_id(e.from()).gain() * e.weight() / total
+            }
+        })
+        .collect()
+}
+
+/// Load arriving at every node once the run settles. Like the engine, every node serves at
+/// most its capacity and forwards its shares of that, and `removed` nodes forward nothing.
+fn settle(graph: &Graph, shares: &[f64], entry: &[f64], removed: &[bool]) -> Vec<f64> {
+    let mut load = entry.to_vec();
+    for _ in 0..MAX_ITERATIONS {
+        let mut next = entry.to_vec();
+        graph
+            .edges()
+            .iter()
+            .filter(|e| !removed[e.from().index()])
+            .for_each(|e| {
+                let served = load[e.from().index()].min(graph.node_by_id(e.from()).capacity());
+                next[e.to().index()] += served * shares[e.id().index()];
+            });
+        let converged = next
+            .iter()
+            .zip(load.iter())
+            .all(|(n, l)| (n - l).abs() <= TOLERANCE * n.abs().max(1.0));
+        load = next;
+        if converged {
+            break;
+        }
+    }
+    load
+}
+
+fn fail(
+    graph: &Graph,
+    entry: &[f64],
+    baseline: &[f64],
+    target: FailureTarget,
+    removed: &[bool],
+) -> BlastRadius {
+    let load = settle(graph, &failover_shares(graph, removed), entry, removed);
+
+    let (starved, overloaded) = graph
+        .nodes()
+        .iter()
+        .map(|n| *n.id())
+        .filter(|id| !removed[id.index()])
+        .fold((vec![], vec![]), |(mut starved, mut overloaded), id| {
+            let capacity = graph.node_by_id(id).capacity();
+            let (before, after) = (baseline[id.index()], load[id.index()]);
+            if before > EPSILON && after <= EPSILON {
+                starved.push(id);
+            } else if after > capacity && before <= capacity {
+                overloaded.push(id);
+            }
+            (starved, overloaded)
+        });
+
+    BlastRadius {
+        target,
+        starved,
+        overloaded,
+    }
+}
+
+/// Fails every node and every group in turn (health 0, so it neither serves nor forwards
+/// load) with `entry_load` rps arriving at the entry nodes. Upstream nodes split their load
+/// across their remaining targets, as clients or load balancers that skip a dead backend do.
+/// The engine does not reroute this way: there a failed node's share is lost, so a node
+/// reported overloaded here only gets that load in a run if something routes around the
+/// failure. Largest blast radius first.
+pub fn blast_radii(
+    graph: &Graph,
+    groups: &GroupSet,
+    entry_nodes: &[NodeId],
+    entry_load: f64,
+) -> Vec<BlastRadius> {
+    let entry = unit_entry_load(graph, entry_nodes)
+        .iter()
+        .map(|l| l * entry_load)
+        .collect::<Vec<f64>>();
+    let mut removed = vec![false; graph.node_count()];
+    let baseline = settle(graph, &failover_shares(graph, &removed), &entry, &removed);
+
+    let targets = graph
+        .nodes()
+        .iter()
+        .map(|n| FailureTarget::Node(*n.id()))
+        .chain((0..groups.groups().len()).map(FailureTarget::Group))
+        .collect::<Vec<FailureTarget>>();
+
+    let mut radii = targets
+        .into_iter()
+        .map(|target| {
+            let nodes = match target {
+                FailureTarget::Node(id) => vec![id],
+                FailureTarget::Group(g_id) => groups.groups()[g_id].nodes().to_vec(),
+            };
+            nodes.iter().for_each(|id| removed[id.index()] = true);
+            let radius = fail(graph, &entry, &baseline, target, &removed);
+            nodes.iter().for_each(|id| removed[id.index()] = false);
+            radius
+        })
+        .collect::<Vec<BlastRadius>>();
+    radii.sort_by_key(|r| Reverse(r.size()));
+    radii
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::groups::Group;
+    use crate::graph::edge::{Edge, EdgeId};
+    use crate::graph::node::Node;
+
+    //        ↗ cache1 ↘
+    //    api            db
+    //        ↘ cache2 ↗
+    fn diamond() -> (Graph, GroupSet) {
+        let api = Node::new(NodeId(0), "api".to_string(), 100.0, 1.0);
+        let cache1 = Node::new(NodeId(1), "cache1".to_string(), 60.0, 1.0);
+        let cache2 = Node::new(NodeId(2), "cache2".to_string(), 60.0, 1.0);
+        let db = Node::new(NodeId(3), "db".to_string(), 200.0, 0.0);
+
+        let link1 = Edge::new(EdgeId(0), NodeId(0), NodeId(1), 1.0);
+        let link2 = Edge::new(EdgeId(1), NodeId(0), NodeId(2), 1.0);
+        let link3 = Edge::new(EdgeId(2), NodeId(1), NodeId(3), 1.0);
+        let link4 = Edge::new(EdgeId(3), NodeId(2), NodeId(3), 1.0);
+
+        let graph = Graph::new(
+            vec![api, cache1, cache2, db],
+            vec![link1, link2, link3, link4],
+        );
+        let groups = GroupSet::new(vec![
+            Group::new("Ingress".to_string(), vec![NodeId(0)]),
+            Group::new("Cache".to_string(), vec![NodeId(1), NodeId(2)]),
+            Group::new("Database".to_string(), vec![NodeId(3)]),
+        ]);
+        (graph, groups)
+    }
+
+    fn find(radii: &[BlastRadius], target: FailureTarget) -> &BlastRadius {
+        radii.iter().find(|r| r.target() == target).unwrap()
+    }
+
+    #[test]
+    fn test_entry_failure_starves_everything_downstream() {
+        let (graph, groups) = diamond();
+        let radii = blast_radii(&graph, &groups, &[NodeId(0)], 100.0);
+
+        let api = find(&radii, FailureTarget::Node(NodeId(0)));
+        assert_eq!(vec![NodeId(1), NodeId(2), NodeId(3)], api.starved());
+        assert!(api.overloaded().is_empty());
+        assert_eq!(3, radii[0].size());
+
+        let cache = find(&radii, FailureTarget::Group(1));
+        assert_eq!(vec![NodeId(3)], cache.starved());
+    }
+
+    #[test]
+    fn test_sibling_failure_overloads_survivor() {
+        let (graph, groups) = diamond();
+        let radii = blast_radii(&graph, &groups, &[NodeId(0)], 100.0);
+
+        // api sends cache2 all 100 rps, db still gets the 60 cache2 can serve
+        let cache1 = find(&radii, FailureTarget::Node(NodeId(1)));
+        assert!(cache1.starved().is_empty());
+        assert_eq!(vec![NodeId(2)], cache1.overloaded());
+
+        let db = find(&radii, FailureTarget::Node(NodeId(3)));
+        assert_eq!(0, db.size());
+
+        // below half of cache capacity a single cache absorbs the whole load
+        let radii = blast_radii(&graph, &groups, &[NodeId(0)], 50.0);
+        assert_eq!(0, find(&radii, FailureTarget::Node(NodeId(1))).size());
+    }
+}
@@ -0,0 +1,275 @@
+use crate::analysis::calibration::solve;
+use crate::analysis::cycles::{
+    component_gain, is_amplifying, is_cyclic, strongly_connected_components,
+};
+use crate::graph::graph::Graph;
+use crate::graph::node::NodeId;
+
+pub struct Bottleneck {
+    node_id: NodeId,
+    amplification: f64,
+    capacity: f64,
+    saturation_load: f64,
+}
+
+impl Bottleneck {
+    pub fn node_id(&self) -> NodeId {
+        self.node_id
+    }
+
+    /// steady-state load on the node per 1 rps of total entry load
+    pub fn amplification(&self) -> f64 {
+        self.amplification
+    }
+
+    pub fn capacity(&self) -> f64 {
+        self.capacity
+    }
+
+    /// total entry load (rps) at which the node reaches its capacity
+    pub fn saturation_load(&self) -> f64 {
+        self.saturation_load
+    }
+
+    pub fn is_unbounded(&self) -> bool {
+        self.amplification.is_infinite()
+    }
+}
+
+/// Fraction of a node's served load forwarded over each edge: `gain * weight / total_weight`.
+pub fn edge_shares(graph: &Graph) -> Vec<f64> {
+    graph
+        .edges()
+        .iter()
+        .map(|e| {
+            let total_weight = graph
+                .outgoing(e.from())
+                .iter()
+                .map(|e_id| graph.edge_by_id(*e_id).weight())
+                .sum::<f64>();
+            if total_weight == 0.0 {
+                0.0
+            } else {
+                graph.node_by_id(e.from()).gain() * e.weight() / total_weight
+            }
+        })
+        .collect()
+}
+
+/// 1 rps of total entry load split evenly across `entry_nodes`.
+pub fn unit_entry_load(graph: &Graph, entry_nodes: &[NodeId]) -> Vec<f64> {
+    let mut entry = vec![0.0; graph.node_count()];
+    entry_nodes
+        .iter()
+        .for_each(|id| entry[id.index()] += 1.0 / entry_nodes.len() as f64);
+    entry
+}
+
+/// `reached` and every node load flows to from there
+fn downstream(graph: &Graph, shares: &[f64], mut reached: Vec<bool>) -> Vec<bool> {
+    let mut queue = (0..graph.node_count())
+        .filter(|i| reached[*i])
+        .collect::<Vec<usize>>();
+    while let Some(i) = queue.pop() {
+        for e_id in graph.outgoing(NodeId(i)) {
+            let to = graph.edge_by_id(*e_id).to().index();
+            if shares[e_id.index()] > 0.0 && !reached[to] {
+                reached[to] = true;
+                queue.push(to);
+            }
+        }
+    }
+    reached
+}
+
+/// Steady-state load on every node for the given per-node entry load, solving
+/// `x = entry + Aᵀx` directly. Nodes fed by a loop that amplifies load without bound, a
+/// component whose loop gain is 1 or more, get `f64::INFINITY`.
+pub fn steady_state(graph: &Graph, shares: &[f64], entry: &[f64]) -> Vec<f64> {
+    let fed = downstream(graph, shares, entry.iter().map(|l| *l > 0.0).collect());
+    let mut amplifying = vec![false; graph.node_count()];
+    strongly_connected_components(graph)
+        .iter()
+        .filter(|nodes| fed[nodes[0].index()] && is_cyclic(graph, nodes))
+        .filter(|nodes| is_amplifying(component_gain(graph, shares, nodes)))
+        .flatten()
+        .for_each(|id| amplifying[id.index()] = true);
+    let unbounded = downstream(graph, shares, amplifying);
+
+    // what an unbounded node sends on is unbounded too, so the rest only feed each other
+    let bounded = (0..graph.node_count())
+        .filter(|i| fed[*i] && !unbounded[*i])
+        .collect::<Vec<usize>>();
+    let mut position = vec![None; graph.node_count()];
+    bounded
+        .iter()
+        .enumerate()
+        .for_each(|(pos, i)| position[*i] = Some(pos));
+    let mut a = (0..bounded.len())
+        .map(|row| {
+            (0..bounded.len())
+                .map(|col| f64::from(row == col))
+                .collect()
+        })
+        .collect::<Vec<Vec<f64>>>();
+    graph.edges().iter().for_each(|e| {
+        if let (Some(from), Some(to)) = (position[e.from().index()], position[e.to().index()]) {
+            a[to][from] -= shares[e.id().index()];
+        }
+    });
+    let b = bounded.iter().map(|i| entry[*i]).collect();
+    // singular only when a loop gain is within rounding of 1
+    let x = solve(a, b).unwrap_or_else(|| vec![f64::INFINITY; bounded.len()]);
+
+    let mut load = unbounded
+        .iter()
+        .map(|u| if *u { f64::INFINITY } else { 0.0 })
+        .collect::<Vec<f64>>();
+    bounded.iter().zip(x).for_each(|(i, l)| load[*i] = l);
+    load
+}
+
+/// Steady-state load on every node for 1 rps of total entry load split evenly across
+/// `entry_nodes`.
+pub fn amplification_factors(graph: &Graph, entry_nodes: &[NodeId]) -> Vec<f64> {
+    steady_state(
+        graph,
+        &edge_shares(graph),
+        &unit_entry_load(graph, entry_nodes),
+    )
+}
+
+/// Reachable nodes ordered by the total entry load at which they saturate, lowest first.
+pub fn rank_bottlenecks(graph: &Graph, entry_nodes: &[NodeId]) -> Vec<Bottleneck> {
+    let mut ranked = amplification_factors(graph, entry_nodes)
+        .into_iter()
+        .enumerate()
+        .filter(|(_, amp)| *amp > 0.0)
+        .map(|(i, amplification)| {
+            let capacity = graph.node_by_id(NodeId(i)).capacity();
+            Bottleneck {
+                node_id: NodeId(i),
+                amplification,
+                capacity,
+                saturation_load: capacity / amplification,
+            }
+        })
+        .collect::<Vec<Bottleneck>>();
+    ranked.sort_by(|a, b| a.saturation_load.total_cmp(&b.saturation_load));
+    ranked
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::cycles::find_feedback_loops;
+    use crate::graph::edge::{Edge, EdgeId};
+    use crate::graph::node::Node;
+    use approx::assert_relative_eq;
+
+    #[test]
+    fn test_amplification_along_chain() {
+        let api = Node::new(NodeId(0), "api".to_string(), 100.0, 2.0);
+        let orders = Node::new(NodeId(1), "orders".to_string(), 100.0, 1.5);
+        let db1 = Node::new(NodeId(2), "db1".to_string(), 60.0, 0.0);
+        let db2 = Node::new(NodeId(3), "db2".to_string(), 90.0, 0.0);
+
+        let link1 = Edge::new(EdgeId(0), NodeId(0), NodeId(1), 1.0);
+        let link2 = Edge::new(EdgeId(1), NodeId(1), NodeId(2), 1.0);
+        let link3 = Edge::new(EdgeId(2), NodeId(1), NodeId(3), 2.0);
+
+        let graph = Graph::new(vec![api, orders, db1, db2], vec![link1, link2, link3]);
+        let amp = amplification_factors(&graph, &[NodeId(0)]);
+
+        assert_relative_eq!(1.0, amp[0]);
+        assert_relative_eq!(2.0, amp[1]);
+        assert_relative_eq!(1.0, amp[2]);
+        assert_relative_eq!(2.0, amp[3]);
+
+        let ranked = rank_bottlenecks(&graph, &[NodeId(0)]);
+        assert_eq!(3, ranked[0].node_id().index());
+        assert_relative_eq!(45.0, ranked[0].saturation_load());
+        assert_eq!(1, ranked[1].node_id().index());
+        assert_relative_eq!(50.0, ranked[1].saturation_load());
+        assert_eq!(2, ranked[2].node_id().index());
+        assert_relative_eq!(60.0, ranked[2].saturation_load());
+    }
+
+    // api0  ← api1    api2
+    //     ↘    ↓     ↙
+    //          db0
+    //       ↙   ↻
+    //   api3
+    #[test]
+    fn test_amplification_with_self_loop() {
+        let api0 = Node::new(NodeId(0), "api0".to_string(), 100.0, 1.0);
+        let api1 = Node::new(NodeId(1), "api1".to_string(), 100.0, 1.0);
+        let api2 = Node::new(NodeId(2), "api2".to_string(), 100.0, 1.0);
+        let api3 = Node::new(NodeId(3), "api3".to_string(), 100.0, 1.0);
+        let db0 = Node::new(NodeId(4), "db0".to_string(), 100.0, 1.0);
+
+        let link1 = Edge::new(EdgeId(0), NodeId(0), NodeId(4), 1.0);
+        let link2 = Edge::new(EdgeId(1), NodeId(1), NodeId(4), 1.0);
+        let link3 = Edge::new(EdgeId(2), NodeId(2), NodeId(4), 2.0);
+        let link4 = Edge::new(EdgeId(3), NodeId(4), NodeId(3), 2.0);
+        let link5 = Edge::new(EdgeId(4), NodeId(4), NodeId(4), 1.0);
+        let link6 = Edge::new(EdgeId(5), NodeId(1), NodeId(0), 1.0);
+
+        let graph = Graph::new(
+            vec![api0, api1, api2, api3, db0],
+            vec![link1, link2, link3, link4, link5, link6],
+        );
+        let amp = amplification_factors(&graph, &[NodeId(0), NodeId(1), NodeId(2)]);
+
+        assert_relative_eq!(0.5, amp[0], epsilon = 1e-6);
+        assert_relative_eq!(1.0 / 3.0, amp[1], epsilon = 1e-6);
+        assert_relative_eq!(1.0 / 3.0, amp[2], epsilon = 1e-6);
+        assert_relative_eq!(1.0, amp[3], epsilon = 1e-6);
+        assert_relative_eq!(1.5, amp[4], epsilon = 1e-6);
+
+        let ranked = rank_bottlenecks(&graph, &[NodeId(0), NodeId(1), NodeId(2)]);
+        assert_eq!(4, ranked[0].node_id().index());
+        assert_relative_eq!(100.0 / 1.5, ranked[0].saturation_load(), epsilon = 1e-4);
+    }
+
+    #[test]
+    fn test_amplifying_loop_is_unbounded() {
+        let api = Node::new(NodeId(0), "api".to_string(), 100.0, 1.0);
+        let worker = Node::new(NodeId(1), "worker".to_string(), 100.0, 1.6);
+        let orders = Node::new(NodeId(2), "orders".to_string(), 100.0, 1.4);
+        let db = Node::new(NodeId(3), "db".to_string(), 100.0, 0.0);
+
+        let link1 = Edge::new(EdgeId(0), NodeId(0), NodeId(1), 1.0);
+        let link2 = Edge::new(EdgeId(1), NodeId(1), NodeId(2), 1.0);
+        let link3 = Edge::new(EdgeId(2), NodeId(2), NodeId(1), 1.0);
+        let link4 = Edge::new(EdgeId(3), NodeId(0), NodeId(3), 1.0);
+
+        let graph = Graph::new(
+            vec![api, worker, orders, db],
+            vec![link1, link2, link3, link4],
+        );
+        let ranked = rank_bottlenecks(&graph, &[NodeId(0)]);
+
+        assert!(ranked[0].is_unbounded());
+        assert!(ranked[1].is_unbounded());
+        assert_relative_eq!(0.0, ranked[0].saturation_load());
+        assert_relative_eq!(0.5, amplification_factors(&graph, &[NodeId(0)])[3]);
+    }
+
+    #[test]
+    fn test_slow_decaying_self_loop_is_bounded() {
+        // power iteration would still be moving after thousands of rounds
+        let api = Node::new(NodeId(0), "api".to_string(), 100.0, 1.0);
+        let worker = Node::new(NodeId(1), "worker".to_string(), 100.0, 0.999);
+        let link1 = Edge::new(EdgeId(0), NodeId(0), NodeId(1), 1.0);
+        let link2 = Edge::new(EdgeId(1), NodeId(1), NodeId(1), 1.0);
+        let graph = Graph::new(vec![api, worker], vec![link1, link2]);
+
+        let amp = amplification_factors(&graph, &[NodeId(0)]);
+        assert_relative_eq!(1000.0, amp[1], epsilon = 1e-6);
+        let ranked = rank_bottlenecks(&graph, &[NodeId(0)]);
+        assert!(!ranked[0].is_unbounded());
+        assert_relative_eq!(0.1, ranked[0].saturation_load(), epsilon = 1e-9);
+        assert!(!find_feedback_loops(&graph)[0].is_unstable());
+    }
+}
@@ -0,0 +1,380 @@
+use crate::graph::graph::Graph;
+use crate::graph::node::NodeId;
+use std::io;
+use std::io::BufRead;
+
+/// Observed request rates per service, one row per sample.
+pub struct RateSeries {
+    services: Vec<String>,
+    samples: Vec<Vec<f64>>,
+}
+
+impl RateSeries {
+    /// Reads a CSV with a time column followed by one column of rps per service, e.g.
+    /// `time,api,db`. The time column is not used, each row is one sample.
+    pub fn read_csv(input: impl BufRead) -> io::Result<Self> {
+        let invalid = |line: usize, msg: &str| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("line {}: {}", line, msg),
+            )
+        };
+
+        let mut lines = input.lines();
+        let header = lines
+            .next()
+            .transpose()?
+            .ok_or_else(|| invalid(1, "expected a header"))?;
+        let services = header
+            .split(',')
+            .skip(1)
+            .map(|s| s.trim().to_string())
+            .collect::<Vec<_>>();
+        if services.is_empty() {
+            return Err(invalid(
+                1,
+                "expected a time column and one column per service",
+            ));
+        }
+
+        let mut samples = vec![];
+        for (i, line) in lines.enumerate() {
+            let line_no = i + 2;
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let fields = line.split(',').map(str::trim).collect::<Vec<&str>>();
+            if fields.len() != services.len() + 1 {
+                return Err(invalid(
+                    line_no,
+                    &format!("expected {} fields", services.len() + 1),
+                ));
+            }
+            let row = fields[1..]
+                .iter()
+                .map(|s| {
+                    s.parse::<f64>()
+                        .map_err(|_| invalid(line_no, &format!("invalid number '{}'", s)))
+                })
+                .collect::<io::Result<Vec<f64>>>()?;
+            samples.push(row);
+        }
+        Ok(Self { services, samples })
+    }
+
+    pub fn services(&self) -> &[String] {
+        &self.services
+    }
+
+    fn series(&self, name: &str) -> Option<Vec<f64>> {
+        let column = self.services.iter().position(|s| s == name)?;
+        Some(self.samples.iter().map(|row| row[column]).collect())
+    }
+}
+
+/// How well the fitted calls per request explain one node's observed rate.
+pub struct NodeFit {
+    node_id: NodeId,
+    samples: usize,
+    r_squared: f64,
+    rmse: f64,
+}
+
+impl NodeFit {
+    pub fn node_id(&self) -> NodeId {
+        self.node_id
+    }
+
+    pub fn samples(&self) -> usize {
+        self.samples
+    }
+
+    /// share of the variance of the observed rate explained by the fit, 1.0 is a perfect fit
+    pub fn r_squared(&self) -> f64 {
+        self.r_squared
+    }
+
+    /// root mean square error in rps
+    pub fn rmse(&self) -> f64 {
+        self.rmse
+    }
+}
+
+pub struct Calibration {
+    /// fitted calls per request along each edge, `None` where the edge was not fitted
+    calls: Vec<Option<f64>>,
+    fits: Vec<NodeFit>,
+    skipped: Vec<(NodeId, String)>,
+}
+
+impl Calibration {
+    /// fitted nodes in node order
+    pub fn fits(&self) -> &[NodeFit] {
+        &self.fits
+    }
+
+    /// nodes with incoming edges that could not be fitted, with the reason
+    pub fn skipped(&self) -> &[(NodeId, String)] {
+        &self.skipped
+    }
+
+    /// Sets each edge's weight to its calls per request and each node's gain to their sum, so
+    /// that `gain * weight / total weight` is the fitted rate. Edges that were not fitted keep
+    /// their current calls per request.
+    pub fn apply(&self, graph: &mut Graph) {
+        for i in 0..graph.node_count() {
+            let outgoing = graph.outgoing(NodeId(i)).to_vec();
+            if !outgoing
+                .iter()
+                .any(|e_id| self.calls[e_id.index()].is_some())
+            {
+                continue;
+            }
+            let gain = graph.node_by_id(NodeId(i)).gain();
+            let total_weight = outgoing
+                .iter()
+                .map(|e_id| graph.edge_by_id(*e_id).weight())
+                .sum::<f64>();
+            let calls = outgoing
+                .iter()
+                .map(|e_id| {
+                    self.calls[e_id.index()].unwrap_or_else(|| {
+                        if total_weight > 0.0 {
+                            gain * graph.edge_by_id(*e_id).weight() / total_weight
+                        } else {
+                            0.0
+                        }
+                    })
+                })
+                .collect::<Vec<f64>>();
+
+            let new_gain = calls.iter().sum::<f64>();
+            graph.node_by_id_mut(NodeId(i)).set_gain(new_gain);
+            // without any calls the weights don't matter and are left alone
+            if new_gain > 0.0 {
+                outgoing
+                    .iter()
+                    .zip(calls)
+                    .for_each(|(e_id, c)| graph.edge_by_id_mut(*e_id).set_weight(c));
+            }
+        }
+    }
+}
+
+/// Solves `a x = b` by Gaussian elimination with partial pivoting, `None` when singular.
+pub fn solve(mut a: Vec<Vec<f64>>, mut b: Vec<f64>) -> Option<Vec<f64>> {
+    let n = b.len();
+    for col in 0..n {
+        let pivot = (col..n).max_by(|x, y| a[*x][col].abs().total_cmp(&a[*y][col].abs()))?;
+        if a[pivot][col].abs() < 1e-12 {
+            return None;
+        }
+        a.swap(col, pivot);
+        b.swap(col, pivot);
+        let pivot_row = a[col].clone();
+        for row in col + 1..n {
+            let factor = a[row][col] / pivot_row[col];
+            a[row]
+                .iter_mut()
+                .zip(&pivot_row)
+                .skip(col)
+                .for_each(|(x, p)| *x -= factor * p);
+            b[row] -= factor * b[col];
+        }
+    }
+    let mut x = vec![0.0; n];
+    for row in (0..n).rev() {
+        let rest = (row + 1..n).map(|k| a[row][k] * x[k]).sum::<f64>();
+        x[row] = (b[row] - rest) / a[row][row];
+    }
+    Some(x)
+}
+
+/// Non-negative least squares of `y ≈ Σ c_k x_k`: solves the normal equations and drops the
+/// columns that come out negative until none does.
+fn fit(columns: &[Vec<f64>], y: &[f64]) -> Option<Vec<f64>> {
+    let mut active = (0..columns.len()).collect::<Vec<usize>>();
+    loop {
+        let a = active
+            .iter()
+            .map(|i| {
+                active
+                    .iter()
+                    .map(|j| {
+                        columns[*i]
+                            .iter()
+                            .zip(&columns[*j])
+                            .map(|(p, q)| p * q)
+                            .sum()
+                    })
+                    .collect()
+            })
+            .collect();
+        let b = active
+            .iter()
+            .map(|i| columns[*i].iter().zip(y).map(|(p, q)| p * q).sum())
+            .collect();
+        let x = solve(a, b)?;
+
+        let mut c = vec![0.0; columns.len()];
+        active.iter().zip(&x).for_each(|(i, v)| c[*i] = *v);
+        if x.iter().all(|v| *v >= 0.0) {
+            return Some(c);
+        }
+        active.retain(|i| c[*i] > 0.0);
+        if active.is_empty() {
+            return Some(vec![0.0; columns.len()]);
+        }
+    }
+}
+
+/// Fits the calls per request of every edge into a node to the observed rates: the rate of
+/// each node that is not an entry node is modelled as the sum over its incoming edges of the
+/// caller's rate times the edge's calls per request. A node is skipped when it or one of its
+/// callers has no series, or when its callers' rates are linearly dependent.
+pub fn calibrate(graph: &Graph, entry_nodes: &[NodeId], rates: &RateSeries) -> Calibration {
+    let mut calls = vec![None; graph.edges().len()];
+    let mut fits = vec![];
+    let mut skipped = vec![];
+
+    for node in graph.nodes() {
+        let id = *node.id();
+        let incoming = graph.incoming(id);
+        if incoming.is_empty() || entry_nodes.contains(&id) {
+            continue;
+        }
+        let Some(y) = rates.series(node.name()) else {
+            skipped.push((id, "no observed rate".to_string()));
+            continue;
+        };
+        let columns = incoming
+            .iter()
+            .map(|e_id| {
+                let caller = graph.node_by_id(graph.edge_by_id(*e_id).from());
+                rates.series(caller.name()).ok_or(caller.name())
+            })
+            .collect::<Result<Vec<_>, _>>();
+        let columns = match columns {
+            Ok(columns) => columns,
+            Err(caller) => {
+                skipped.push((id, format!("no observed rate for caller {}", caller)));
+                continue;
+            }
+        };
+        let Some(c) = fit(&columns, &y) else {
+            skipped.push((id, "callers' rates are linearly dependent".to_string()));
+            continue;
+        };
+
+        let predicted = (0..y.len())
+            .map(|t| columns.iter().zip(&c).map(|(x, c)| x[t] * c).sum::<f64>())
+            .collect::<Vec<f64>>();
+        let mean = y.iter().sum::<f64>() / y.len() as f64;
+        let ss_res = y
+            .iter()
+            .zip(&predicted)
+            .map(|(o, p)| (o - p).powi(2))
+            .sum::<f64>();
+        let ss_tot = y.iter().map(|o| (o - mean).powi(2)).sum::<f64>();
+        let r_squared = if ss_tot > 0.0 {
+            1.0 - ss_res / ss_tot
+        } else if ss_res == 0.0 {
+            1.0
+        } else {
+            0.0
+        };
+
+        incoming
+            .iter()
+            .zip(c)
+            .for_each(|(e_id, c)| calls[e_id.index()] = Some(c));
+        fits.push(NodeFit {
+            node_id: id,
+            samples: y.len(),
+            r_squared,
+            rmse: (ss_res / y.len() as f64).sqrt(),
+        });
+    }
+
+    Calibration {
+        calls,
+        fits,
+        skipped,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::edge::{Edge, EdgeId};
+    use crate::graph::node::Node;
+    use approx::assert_relative_eq;
+
+    // api → auth
+    //   ↘ db ← auth
+    fn graph() -> Graph {
+        let api = Node::new(NodeId(0), "api".to_string(), 100.0, 1.0);
+        let auth = Node::new(NodeId(1), "auth".to_string(), 100.0, 1.0);
+        let db = Node::new(NodeId(2), "db".to_string(), 100.0, 0.0);
+        Graph::new(
+            vec![api, auth, db],
+            vec![
+                Edge::new(EdgeId(0), NodeId(0), NodeId(1), 1.0),
+                Edge::new(EdgeId(1), NodeId(0), NodeId(2), 1.0),
+                Edge::new(EdgeId(2), NodeId(1), NodeId(2), 1.0),
+            ],
+        )
+    }
+
+    fn rates(csv: &str) -> RateSeries {
+        RateSeries::read_csv(csv.as_bytes()).unwrap()
+    }
+
+    #[test]
+    fn test_recovers_calls_per_request() {
+        // auth is roughly half of api, db is exactly 2 api + 1 auth
+        let rates = rates("time,api,auth,db\n0,10,5,25\n1,20,11,51\n2,40,19,99\n3,30,15,75\n");
+        let mut graph = graph();
+        let calibration = calibrate(&graph, &[NodeId(0)], &rates);
+
+        assert!(calibration.skipped().is_empty());
+        let fits = calibration.fits();
+        assert_eq!(
+            vec![NodeId(1), NodeId(2)],
+            fits.iter().map(|f| f.node_id()).collect::<Vec<_>>()
+        );
+        assert_eq!(4, fits[0].samples());
+        assert!(fits[0].r_squared() > 0.95 && fits[0].r_squared() < 1.0);
+        assert_relative_eq!(1.0, fits[1].r_squared(), epsilon = 1e-9);
+        assert_relative_eq!(0.0, fits[1].rmse(), epsilon = 1e-9);
+
+        calibration.apply(&mut graph);
+        // Σ api·auth / Σ api² = 1480 / 3000
+        let api_auth = 1480.0 / 3000.0;
+        assert_relative_eq!(
+            api_auth + 2.0,
+            graph.node_by_id(NodeId(0)).gain(),
+            epsilon = 1e-9
+        );
+        assert_relative_eq!(api_auth, graph.edges()[0].weight(), epsilon = 1e-9);
+        assert_relative_eq!(2.0, graph.edges()[1].weight(), epsilon = 1e-9);
+        assert_relative_eq!(1.0, graph.node_by_id(NodeId(1)).gain(), epsilon = 1e-9);
+        assert_relative_eq!(0.0, graph.node_by_id(NodeId(2)).gain());
+    }
+
+    #[test]
+    fn test_skips_nodes_without_series() {
+        let rates = rates("time,api,db\n0,10,20\n1,20,40\n");
+        let calibration = calibrate(&graph(), &[NodeId(0)], &rates);
+
+        assert!(calibration.fits().is_empty());
+        assert_eq!(
+            vec![
+                (NodeId(1), "no observed rate".to_string()),
+                (NodeId(2), "no observed rate for caller auth".to_string())
+            ],
+            calibration.skipped()
+        );
+    }
+}
@@ -0,0 +1,423 @@
+use crate::analysis::groups::GroupSet;
+use crate::graph::graph::Graph;
+use crate::graph::node::NodeId;
+use crate::state::snapshot::Snapshot;
+
+#[derive(Clone)]
+pub struct FailureEvent {
+    node_id: NodeId,
+    overloaded_at: usize,
+    failed_at: usize,
+    trigger: Option<NodeId>,
+    trigger_share: f64,
+    trigger_group: Option<(usize, f64)>,
+    upstream: Vec<(NodeId, f64)>,
+    parent: Option<usize>,
+}
+
+impl FailureEvent {
+    pub fn node_id(&self) -> NodeId {
+        self.node_id
+    }
+
+    /// first turn of the overload streak that ended in the failure
+    pub fn overloaded_at(&self) -> usize {
+        self.overloaded_at
+    }
+
+    /// turn at which health reached 0
+    pub fn failed_at(&self) -> usize {
+        self.failed_at
+    }
+
+    /// upstream node sending the most load when the overload started, `None` when entry
+    /// traffic dominated
+    pub fn trigger(&self) -> Option<NodeId> {
+        self.trigger
+    }
+
+    /// fraction of the incoming load that came from the trigger
+    pub fn trigger_share(&self) -> f64 {
+        self.trigger_share
+    }
+
+    /// upstream group sending the most load when the overload started, with its share
+    pub fn trigger_group(&self) -> Option<(usize, f64)> {
+        self.trigger_group
+    }
+
+    pub fn is_root(&self) -> bool {
+        self.parent.is_none()
+    }
+
+    pub fn describe(&self, graph: &Graph, groups: &GroupSet) -> String {
+        let percent = |share: f64| (share * 100.0).round() as usize;
+        let cause = match (self.trigger, self.trigger_group) {
+            (Some(id), Some((g_id, g_share))) if g_share > self.trigger_share => format!(
+                "{} pushed it over capacity ({}% of its load, {}% from {})",
+                groups.groups()[g_id].name(),
+                percent(g_share),
+                percent(self.trigger_share),
+                graph.node_by_id(id).name()
+            ),
+            (Some(id), _) => format!(
+                "{} pushed it over capacity ({}% of its load)",
+                graph.node_by_id(id).name(),
+                percent(self.trigger_share)
+            ),
+            (None, _) if self.trigger_share > 0.0 => {
+                "entry traffic pushed it over capacity".to_string()
+            }
+            (None, _) => "its own backlog kept it over capacity".to_string(),
+        };
+        format!(
+            "{} failed at turn {}, {} turns after {}",
+            graph.node_by_id(self.node_id).name(),
+            self.failed_at,
+            self.failed_at - self.overloaded_at,
+            cause
+        )
+    }
+}
+
+pub struct CascadeTree {
+    events: Vec<FailureEvent>,
+}
+
+impl CascadeTree {
+    /// failures in the order they happened
+    pub fn events(&self) -> &[FailureEvent] {
+        &self.events
+    }
+
+    /// earliest overload that was not caused by another failure
+    pub fn root_overload(&self) -> Option<&FailureEvent> {
+        self.events
+            .iter()
+            .filter(|e| e.is_root())
+            .min_by_key(|e| (e.overloaded_at, e.failed_at))
+    }
+
+    /// events in depth-first order with their depth in the tree
+    pub fn walk(&self) -> Vec<(usize, &FailureEvent)> {
+        let mut children = vec![vec![]; self.events.len()];
+        self.events
+            .iter()
+            .enumerate()
+            .filter_map(|(i, e)| e.parent.map(|p| (p, i)))
+            .for_each(|(p, i)| children[p].push(i));
+
+        let mut walk = Vec::with_capacity(self.events.len());
+        let mut stack = (0..self.events.len())
+            .rev()
+            .filter(|i| self.events[*i].is_root())
+            .map(|i| (0, i))
+            .collect::<Vec<(usize, usize)>>();
+        while let Some((depth, i)) = stack.pop() {
+            walk.push((depth, &self.events[i]));
+            children[i]
+                .iter()
+                .rev()
+                .for_each(|child| stack.push((depth + 1, *child)));
+        }
+        walk
+    }
+}
+
+/// Pressure as the engine computes it: the turn's demand plus the backlog carried over from
+/// `previous`, against the throttled capacity.
+fn pressure(
+    graph: &Graph,
+    groups: &GroupSet,
+    previous: Option<&Snapshot>,
+    snapshot: &Snapshot,
+    id: NodeId,
+) -> f64 {
+    let state = &snapshot.node_states()[id.index()];
+    let carried = previous.map_or(0.0, |p| p.node_states()[id.index()].backlog());
+    let capacity = graph.node_by_id(id).capacity()
+        * snapshot
+            .capacity_mod(groups.group_by_node_id(id.index()))
+            .factor();
+    if capacity == 0.0 {
+        0.0
+    } else {
+        (state.demand() + carried) / capacity
+    }
+}
+
+/// Load from each upstream node, and the entry load.
+type IncomingLoad = (Vec<(NodeId, f64)>, f64);
+
+/// Load each upstream node sent into `id` for `snapshot`, and the entry load (the part of the
+/// demand no edge accounts for).
+fn incoming_load(
+    graph: &Graph,
+    previous: Option<&Snapshot>,
+    snapshot: &Snapshot,
+    id: NodeId,
+) -> IncomingLoad {
+    let demand = snapshot.node_states()[id.index()].demand();
+    let Some(sender) = previous else {
+        return (vec![], demand);
+    };
+    let mut upstream: Vec<(NodeId, f64)> = Vec::new();
+    graph.incoming(id).iter().for_each(|e_id| {
+        let from = graph.edge_by_id(*e_id).from();
+        let load = sender.edge_load(*e_id, graph);
+        match upstream.iter_mut().find(|(n_id, _)| *n_id == from) {
+            Some((_, l)) => *l += load,
+            None => upstream.push((from, load)),
+        }
+    });
+    upstream.retain(|(_, l)| *l > 0.0);
+    let entry = demand - upstream.iter().map(|(_, l)| l).sum::<f64>();
+    (upstream, entry.max(0.0))
+}
+
+fn failure_event(
+    groups: &GroupSet,
+    id: NodeId,
+    overloaded_at: usize,
+    failed_at: usize,
+    (upstream, entry): IncomingLoad,
+) -> FailureEvent {
+    let total = entry + upstream.iter().map(|(_, l)| l).sum::<f64>();
+    let share = |load: f64| if total > 0.0 { load / total } else { 0.0 };
+
+    let (trigger, trigger_share) = upstream
+        .iter()
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+        .filter(|(_, load)| *load > entry)
+        .map_or((None, share(entry)), |(from, load)| {
+            (Some(*from), share(*load))
+        });
+
+    let mut group_load = vec![0.0; groups.groups().len()];
+    upstream
+        .iter()
+        .for_each(|(from, load)| group_load[groups.group_by_node_id(from.index())] += load);
+    let trigger_group = trigger.and_then(|_| {
+        group_load
+            .iter()
+            .enumerate()
+            .max_by(|a, b| a.1.total_cmp(b.1))
+            .map(|(g_id, load)| (g_id, share(*load)))
+    });
+
+    FailureEvent {
+        node_id: id,
+        overloaded_at,
+        failed_at,
+        trigger,
+        trigger_share,
+        trigger_group,
+        upstream,
+        parent: None,
+    }
+}
+
+/// Follows a run one snapshot at a time, so that the cascade of a growing run can be rebuilt
+/// without going over its whole history again.
+pub struct CascadeTracker {
+    turn: usize,
+    previous: Option<Snapshot>,
+    /// first turn of each node's current overload streak, with its incoming load then
+    streaks: Vec<Option<(usize, IncomingLoad)>>,
+    events: Vec<FailureEvent>,
+}
+
+impl CascadeTracker {
+    pub fn new(graph: &Graph) -> Self {
+        Self {
+            turn: 0,
+            previous: None,
+            streaks: vec![None; graph.node_count()],
+            events: vec![],
+        }
+    }
+
+    /// Tracks `snapshot` as the turn after the last tracked one.
+    pub fn push(&mut self, graph: &Graph, groups: &GroupSet, snapshot: &Snapshot) {
+        if let Some(previous) = self.previous.as_ref() {
+            for node in graph.nodes() {
+                let id = *node.id();
+                if pressure(graph, groups, Some(previous), snapshot, id) <= 1.0 {
+                    self.streaks[id.index()] = None;
+                } else if self.streaks[id.index()].is_none() {
+                    let incoming = incoming_load(graph, Some(previous), snapshot, id);
+                    self.streaks[id.index()] = Some((self.turn, incoming));
+                }
+
+                if previous.node_states()[id.index()].is_healthy()
+                    && !snapshot.node_states()[id.index()].is_healthy()
+                {
+                    let (overloaded_at, incoming) =
+                        self.streaks[id.index()].take().unwrap_or_else(|| {
+                            (
+                                self.turn,
+                                incoming_load(graph, Some(previous), snapshot, id),
+                            )
+                        });
+                    self.events.push(failure_event(
+                        groups,
+                        id,
+                        overloaded_at,
+                        self.turn,
+                        incoming,
+                    ));
+                }
+            }
+        }
+        self.previous = Some(snapshot.clone());
+        self.turn += 1;
+    }
+
+    /// A failure is caused by the upstream failure sending it the most load among those that
+    /// were already overloaded when it went over capacity; otherwise it is a root overload.
+    pub fn tree(&self, graph: &Graph) -> CascadeTree {
+        let mut events = self.events.clone();
+        events.sort_by_key(|e| (e.failed_at, e.overloaded_at, e.node_id.index()));
+
+        let mut event_by_node = vec![None; graph.node_count()];
+        events
+            .iter()
+            .enumerate()
+            .for_each(|(i, e)| event_by_node[e.node_id.index()] = Some(i));
+
+        let parents = events
+            .iter()
+            .map(|e| {
+                e.upstream
+                    .iter()
+                    .filter_map(|(from, load)| event_by_node[from.index()].map(|p| (p, *load)))
+                    .filter(|(p, _)| events[*p].overloaded_at < e.overloaded_at)
+                    .max_by(|a, b| a.1.total_cmp(&b.1))
+                    .map(|(p, _)| p)
+            })
+            .collect::<Vec<Option<usize>>>();
+        events
+            .iter_mut()
+            .zip(parents)
+            .for_each(|(e, parent)| e.parent = parent);
+
+        CascadeTree { events }
+    }
+}
+
+/// Rebuilds which failure led to which from the snapshot history, see `CascadeTracker::tree`.
+pub fn reconstruct_cascade(
+    graph: &Graph,
+    groups: &GroupSet,
+    snapshots: &[&Snapshot],
+) -> CascadeTree {
+    let mut tracker = CascadeTracker::new(graph);
+    snapshots
+        .iter()
+        .for_each(|s| tracker.push(graph, groups, s));
+    tracker.tree(graph)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::groups::Group;
+    use crate::graph::edge::{Edge, EdgeId};
+    use crate::graph::node::Node;
+    use crate::scenario::scenario::TestScenario;
+    use crate::simulation::engine::SimulationEngine;
+    use crate::simulation::modifiers::CapacityModifier;
+    use crate::state::edge_state::EdgeState;
+    use crate::state::node_state::NodeState;
+    use approx::assert_relative_eq;
+
+    // api → orders → db
+    //   ↘ cache
+    fn engine(load: f64) -> SimulationEngine {
+        let api = Node::new(NodeId(0), "api".to_string(), 100.0, 2.0);
+        let orders = Node::new(NodeId(1), "orders".to_string(), 50.0, 1.0);
+        let db = Node::new(NodeId(2), "db".to_string(), 20.0, 0.0);
+        let cache = Node::new(NodeId(3), "cache".to_string(), 100.0, 0.0);
+
+        let link1 = Edge::new(EdgeId(0), NodeId(0), NodeId(1), 1.0);
+        let link2 = Edge::new(EdgeId(1), NodeId(1), NodeId(2), 1.0);
+        let link3 = Edge::new(EdgeId(2), NodeId(0), NodeId(3), 1.0);
+
+        let graph = Graph::new(vec![api, orders, db, cache], vec![link1, link2, link3]);
+        let groups = GroupSet::new(vec![Group::new(
+            "group1".to_string(),
+            vec![NodeId(0), NodeId(1), NodeId(2), NodeId(3)],
+        )]);
+        let snapshot = Snapshot::new(
+            0,
+            graph
+                .nodes()
+                .iter()
+                .map(|_| NodeState::new(0.0, 0.0, 0.0, 1.0))
+                .collect(),
+            graph.edges().iter().map(|_| EdgeState::new(true)).collect(),
+            vec![CapacityModifier::new(); 1],
+        );
+        SimulationEngine::new(
+            graph,
+            groups,
+            snapshot,
+            Box::new(TestScenario::new(vec![NodeId(0)], vec![load])),
+        )
+    }
+
+    #[test]
+    fn test_overload_from_healthy_upstream_is_root() {
+        let mut engine = engine(40.0);
+        (0..30).for_each(|_| engine.step());
+
+        let tree = reconstruct_cascade(engine.graph(), engine.groups(), &engine.snapshots());
+        let events = tree.events();
+        assert_eq!(1, events.len());
+
+        // orders forwards 40 rps into a 20 rps db from turn 3, health drops 0.1, 0.2, ...
+        let db = &events[0];
+        assert_eq!(NodeId(2), db.node_id());
+        assert_eq!(Some(NodeId(1)), db.trigger());
+        assert_relative_eq!(1.0, db.trigger_share());
+        assert_eq!(3, db.overloaded_at());
+        assert_eq!(6, db.failed_at());
+        assert!(db.is_root());
+        assert_eq!(NodeId(2), tree.root_overload().unwrap().node_id());
+    }
+
+    #[test]
+    fn test_overloaded_trigger_becomes_parent() {
+        let mut engine = engine(60.0);
+        (0..30).for_each(|_| engine.step());
+
+        let tree = reconstruct_cascade(engine.graph(), engine.groups(), &engine.snapshots());
+
+        // db fails first, but only because orders was already overloaded and serving at capacity
+        assert_eq!(NodeId(2), tree.events()[0].node_id());
+        assert_eq!(NodeId(1), tree.events()[1].node_id());
+        assert_eq!(Some(1), tree.events()[0].parent);
+
+        let walk = tree
+            .walk()
+            .iter()
+            .map(|(depth, e)| (*depth, e.node_id()))
+            .collect::<Vec<(usize, NodeId)>>();
+        assert_eq!(vec![(0, NodeId(1)), (1, NodeId(2))], walk);
+
+        let root = tree.root_overload().unwrap();
+        assert_eq!(NodeId(1), root.node_id());
+        assert_eq!(Some(NodeId(0)), root.trigger());
+        assert_eq!(2, root.overloaded_at());
+    }
+
+    #[test]
+    fn test_no_failures_without_overload() {
+        let mut engine = engine(10.0);
+        (0..30).for_each(|_| engine.step());
+
+        let tree = reconstruct_cascade(engine.graph(), engine.groups(), &engine.snapshots());
+        assert!(tree.events().is_empty());
+        assert!(tree.root_overload().is_none());
+    }
+}
@@ -0,0 +1,255 @@
+use crate::analysis::groups::GroupHealth;
+use crate::analysis::metrics::{GroupMetrics, RunMetrics};
+
+const TOLERANCE: f64 = 1e-6;
+
+#[derive(Debug, PartialEq)]
+pub enum Outcome {
+    /// failed in the baseline, alive in the variant
+    Saved,
+    /// alive in the baseline, failed in the variant
+    Lost,
+    Unchanged,
+}
+
+pub struct GroupDiff {
+    name: String,
+    baseline: GroupMetrics,
+    variant: GroupMetrics,
+    max_health_gap: f64,
+    baseline_spend: f64,
+    variant_spend: f64,
+    outcome: Outcome,
+}
+
+impl GroupDiff {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// metrics at the last aligned turn
+    pub fn baseline(&self) -> &GroupMetrics {
+        &self.baseline
+    }
+
+    /// metrics at the last aligned turn
+    pub fn variant(&self) -> &GroupMetrics {
+        &self.variant
+    }
+
+    pub fn health_diff(&self) -> f64 {
+        self.variant.health() - self.baseline.health()
+    }
+
+    pub fn utilization_diff(&self) -> f64 {
+        self.variant.utilization() - self.baseline.utilization()
+    }
+
+    pub fn backlog_diff(&self) -> f64 {
+        self.variant.backlog() - self.baseline.backlog()
+    }
+
+    /// largest absolute health difference over all aligned turns
+    pub fn max_health_gap(&self) -> f64 {
+        self.max_health_gap
+    }
+
+    /// spend over all aligned turns
+    pub fn baseline_spend(&self) -> f64 {
+        self.baseline_spend
+    }
+
+    /// spend over all aligned turns
+    pub fn variant_spend(&self) -> f64 {
+        self.variant_spend
+    }
+
+    pub fn spend_diff(&self) -> f64 {
+        self.variant_spend - self.baseline_spend
+    }
+
+    pub fn outcome(&self) -> &Outcome {
+        &self.outcome
+    }
+}
+
+pub struct Comparison {
+    last_turn: Option<usize>,
+    diverged_at: Option<usize>,
+    groups: Vec<GroupDiff>,
+}
+
+impl Comparison {
+    /// last turn present in both runs
+    pub fn last_turn(&self) -> Option<usize> {
+        self.last_turn
+    }
+
+    /// first turn at which any group metric differs
+    pub fn diverged_at(&self) -> Option<usize> {
+        self.diverged_at
+    }
+
+    pub fn groups(&self) -> &[GroupDiff] {
+        &self.groups
+    }
+
+    /// extra spend of the variant over all compared groups
+    pub fn spend_diff(&self) -> f64 {
+        self.groups.iter().map(|g| g.spend_diff()).sum()
+    }
+}
+
+fn differs(a: f64, b: f64) -> bool {
+    (a - b).abs() > TOLERANCE * a.abs().max(b.abs()).max(1.0)
+}
+
+fn is_failed(metrics: &GroupMetrics) -> bool {
+    GroupHealth::classify(metrics.health()) == GroupHealth::Failed
+}
+
+/// Aligns `baseline` and `variant` by turn and compares the groups present in both,
+/// matched by name, in baseline order.
+pub fn compare_runs(baseline: &RunMetrics, variant: &RunMetrics) -> Comparison {
+    let matched = baseline
+        .groups()
+        .iter()
+        .enumerate()
+        .filter_map(|(b_id, name)| {
+            let v_id = variant.groups().iter().position(|g| g == name)?;
+            Some((b_id, v_id))
+        })
+        .collect::<Vec<(usize, usize)>>();
+    let aligned = baseline
+        .turns()
+        .iter()
+        .filter_map(|b| Some((b, variant.at_turn(b.turn())?)))
+        .collect::<Vec<_>>();
+
+    let diverged_at = aligned
+        .iter()
+        .find(|(b, v)| {
+            matched.iter().any(|(b_id, v_id)| {
+                let (bm, vm) = (b.groups()[*b_id], v.groups()[*v_id]);
+                differs(bm.health(), vm.health())
+                    || differs(bm.utilization(), vm.utilization())
+                    || differs(bm.backlog(), vm.backlog())
+                    || differs(bm.cost(), vm.cost())
+            })
+        })
+        .map(|(b, _)| b.turn());
+
+    let groups = match aligned.last() {
+        None => vec![],
+        Some((b_last, v_last)) => matched
+            .iter()
+            .map(|(b_id, v_id)| {
+                let baseline_final = b_last.groups()[*b_id];
+                let variant_final = v_last.groups()[*v_id];
+                let max_health_gap = aligned
+                    .iter()
+                    .map(|(b, v)| (b.groups()[*b_id].health() - v.groups()[*v_id].health()).abs())
+                    .fold(0.0, f64::max);
+                let (baseline_spend, variant_spend) =
+                    aligned.iter().fold((0.0, 0.0), |(b_sum, v_sum), (b, v)| {
+                        (
+                            b_sum + b.groups()[*b_id].cost(),
+                            v_sum + v.groups()[*v_id].cost(),
+                        )
+                    });
+                let outcome = match (is_failed(&baseline_final), is_failed(&variant_final)) {
+                    (true, false) => Outcome::Saved,
+                    (false, true) => Outcome::Lost,
+                    _ => Outcome::Unchanged,
+                };
+                GroupDiff {
+                    name: baseline.groups()[*b_id].clone(),
+                    baseline: baseline_final,
+                    variant: variant_final,
+                    max_health_gap,
+                    baseline_spend,
+                    variant_spend,
+                    outcome,
+                }
+            })
+            .collect(),
+    };
+
+    Comparison {
+        last_turn: aligned.last().map(|(b, _)| b.turn()),
+        diverged_at,
+        groups,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+
+    fn run(csv: &str) -> RunMetrics {
+        RunMetrics::read_csv(csv.as_bytes()).unwrap()
+    }
+
+    #[test]
+    fn test_divergence_and_outcomes() {
+        let baseline = run("turn,group,health,utilization,backlog\n\
+            0,Api,1.0,0.5,0.0\n0,Db,1.0,0.9,0.0\n0,Cache,1.0,0.9,0.0\n\
+            1,Api,1.0,0.5,0.0\n1,Db,0.4,1.0,20.0\n1,Cache,0.5,1.0,0.0\n\
+            2,Api,1.0,0.5,0.0\n2,Db,0.0,0.0,40.0\n2,Cache,0.5,1.0,0.0\n");
+        let variant = run("turn,group,health,utilization,backlog\n\
+            0,Api,1.0,0.5,0.0\n0,Db,1.0,0.9,0.0\n0,Cache,1.0,0.9,0.0\n\
+            1,Api,1.0,0.4,0.0\n1,Db,0.7,1.0,5.0\n1,Cache,0.5,1.0,0.0\n\
+            2,Api,1.0,0.4,0.0\n2,Db,0.6,0.9,0.0\n2,Cache,0.0,0.0,10.0\n");
+
+        let comparison = compare_runs(&baseline, &variant);
+
+        assert_eq!(Some(1), comparison.diverged_at());
+        assert_eq!(Some(2), comparison.last_turn());
+        let groups = comparison.groups();
+        assert_eq!(Outcome::Unchanged, *groups[0].outcome());
+        assert_relative_eq!(-0.1, groups[0].utilization_diff(), epsilon = 1e-9);
+        assert_eq!(Outcome::Saved, *groups[1].outcome());
+        assert_relative_eq!(0.6, groups[1].health_diff());
+        assert_relative_eq!(-40.0, groups[1].backlog_diff());
+        assert_relative_eq!(0.6, groups[1].max_health_gap());
+        assert_eq!(Outcome::Lost, *groups[2].outcome());
+    }
+
+    #[test]
+    fn test_aligns_by_turn_and_group_name() {
+        let baseline = run("turn,group,health,utilization,backlog\n\
+            0,Api,1.0,0.5,0.0\n0,Db,1.0,0.9,0.0\n\
+            1,Api,1.0,0.5,0.0\n1,Db,0.9,1.0,0.0\n\
+            2,Api,1.0,0.5,0.0\n2,Db,0.8,1.0,0.0\n");
+        let variant = run("turn,group,health,utilization,backlog\n\
+            1,Db,0.9,1.0,0.0\n1,Api,1.0,0.5,0.0\n");
+
+        let comparison = compare_runs(&baseline, &variant);
+
+        assert_eq!(None, comparison.diverged_at());
+        assert_eq!(Some(1), comparison.last_turn());
+        assert_eq!("Api", comparison.groups()[0].name());
+        assert_relative_eq!(0.0, comparison.groups()[1].max_health_gap());
+    }
+
+    #[test]
+    fn test_spend_over_aligned_turns() {
+        let baseline = run("turn,group,health,utilization,backlog,cost\n\
+            0,Api,1.0,0.5,0.0,2.0\n0,Db,1.0,0.9,0.0,1.0\n\
+            1,Api,1.0,0.5,0.0,2.0\n1,Db,0.0,0.0,0.0,1.0\n");
+        let variant = run("turn,group,health,utilization,backlog,cost\n\
+            0,Api,1.0,0.5,0.0,2.0\n0,Db,1.0,0.9,0.0,1.5\n\
+            1,Api,1.0,0.5,0.0,2.0\n1,Db,0.9,0.6,0.0,1.5\n");
+
+        let comparison = compare_runs(&baseline, &variant);
+
+        assert_eq!(Some(0), comparison.diverged_at());
+        let groups = comparison.groups();
+        assert_relative_eq!(0.0, groups[0].spend_diff());
+        assert_relative_eq!(2.0, groups[1].baseline_spend());
+        assert_relative_eq!(3.0, groups[1].variant_spend());
+        assert_eq!(Outcome::Saved, *groups[1].outcome());
+        assert_relative_eq!(1.0, comparison.spend_diff());
+    }
+}
@@ -0,0 +1,390 @@
+use crate::analysis::bottleneck::edge_shares;
+use crate::graph::graph::Graph;
+use crate::graph::node::NodeId;
+
+const MAX_ITERATIONS: usize = 10_000;
+const TOLERANCE: f64 = 1e-9;
+
+pub struct FeedbackLoop {
+    nodes: Vec<NodeId>,
+    loop_gain: f64,
+    cycle: Vec<NodeId>,
+    cycle_gain: f64,
+}
+
+impl FeedbackLoop {
+    pub fn nodes(&self) -> &[NodeId] {
+        &self.nodes
+    }
+
+    /// growth factor per turn of load circulating inside the component
+    pub fn loop_gain(&self) -> f64 {
+        self.loop_gain
+    }
+
+    /// cycle with the highest gain per hop, first node repeated at the end
+    pub fn cycle(&self) -> &[NodeId] {
+        &self.cycle
+    }
+
+    /// load returned to the start of `cycle` per 1 rps sent around it
+    pub fn cycle_gain(&self) -> f64 {
+        self.cycle_gain
+    }
+
+    pub fn is_unstable(&self) -> bool {
+        is_amplifying(self.loop_gain)
+    }
+}
+
+/// whether load circulating with `loop_gain` per turn grows without bound
+pub fn is_amplifying(loop_gain: f64) -> bool {
+    loop_gain >= 1.0 - TOLERANCE
+}
+
+/// whether the strongly connected component `nodes` has a cycle, possibly a self-loop
+pub fn is_cyclic(graph: &Graph, nodes: &[NodeId]) -> bool {
+    nodes.len() > 1
+        || graph
+            .outgoing(nodes[0])
+            .iter()
+            .any(|e_id| graph.edge_by_id(*e_id).to() == nodes[0])
+}
+
+/// Growth factor per turn of load circulating inside the strongly connected component `nodes`.
+pub fn component_gain(graph: &Graph, shares: &[f64], nodes: &[NodeId]) -> f64 {
+    let mut in_scc = vec![false; graph.node_count()];
+    nodes.iter().for_each(|id| in_scc[id.index()] = true);
+    spectral_radius(graph, shares, nodes, &in_scc)
+}
+
+/// Tarjan's algorithm, iterative so that long dependency chains do not overflow the stack.
+pub fn strongly_connected_components(graph: &Graph) -> Vec<Vec<NodeId>> {
+    let n = graph.node_count();
+    let mut index: Vec<Option<usize>> = vec![None; n];
+    let mut low = vec![0; n];
+    let mut on_stack = vec![false; n];
+    let mut stack = Vec::new();
+    let mut components = Vec::new();
+    let mut next_index = 0;
+
+    for root in 0..n {
+        if index[root].is_some() {
+            continue;
+        }
+        index[root] = Some(next_index);
+        low[root] = next_index;
+        next_index += 1;
+        stack.push(root);
+        on_stack[root] = true;
+
+        let mut call_stack = vec![(root, 0)];
+        while let Some(&(v, pos)) = call_stack.last() {
+            let outgoing = graph.outgoing(NodeId(v));
+            if pos < outgoing.len() {
+                call_stack.last_mut().unwrap().1 += 1;
+                let w = graph.edge_by_id(outgoing[pos]).to().index();
+                match index[w] {
+                    None => {
+                        index[w] = Some(next_index);
+                        low[w] = next_index;
+                        next_index += 1;
+                        stack.push(w);
+                        on_stack[w] = true;
+                        call_stack.push((w, 0));
+                    }
+                    Some(w_index) if on_stack[w] => low[v] = low[v].min(w_index),
+                    Some(_) => {}
+                }
+                continue;
+            }
+
+            call_stack.pop();
+            if let Some(&(parent, _)) = call_stack.last() {
+                low[parent] = low[parent].min(low[v]);
+            }
+            if Some(low[v]) == index[v] {
+                let mut component = Vec::new();
+                while let Some(w) = stack.pop() {
+                    on_stack[w] = false;
+                    component.push(NodeId(w));
+                    if w == v {
+                        break;
+                    }
+                }
+                component.sort_by_key(|id| id.index());
+                components.push(component);
+            }
+        }
+    }
+    components
+}
+
+/// Perron root of the component's share matrix, via power iteration on `A + I` which is
+/// primitive even when the cycles in the component share a common period.
+fn spectral_radius(graph: &Graph, shares: &[f64], nodes: &[NodeId], in_scc: &[bool]) -> f64 {
+    let mut x = vec![0.0; graph.node_count()];
+    nodes
+        .iter()
+        .for_each(|id| x[id.index()] = 1.0 / nodes.len() as f64);
+
+    let mut radius = 0.0;
+    for _ in 0..MAX_ITERATIONS {
+        let mut next = x.clone();
+        nodes
+            .iter()
+            .flat_map(|id| graph.outgoing(*id))
+            .map(|e_id| graph.edge_by_id(*e_id))
+            .filter(|e| in_scc[e.to().index()])
+            .for_each(|e| next[e.to().index()] += x[e.from().index()] * shares[e.id().index()]);
+
+        let norm = nodes.iter().map(|id| next[id.index()]).sum::<f64>();
+        nodes.iter().for_each(|id| next[id.index()] /= norm);
+        let converged = (norm - 1.0 - radius).abs() <= TOLERANCE;
+        radius = norm - 1.0;
+        x = next;
+        if converged {
+            break;
+        }
+    }
+    radius
+}
+
+/// Cycle with the highest gain per hop (Karp's maximum mean cycle over `ln(share)`), first
+/// node repeated at the end. `None` when every cycle passes a gain-0 node or a weight-0 edge.
+fn strongest_cycle(
+    graph: &Graph,
+    shares: &[f64],
+    nodes: &[NodeId],
+    in_scc: &[bool],
+) -> Option<Vec<NodeId>> {
+    let n = nodes.len();
+    let mut position = vec![usize::MAX; graph.node_count()];
+    nodes
+        .iter()
+        .enumerate()
+        .for_each(|(i, id)| position[id.index()] = i);
+
+    let edges = nodes
+        .iter()
+        .flat_map(|id| graph.outgoing(*id))
+        .map(|e_id| graph.edge_by_id(*e_id))
+        .filter(|e| in_scc[e.to().index()] && shares[e.id().index()] > 0.0)
+        .map(|e| {
+            (
+                position[e.from().index()],
+                position[e.to().index()],
+                shares[e.id().index()].ln(),
+            )
+        })
+        .collect::<Vec<(usize, usize, f64)>>();
+
+    let mut best = vec![vec![f64::NEG_INFINITY; n]; n + 1];
+    let mut pred = vec![vec![usize::MAX; n]; n + 1];
+    best[0].iter_mut().for_each(|d| *d = 0.0);
+    for k in 1..=n {
+        for (from, to, w) in &edges {
+            if best[k - 1][*from] + w > best[k][*to] {
+                best[k][*to] = best[k - 1][*from] + w;
+                pred[k][*to] = *from;
+            }
+        }
+    }
+
+    let end = (0..n)
+        .filter(|v| best[n][*v].is_finite())
+        .map(|v| {
+            let mean = (0..n)
+                .filter(|k| best[*k][v].is_finite())
+                .map(|k| (best[n][v] - best[k][v]) / (n - k) as f64)
+                .fold(f64::INFINITY, f64::min);
+            (v, mean)
+        })
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(v, _)| v)?;
+
+    let mut walk = vec![end];
+    let mut current = end;
+    for k in (1..=n).rev() {
+        current = pred[k][current];
+        walk.push(current);
+    }
+    walk.reverse();
+
+    let mut last_seen = vec![usize::MAX; n];
+    let mut cycle: Vec<usize> = Vec::new();
+    let mut cycle_mean = f64::NEG_INFINITY;
+    for (i, v) in walk.iter().enumerate() {
+        if last_seen[*v] != usize::MAX {
+            let candidate = walk[last_seen[*v]..=i].to_vec();
+            let mean = candidate
+                .windows(2)
+                .map(|w| {
+                    edges
+                        .iter()
+                        .filter(|(from, to, _)| *from == w[0] && *to == w[1])
+                        .map(|(_, _, w)| *w)
+                        .fold(f64::NEG_INFINITY, f64::max)
+                })
+                .sum::<f64>()
+                / (candidate.len() - 1) as f64;
+            if mean > cycle_mean {
+                cycle_mean = mean;
+                cycle = candidate;
+            }
+        }
+        last_seen[*v] = i;
+    }
+    Some(cycle.into_iter().map(|p| nodes[p]).collect())
+}
+
+fn cycle_gain(graph: &Graph, shares: &[f64], cycle: &[NodeId]) -> f64 {
+    cycle
+        .windows(2)
+        .map(|w| {
+            graph
+                .outgoing(w[0])
+                .iter()
+                .filter(|e_id| graph.edge_by_id(**e_id).to() == w[1])
+                .map(|e_id| shares[e_id.index()])
+                .sum::<f64>()
+        })
+        .product()
+}
+
+/// Components that can route load back into themselves, most amplifying first. Components
+/// whose cycles all carry no load, through a gain-0 node or a weight-0 edge, are left out.
+pub fn find_feedback_loops(graph: &Graph) -> Vec<FeedbackLoop> {
+    let shares = edge_shares(graph);
+    let mut in_scc = vec![false; graph.node_count()];
+
+    let mut loops = strongly_connected_components(graph)
+        .into_iter()
+        .filter(|nodes| is_cyclic(graph, nodes))
+        .filter_map(|nodes| {
+            nodes.iter().for_each(|id| in_scc[id.index()] = true);
+            let loop_gain = spectral_radius(graph, &shares, &nodes, &in_scc);
+            let cycle = strongest_cycle(graph, &shares, &nodes, &in_scc);
+            nodes.iter().for_each(|id| in_scc[id.index()] = false);
+            let cycle = cycle?;
+            let cycle_gain = cycle_gain(graph, &shares, &cycle);
+            Some(FeedbackLoop {
+                nodes,
+                loop_gain,
+                cycle,
+                cycle_gain,
+            })
+        })
+        .collect::<Vec<FeedbackLoop>>();
+    loops.sort_by(|a, b| b.loop_gain.total_cmp(&a.loop_gain));
+    loops
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::edge::{Edge, EdgeId};
+    use crate::graph::node::Node;
+    use approx::assert_relative_eq;
+
+    // api0  ← api1    api2
+    //     ↘    ↓     ↙
+    //          db0
+    //       ↙   ↻
+    //   api3
+    #[test]
+    fn test_self_loop_is_stable_feedback() {
+        let api0 = Node::new(NodeId(0), "api0".to_string(), 100.0, 1.0);
+        let api1 = Node::new(NodeId(1), "api1".to_string(), 100.0, 1.0);
+        let api2 = Node::new(NodeId(2), "api2".to_string(), 100.0, 1.0);
+        let api3 = Node::new(NodeId(3), "api3".to_string(), 100.0, 1.0);
+        let db0 = Node::new(NodeId(4), "db0".to_string(), 100.0, 1.0);
+
+        let link1 = Edge::new(EdgeId(0), NodeId(0), NodeId(4), 1.0);
+        let link2 = Edge::new(EdgeId(1), NodeId(1), NodeId(4), 1.0);
+        let link3 = Edge::new(EdgeId(2), NodeId(2), NodeId(4), 2.0);
+        let link4 = Edge::new(EdgeId(3), NodeId(4), NodeId(3), 2.0);
+        let link5 = Edge::new(EdgeId(4), NodeId(4), NodeId(4), 1.0);
+        let link6 = Edge::new(EdgeId(5), NodeId(1), NodeId(0), 1.0);
+
+        let graph = Graph::new(
+            vec![api0, api1, api2, api3, db0],
+            vec![link1, link2, link3, link4, link5, link6],
+        );
+
+        assert_eq!(5, strongly_connected_components(&graph).len());
+
+        let loops = find_feedback_loops(&graph);
+        assert_eq!(1, loops.len());
+        assert_eq!(4, loops[0].nodes()[0].index());
+        assert_relative_eq!(1.0 / 3.0, loops[0].loop_gain(), epsilon = 1e-6);
+        assert_relative_eq!(1.0 / 3.0, loops[0].cycle_gain(), epsilon = 1e-6);
+        assert!(!loops[0].is_unstable());
+    }
+
+    //  api → worker ⇄ orders → db
+    //          ↑        |
+    //          └─ cache ┘
+    #[test]
+    fn test_amplifying_loop_is_unstable() {
+        let api = Node::new(NodeId(0), "api".to_string(), 100.0, 1.0);
+        let worker = Node::new(NodeId(1), "worker".to_string(), 100.0, 1.6);
+        let orders = Node::new(NodeId(2), "orders".to_string(), 100.0, 1.4);
+        let db = Node::new(NodeId(3), "db".to_string(), 100.0, 0.0);
+        let cache = Node::new(NodeId(4), "cache".to_string(), 100.0, 0.5);
+
+        let link1 = Edge::new(EdgeId(0), NodeId(0), NodeId(1), 1.0);
+        let link2 = Edge::new(EdgeId(1), NodeId(1), NodeId(2), 1.0);
+        let link3 = Edge::new(EdgeId(2), NodeId(2), NodeId(1), 3.0);
+        let link4 = Edge::new(EdgeId(3), NodeId(2), NodeId(3), 1.0);
+        let link5 = Edge::new(EdgeId(4), NodeId(2), NodeId(4), 1.0);
+        let link6 = Edge::new(EdgeId(5), NodeId(4), NodeId(1), 1.0);
+
+        let graph = Graph::new(
+            vec![api, worker, orders, db, cache],
+            vec![link1, link2, link3, link4, link5, link6],
+        );
+
+        let loops = find_feedback_loops(&graph);
+        assert_eq!(1, loops.len());
+        assert_eq!(3, loops[0].nodes().len());
+        assert!(loops[0].is_unstable());
+
+        // worker → orders → worker returns 1.6 * 1.4 * 3/5, the detour via cache adds
+        // 1.6 * 1.4 * 1/5 * 0.5 over three hops
+        let cycle = loops[0]
+            .cycle()
+            .iter()
+            .map(|id| id.index())
+            .collect::<Vec<_>>();
+        assert_eq!(vec![1, 2, 1], cycle);
+        assert_relative_eq!(1.6 * 1.4 * 0.6, loops[0].cycle_gain(), epsilon = 1e-9);
+        assert!(loops[0].loop_gain() > (1.6 * 1.4 * 0.6_f64).sqrt());
+    }
+
+    #[test]
+    fn test_acyclic_graph_has_no_loops() {
+        let api = Node::new(NodeId(0), "api".to_string(), 100.0, 2.0);
+        let db = Node::new(NodeId(1), "db".to_string(), 60.0, 0.0);
+        let link = Edge::new(EdgeId(0), NodeId(0), NodeId(1), 1.0);
+
+        let graph = Graph::new(vec![api, db], vec![link]);
+
+        assert_eq!(2, strongly_connected_components(&graph).len());
+        assert!(find_feedback_loops(&graph).is_empty());
+    }
+
+    // a → db → a with nothing coming back out of db
+    #[test]
+    fn test_loop_through_zero_gain_node_carries_no_load() {
+        let a = Node::new(NodeId(0), "a".to_string(), 100.0, 1.0);
+        let db = Node::new(NodeId(1), "db".to_string(), 60.0, 0.0);
+        let link1 = Edge::new(EdgeId(0), NodeId(0), NodeId(1), 1.0);
+        let link2 = Edge::new(EdgeId(1), NodeId(1), NodeId(0), 1.0);
+
+        let graph = Graph::new(vec![a, db], vec![link1, link2]);
+
+        assert_eq!(1, strongly_connected_components(&graph).len());
+        assert!(find_feedback_loops(&graph).is_empty());
+    }
+}
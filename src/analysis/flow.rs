@@ -0,0 +1,163 @@
+use crate::analysis::groups::GroupSet;
+use crate::graph::graph::Graph;
+use crate::state::snapshot::Snapshot;
+use std::io;
+use std::io::Write;
+
+/// Load each group pushes into each group in `snapshot`, indexed `[from][to]`. Edges within a
+/// group show up on the diagonal.
+pub fn flow_matrix(graph: &Graph, group_set: &GroupSet, snapshot: &Snapshot) -> Vec<Vec<f64>> {
+    let n = group_set.groups().len();
+    let mut matrix = vec![vec![0.0; n]; n];
+    graph.edges().iter().for_each(|edge| {
+        let load = snapshot.edge_load(edge.id(), graph);
+        if load > 0.0 {
+            let from = group_set.group_by_node_id(edge.from().index());
+            let to = group_set.group_by_node_id(edge.to().index());
+            matrix[from][to] += load;
+        }
+    });
+    matrix
+}
+
+pub struct TurnFlows {
+    turn: usize,
+    matrix: Vec<Vec<f64>>,
+}
+
+impl TurnFlows {
+    pub fn turn(&self) -> usize {
+        self.turn
+    }
+
+    /// indexed `[from][to]` like `FlowSeries::groups`
+    pub fn matrix(&self) -> &[Vec<f64>] {
+        &self.matrix
+    }
+}
+
+/// Group-to-group flow matrix for every turn of a run.
+pub struct FlowSeries {
+    groups: Vec<String>,
+    turns: Vec<TurnFlows>,
+}
+
+impl FlowSeries {
+    pub fn from_snapshots(graph: &Graph, group_set: &GroupSet, snapshots: &[&Snapshot]) -> Self {
+        Self {
+            groups: group_set
+                .groups()
+                .iter()
+                .map(|g| g.name().to_string())
+                .collect(),
+            turns: snapshots
+                .iter()
+                .map(|s| TurnFlows {
+                    turn: s.turn(),
+                    matrix: flow_matrix(graph, group_set, s),
+                })
+                .collect(),
+        }
+    }
+
+    pub fn groups(&self) -> &[String] {
+        &self.groups
+    }
+
+    pub fn turns(&self) -> &[TurnFlows] {
+        &self.turns
+    }
+
+    /// One row per turn and pair of groups with load between them.
+    pub fn write_csv(&self, out: &mut impl Write) -> io::Result<()> {
+        writeln!(out, "turn,from,to,load")?;
+        for t in &self.turns {
+            for (from, row) in t.matrix.iter().enumerate() {
+                for (to, load) in row.iter().enumerate().filter(|(_, l)| **l > 0.0) {
+                    writeln!(
+                        out,
+                        "{},{},{},{}",
+                        t.turn, self.groups[from], self.groups[to], load
+                    )?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::groups::Group;
+    use crate::graph::edge::{Edge, EdgeId};
+    use crate::graph::node::{Node, NodeId};
+    use crate::simulation::modifiers::CapacityModifier;
+    use crate::state::edge_state::EdgeState;
+    use crate::state::node_state::NodeState;
+    use approx::assert_relative_eq;
+
+    // api1 → api2 → db
+    //    ↘────────↗
+    fn setup() -> (Graph, GroupSet) {
+        let api1 = Node::new(NodeId(0), "api1".to_string(), 100.0, 1.0);
+        let api2 = Node::new(NodeId(1), "api2".to_string(), 100.0, 1.0);
+        let db = Node::new(NodeId(2), "db".to_string(), 100.0, 0.0);
+
+        let graph = Graph::new(
+            vec![api1, api2, db],
+            vec![
+                Edge::new(EdgeId(0), NodeId(0), NodeId(1), 1.0),
+                Edge::new(EdgeId(1), NodeId(0), NodeId(2), 3.0),
+                Edge::new(EdgeId(2), NodeId(1), NodeId(2), 1.0),
+            ],
+        );
+        let groups = GroupSet::new(vec![
+            Group::new("Api".to_string(), vec![NodeId(0), NodeId(1)]),
+            Group::new("Db".to_string(), vec![NodeId(2)]),
+        ]);
+        (graph, groups)
+    }
+
+    fn snapshot(turn: usize, served: f64) -> Snapshot {
+        Snapshot::new(
+            turn,
+            vec![
+                NodeState::new(served, served, 0.0, 1.0),
+                NodeState::new(served / 4.0, served / 4.0, 0.0, 1.0),
+                NodeState::new(0.0, 0.0, 0.0, 1.0),
+            ],
+            vec![EdgeState::new(true); 3],
+            vec![CapacityModifier::new(); 2],
+        )
+    }
+
+    #[test]
+    fn test_matrix_includes_intra_group_edges() {
+        let (graph, groups) = setup();
+        let matrix = flow_matrix(&graph, &groups, &snapshot(1, 40.0));
+
+        assert_relative_eq!(10.0, matrix[0][0]);
+        assert_relative_eq!(40.0, matrix[0][1]);
+        assert_relative_eq!(0.0, matrix[1][0]);
+        assert_relative_eq!(0.0, matrix[1][1]);
+    }
+
+    #[test]
+    fn test_series_csv_skips_empty_pairs() {
+        let (graph, groups) = setup();
+        let snapshots = [snapshot(0, 0.0), snapshot(1, 40.0)];
+        let series =
+            FlowSeries::from_snapshots(&graph, &groups, &snapshots.iter().collect::<Vec<_>>());
+
+        assert_eq!(2, series.turns().len());
+        assert_eq!(vec!["Api", "Db"], series.groups());
+
+        let mut csv = vec![];
+        series.write_csv(&mut csv).unwrap();
+        assert_eq!(
+            "turn,from,to,load\n1,Api,Api,10\n1,Api,Db,40\n",
+            String::from_utf8(csv).unwrap()
+        );
+    }
+}
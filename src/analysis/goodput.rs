@@ -0,0 +1,213 @@
+use crate::graph::graph::Graph;
+use crate::graph::node::NodeId;
+use crate::state::snapshot::Snapshot;
+
+/// below this much load still in flight the trace stops
+const EPSILON: f64 = 1e-9;
+
+/// End-to-end fate of the load offered at the entry nodes in one turn. Every request offered
+/// ends up in exactly one of the buckets, so they add up to `offered`.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Goodput {
+    offered: f64,
+    goodput: f64,
+    lost_failed: f64,
+    lost_disabled: f64,
+    lost_backlog: f64,
+}
+
+impl Goodput {
+    /// load arriving at the entry nodes
+    pub fn offered(&self) -> f64 {
+        self.offered
+    }
+
+    /// load served at every hop down to a leaf node or one with gain 0
+    pub fn goodput(&self) -> f64 {
+        self.goodput
+    }
+
+    /// load that reached a failed node or one in an outage
+    pub fn lost_failed(&self) -> f64 {
+        self.lost_failed
+    }
+
+    /// load that reached a node whose outgoing edges are all disabled
+    pub fn lost_disabled(&self) -> f64 {
+        self.lost_disabled
+    }
+
+    /// load left queued at an overloaded node
+    pub fn lost_backlog(&self) -> f64 {
+        self.lost_backlog
+    }
+
+    /// share of the offered load that was served end to end, 1.0 when nothing was offered
+    pub fn success_rate(&self) -> f64 {
+        if self.offered == 0.0 {
+            1.0
+        } else {
+            self.goodput / self.offered
+        }
+    }
+}
+
+/// Traces the demand of the `entry_nodes` through `snapshot`. At each hop a node passes on the
+/// share of its work it served this turn, split across its enabled edges by weight; the rest
+/// is lost to the node's backlog. Load still circling a cycle when the trace stops counts as
+/// backlog too.
+pub fn goodput(graph: &Graph, snapshot: &Snapshot, entry_nodes: &[NodeId]) -> Goodput {
+    let states = snapshot.node_states();
+    let edge_states = snapshot.edge_states();
+
+    let mut flow = vec![0.0; graph.node_count()];
+    entry_nodes
+        .iter()
+        .for_each(|id| flow[id.index()] += states[id.index()].demand());
+
+    let mut result = Goodput {
+        offered: flow.iter().sum(),
+        ..Goodput::default()
+    };
+
+    // a DAG is fully traced after `node_count` hops, cycles decay geometrically
+    for _ in 0..graph.node_count() * 4 {
+        if flow.iter().sum::<f64>() <= EPSILON {
+            break;
+        }
+        let mut next = vec![0.0; graph.node_count()];
+        for (i, &load) in flow.iter().enumerate().filter(|(_, l)| **l > 0.0) {
+            let state = &states[i];
+            if !state.is_up() {
+                result.lost_failed += load;
+                continue;
+            }
+
+            let outgoing = graph.outgoing(NodeId(i));
+            let enabled = outgoing
+                .iter()
+                .filter(|e_id| edge_states[e_id.index()].is_enabled())
+                .map(|e_id| graph.edge_by_id(*e_id))
+                .collect::<Vec<_>>();
+            if !outgoing.is_empty() && enabled.is_empty() {
+                result.lost_disabled += load;
+                continue;
+            }
+
+            let total = state.served() + state.backlog();
+            let served = if total == 0.0 {
+                load
+            } else {
+                load * state.served() / total
+            };
+            result.lost_backlog += load - served;
+
+            // a node with gain 0 sends nothing downstream, so it ends the request like a leaf
+            let total_weight = enabled.iter().map(|e| e.weight()).sum::<f64>();
+            if enabled.is_empty() || total_weight == 0.0 || graph.nodes()[i].gain() == 0.0 {
+                result.goodput += served;
+                continue;
+            }
+            enabled
+                .iter()
+                .for_each(|e| next[e.to().index()] += served * e.weight() / total_weight);
+        }
+        flow = next;
+    }
+    result.lost_backlog += flow.iter().sum::<f64>();
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::edge::{Edge, EdgeId};
+    use crate::graph::node::Node;
+    use crate::simulation::modifiers::CapacityModifier;
+    use crate::state::edge_state::EdgeState;
+    use crate::state::node_state::NodeState;
+    use approx::assert_relative_eq;
+
+    // api → db1
+    //   ↘ db2
+    fn graph() -> Graph {
+        let api = Node::new(NodeId(0), "api".to_string(), 100.0, 1.0);
+        let db1 = Node::new(NodeId(1), "db1".to_string(), 40.0, 0.0);
+        let db2 = Node::new(NodeId(2), "db2".to_string(), 60.0, 0.0);
+
+        let link1 = Edge::new(EdgeId(0), NodeId(0), NodeId(1), 1.0);
+        let link2 = Edge::new(EdgeId(1), NodeId(0), NodeId(2), 1.0);
+
+        Graph::new(vec![api, db1, db2], vec![link1, link2])
+    }
+
+    fn snapshot(node_states: Vec<NodeState>, edges: [bool; 2]) -> Snapshot {
+        Snapshot::new(
+            1,
+            node_states,
+            edges.into_iter().map(EdgeState::new).collect(),
+            vec![CapacityModifier::new(); 1],
+        )
+    }
+
+    #[test]
+    fn test_backlog_and_failed_nodes_lose_load() {
+        // api serves all 80, db1 serves 40 of its 40 + 10 backlog, db2 has failed
+        let snapshot = snapshot(
+            vec![
+                NodeState::new(80.0, 80.0, 0.0, 1.0),
+                NodeState::new(40.0, 40.0, 10.0, 0.8),
+                NodeState::new(40.0, 0.0, 0.0, 0.0),
+            ],
+            [true, true],
+        );
+        let result = goodput(&graph(), &snapshot, &[NodeId(0)]);
+
+        assert_relative_eq!(80.0, result.offered());
+        assert_relative_eq!(32.0, result.goodput());
+        assert_relative_eq!(8.0, result.lost_backlog());
+        assert_relative_eq!(40.0, result.lost_failed());
+        assert_relative_eq!(0.0, result.lost_disabled());
+        assert_relative_eq!(0.4, result.success_rate());
+    }
+
+    #[test]
+    fn test_disabled_edges() {
+        let healthy = || {
+            vec![
+                NodeState::new(80.0, 80.0, 0.0, 1.0),
+                NodeState::new(40.0, 40.0, 0.0, 1.0),
+                NodeState::new(40.0, 40.0, 0.0, 1.0),
+            ]
+        };
+
+        // the remaining edge takes all the load
+        let result = goodput(&graph(), &snapshot(healthy(), [false, true]), &[NodeId(0)]);
+        assert_relative_eq!(1.0, result.success_rate());
+
+        let result = goodput(&graph(), &snapshot(healthy(), [false, false]), &[NodeId(0)]);
+        assert_relative_eq!(80.0, result.lost_disabled());
+        assert_relative_eq!(0.0, result.success_rate());
+    }
+
+    #[test]
+    fn test_zero_gain_node_ends_the_trace() {
+        // api calls nothing with gain 0, so the failed db2 behind it loses no load
+        let mut graph = graph();
+        graph.node_by_id_mut(NodeId(0)).set_gain(0.0);
+        let snapshot = snapshot(
+            vec![
+                NodeState::new(80.0, 80.0, 0.0, 1.0),
+                NodeState::new(0.0, 0.0, 0.0, 1.0),
+                NodeState::new(0.0, 0.0, 0.0, 0.0),
+            ],
+            [true, true],
+        );
+        let result = goodput(&graph, &snapshot, &[NodeId(0)]);
+
+        assert_relative_eq!(80.0, result.goodput());
+        assert_relative_eq!(0.0, result.lost_failed());
+        assert_relative_eq!(1.0, result.success_rate());
+    }
+}
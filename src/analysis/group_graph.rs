@@ -0,0 +1,133 @@
+use crate::analysis::groups::GroupSet;
+use crate::graph::graph::Graph;
+use crate::graph::node::NodeId;
+use std::collections::VecDeque;
+
+/// The topology at group level: a link wherever a node of one group calls a node of another.
+pub struct GroupGraph {
+    /// indexed `[from][to]`, calls within a group are left out
+    linked: Vec<Vec<bool>>,
+    layers: Vec<Vec<usize>>,
+}
+
+impl GroupGraph {
+    /// Layers the groups by their hops from the groups of `entry_nodes`.
+    pub fn new(graph: &Graph, group_set: &GroupSet, entry_nodes: &[NodeId]) -> Self {
+        let n = group_set.groups().len();
+        let mut linked = vec![vec![false; n]; n];
+        graph.edges().iter().for_each(|edge| {
+            let from = group_set.group_by_node_id(edge.from().index());
+            let to = group_set.group_by_node_id(edge.to().index());
+            if from != to {
+                linked[from][to] = true;
+            }
+        });
+
+        let mut hops = vec![None; n];
+        let mut queue = VecDeque::new();
+        for g_id in group_set.groups_of(entry_nodes) {
+            hops[g_id] = Some(0);
+            queue.push_back(g_id);
+        }
+        while let Some(from) = queue.pop_front() {
+            for to in 0..n {
+                if linked[from][to] && hops[to].is_none() {
+                    hops[to] = hops[from].map(|h| h + 1);
+                    queue.push_back(to);
+                }
+            }
+        }
+
+        let unreachable = hops.iter().flatten().max().map_or(0, |h| h + 1);
+        let mut layers = vec![vec![]; unreachable + 1];
+        hops.iter()
+            .enumerate()
+            .for_each(|(g_id, h)| layers[h.unwrap_or(unreachable)].push(g_id));
+        layers.retain(|layer| !layer.is_empty());
+
+        Self { linked, layers }
+    }
+
+    /// every `(from, to)` link, in group order
+    pub fn edges(&self) -> Vec<(usize, usize)> {
+        (0..self.linked.len())
+            .flat_map(|from| (0..self.linked.len()).map(move |to| (from, to)))
+            .filter(|(from, to)| self.linked[*from][*to])
+            .collect()
+    }
+
+    /// groups calling into `group_id`
+    pub fn upstream(&self, group_id: usize) -> Vec<usize> {
+        (0..self.linked.len())
+            .filter(|from| self.linked[*from][group_id])
+            .collect()
+    }
+
+    /// groups `group_id` calls into
+    pub fn downstream(&self, group_id: usize) -> Vec<usize> {
+        (0..self.linked.len())
+            .filter(|to| self.linked[group_id][*to])
+            .collect()
+    }
+
+    /// Groups by hops from the entry groups, in group order within a layer. Groups no entry
+    /// traffic reaches come last.
+    pub fn layers(&self) -> &[Vec<usize>] {
+        &self.layers
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::groups::Group;
+    use crate::graph::edge::{Edge, EdgeId};
+    use crate::graph::node::Node;
+
+    // api → auth, api → db, auth → db, db → api, and a batch job nobody calls
+    fn setup() -> (Graph, GroupSet) {
+        let nodes = ["api-1", "api-2", "auth", "db", "batch"]
+            .iter()
+            .enumerate()
+            .map(|(i, name)| Node::new(NodeId(i), name.to_string(), 100.0, 1.0))
+            .collect();
+        let edges = [(0, 1), (0, 2), (1, 3), (2, 3), (3, 0), (4, 3)]
+            .iter()
+            .enumerate()
+            .map(|(i, (from, to))| Edge::new(EdgeId(i), NodeId(*from), NodeId(*to), 1.0))
+            .collect();
+        let groups = GroupSet::new(vec![
+            Group::new("Api".to_string(), vec![NodeId(0), NodeId(1)]),
+            Group::new("Auth".to_string(), vec![NodeId(2)]),
+            Group::new("Db".to_string(), vec![NodeId(3)]),
+            Group::new("Batch".to_string(), vec![NodeId(4)]),
+        ]);
+        (Graph::new(nodes, edges), groups)
+    }
+
+    #[test]
+    fn test_links_between_groups() {
+        let (graph, groups) = setup();
+        let group_graph = GroupGraph::new(&graph, &groups, &[NodeId(0)]);
+
+        // api-1 → api-2 stays within the group
+        assert_eq!(
+            vec![(0, 1), (0, 2), (1, 2), (2, 0), (3, 2)],
+            group_graph.edges()
+        );
+        assert_eq!(vec![0, 1, 3], group_graph.upstream(2));
+        assert_eq!(vec![0], group_graph.downstream(2));
+        assert!(group_graph.upstream(3).is_empty());
+    }
+
+    #[test]
+    fn test_layers_by_hops_from_entry() {
+        let (graph, groups) = setup();
+        let group_graph = GroupGraph::new(&graph, &groups, &[NodeId(0)]);
+        // db → api goes back up, batch is never reached
+        assert_eq!(&[vec![0], vec![1, 2], vec![3]], group_graph.layers());
+
+        let group_graph = GroupGraph::new(&graph, &groups, &[NodeId(2), NodeId(4)]);
+        assert_eq!(&[vec![1, 3], vec![2], vec![0]], group_graph.layers());
+    }
+}
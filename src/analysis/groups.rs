@@ -47,6 +47,24 @@ impl GroupSet {
     pub fn group_by_node_id(&self, node_id: usize) -> usize {
         self.node_to_group[node_id]
     }
+
+    /// case-insensitive lookup
+    pub fn group_by_name(&self, name: &str) -> Option<usize> {
+        self.groups
+            .iter()
+            .position(|g| g.name().eq_ignore_ascii_case(name))
+    }
+
+    /// distinct groups the given nodes belong to, in group order
+    pub fn groups_of(&self, nodes: &[NodeId]) -> Vec<usize> {
+        let mut group_ids = nodes
+            .iter()
+            .map(|id| self.group_by_node_id(id.index()))
+            .collect::<Vec<usize>>();
+        group_ids.sort();
+        group_ids.dedup();
+        group_ids
+    }
 }
 
 #[derive(Debug, PartialEq)]
@@ -64,6 +82,17 @@ pub enum GroupHealth {
     Failed,
 }
 
+impl GroupHealth {
+    pub fn classify(health: f64) -> Self {
+        match health {
+            n if n > 0.8 => GroupHealth::Ok,
+            n if n > 0.3 => GroupHealth::Degraded,
+            n if n > 0.0 => GroupHealth::Critical,
+            _ => GroupHealth::Failed,
+        }
+    }
+}
+
 impl Display for GroupHealth {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         let s = match self {
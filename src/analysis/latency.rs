@@ -0,0 +1,275 @@
+use crate::analysis::calibration::solve;
+use crate::analysis::cycles::{
+    component_gain, is_amplifying, is_cyclic, strongly_connected_components,
+};
+use crate::analysis::groups::GroupSet;
+use crate::graph::edge::EdgeId;
+use crate::graph::graph::Graph;
+use crate::graph::node::NodeId;
+use crate::state::snapshot::Snapshot;
+
+/// length of a turn in ms; loads are per second, so a turn is one second
+const TURN_MS: f64 = 1000.0;
+
+/// Latency of each node in ms this turn: its service time plus the time to work off its backlog
+/// at the current capacity. `None` for nodes that are down, they don't answer at all.
+pub fn node_latencies(graph: &Graph, groups: &GroupSet, snapshot: &Snapshot) -> Vec<Option<f64>> {
+    snapshot
+        .node_states()
+        .iter()
+        .enumerate()
+        .map(|(i, state)| {
+            if !state.is_up() {
+                return None;
+            }
+            let node = graph.node_by_id(NodeId(i));
+            let capacity =
+                node.capacity() * snapshot.capacity_mod(groups.group_by_node_id(i)).factor();
+            let queue_wait = if state.backlog() == 0.0 {
+                0.0
+            } else if capacity > 0.0 {
+                state.backlog() / capacity * TURN_MS
+            } else {
+                f64::INFINITY
+            };
+            Some(node.service_time() + queue_wait)
+        })
+        .collect()
+}
+
+/// Calls each node makes down each edge per request it serves: its gain split by weight over the
+/// enabled edges, as the engine forwards load. Calls to a node that is down fail at once and
+/// count as none.
+fn calls_per_request(graph: &Graph, snapshot: &Snapshot, latencies: &[Option<f64>]) -> Vec<f64> {
+    let enabled = |e_id: EdgeId| snapshot.edge_states()[e_id.index()].is_enabled();
+    graph
+        .edges()
+        .iter()
+        .map(|e| {
+            let total_weight = graph
+                .outgoing(e.from())
+                .iter()
+                .filter(|e_id| enabled(**e_id))
+                .map(|e_id| graph.edge_by_id(*e_id).weight())
+                .sum::<f64>();
+            if !enabled(e.id()) || latencies[e.to().index()].is_none() || total_weight == 0.0 {
+                0.0
+            } else {
+                graph.node_by_id(e.from()).gain() * e.weight() / total_weight
+            }
+        })
+        .collect()
+}
+
+/// Mean latency of a request entering at each node: its own latency plus, for every call it
+/// makes, the edge latency and the latency of the callee. Calls around a loop add up as a
+/// geometric series, so a loop making one call or more per call never returns, and neither
+/// does anything calling into it or into a node with an unbounded queue. `None` for nodes
+/// that are down.
+fn path_latencies(
+    graph: &Graph,
+    snapshot: &Snapshot,
+    latencies: &[Option<f64>],
+) -> Vec<Option<f64>> {
+    let calls = calls_per_request(graph, snapshot, latencies);
+    let mut unbounded = latencies
+        .iter()
+        .map(|l| l.is_some_and(f64::is_infinite))
+        .collect::<Vec<bool>>();
+    strongly_connected_components(graph)
+        .iter()
+        .filter(|nodes| is_cyclic(graph, nodes))
+        .filter(|nodes| is_amplifying(component_gain(graph, &calls, nodes)))
+        .flatten()
+        .for_each(|id| unbounded[id.index()] = true);
+    let mut queue = (0..graph.node_count())
+        .filter(|i| unbounded[*i])
+        .map(NodeId)
+        .collect::<Vec<NodeId>>();
+    while let Some(id) = queue.pop() {
+        for e_id in graph.incoming(id) {
+            let from = graph.edge_by_id(*e_id).from();
+            if calls[e_id.index()] > 0.0 && !unbounded[from.index()] {
+                unbounded[from.index()] = true;
+                queue.push(from);
+            }
+        }
+    }
+
+    // (I - calls) x = own latency plus the latency of the edges taken
+    let bounded = (0..graph.node_count())
+        .filter(|i| latencies[*i].is_some() && !unbounded[*i])
+        .collect::<Vec<usize>>();
+    let mut position = vec![None; graph.node_count()];
+    bounded
+        .iter()
+        .enumerate()
+        .for_each(|(pos, i)| position[*i] = Some(pos));
+    let mut a = (0..bounded.len())
+        .map(|row| {
+            (0..bounded.len())
+                .map(|col| f64::from(row == col))
+                .collect()
+        })
+        .collect::<Vec<Vec<f64>>>();
+    let mut b = bounded
+        .iter()
+        .map(|i| latencies[*i].unwrap_or_default())
+        .collect::<Vec<f64>>();
+    graph.edges().iter().for_each(|e| {
+        if let (Some(from), Some(to)) = (position[e.from().index()], position[e.to().index()]) {
+            a[from][to] -= calls[e.id().index()];
+            b[from] += calls[e.id().index()] * e.latency();
+        }
+    });
+    // singular only when a loop makes within rounding of one call per call
+    let x = solve(a, b).unwrap_or_else(|| vec![f64::INFINITY; bounded.len()]);
+
+    let mut path = latencies
+        .iter()
+        .map(|l| l.map(|_| f64::INFINITY))
+        .collect::<Vec<Option<f64>>>();
+    bounded.iter().zip(x).for_each(|(i, l)| path[*i] = Some(l));
+    path
+}
+
+/// Mean end-to-end latency in ms of requests entering at `entry_nodes`, weighted by each entry
+/// node's demand. A mean, not a percentile: a request that waits behind a backlog counts with
+/// the whole wait. `None` when no entry node is up.
+pub fn mean_latency(
+    graph: &Graph,
+    snapshot: &Snapshot,
+    latencies: &[Option<f64>],
+    entry_nodes: &[NodeId],
+) -> Option<f64> {
+    let path = path_latencies(graph, snapshot, latencies);
+
+    let (sum, weight) = entry_nodes
+        .iter()
+        .filter_map(|id| {
+            let latency = path[id.index()]?;
+            // without any demand yet every entry node counts the same
+            let demand = snapshot.node_states()[id.index()]
+                .demand()
+                .max(f64::MIN_POSITIVE);
+            Some((latency * demand, demand))
+        })
+        .fold((0.0, 0.0), |acc, (l, d)| (acc.0 + l, acc.1 + d));
+
+    if weight == 0.0 {
+        None
+    } else {
+        Some(sum / weight)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::groups::Group;
+    use crate::graph::edge::Edge;
+    use crate::graph::node::Node;
+    use crate::simulation::modifiers::CapacityModifier;
+    use crate::state::edge_state::EdgeState;
+    use crate::state::node_state::NodeState;
+    use approx::assert_relative_eq;
+
+    // api → db1
+    //   ↘ db2 (weight 3)
+    fn graph() -> (Graph, GroupSet) {
+        let api = Node::new(NodeId(0), "api".to_string(), 100.0, 1.0).with_service_time(5.0);
+        let db1 = Node::new(NodeId(1), "db1".to_string(), 50.0, 0.0).with_service_time(20.0);
+        let db2 = Node::new(NodeId(2), "db2".to_string(), 50.0, 0.0).with_service_time(40.0);
+
+        let link1 = Edge::new(EdgeId(0), NodeId(0), NodeId(1), 1.0).with_latency(2.0);
+        let link2 = Edge::new(EdgeId(1), NodeId(0), NodeId(2), 3.0).with_latency(2.0);
+
+        let graph = Graph::new(vec![api, db1, db2], vec![link1, link2]);
+        let groups = GroupSet::new(vec![Group::new(
+            "group1".to_string(),
+            vec![NodeId(0), NodeId(1), NodeId(2)],
+        )]);
+        (graph, groups)
+    }
+
+    fn snapshot(node_states: Vec<NodeState>) -> Snapshot {
+        Snapshot::new(
+            1,
+            node_states,
+            vec![EdgeState::new(true); 2],
+            vec![CapacityModifier::new(); 1],
+        )
+    }
+
+    #[test]
+    fn test_backlog_adds_queue_wait() {
+        let (graph, groups) = graph();
+        let snapshot = snapshot(vec![
+            NodeState::new(80.0, 80.0, 0.0, 1.0),
+            NodeState::new(20.0, 20.0, 0.0, 1.0),
+            NodeState::new(60.0, 50.0, 10.0, 0.9),
+        ]);
+
+        let latencies = node_latencies(&graph, &groups, &snapshot);
+        assert_relative_eq!(5.0, latencies[0].unwrap());
+        assert_relative_eq!(20.0, latencies[1].unwrap());
+        // 10 queued at 50 rps is a fifth of a second
+        assert_relative_eq!(240.0, latencies[2].unwrap());
+
+        // 5 + (1 * (2 + 20) + 3 * (2 + 240)) / 4
+        let e2e = mean_latency(&graph, &snapshot, &latencies, &[NodeId(0)]);
+        assert_relative_eq!(192.0, e2e.unwrap());
+    }
+
+    #[test]
+    fn test_down_nodes_are_left_out() {
+        let (graph, groups) = graph();
+        let snapshot = snapshot(vec![
+            NodeState::new(80.0, 80.0, 0.0, 1.0),
+            NodeState::new(20.0, 20.0, 0.0, 1.0),
+            NodeState::new(60.0, 0.0, 0.0, 0.0),
+        ]);
+
+        let latencies = node_latencies(&graph, &groups, &snapshot);
+        assert_eq!(None, latencies[2]);
+
+        // a quarter of api's calls go to db1, the rest fail at once
+        let e2e = mean_latency(&graph, &snapshot, &latencies, &[NodeId(0)]);
+        assert_relative_eq!(10.5, e2e.unwrap());
+        assert_eq!(
+            None,
+            mean_latency(&graph, &snapshot, &latencies, &[NodeId(2)])
+        );
+    }
+
+    #[test]
+    fn test_calls_scale_with_gain_and_add_up_around_loops() {
+        // api (gain 0.5) → worker ↺ (gain 0.9)
+        let api = Node::new(NodeId(0), "api".to_string(), 100.0, 0.5).with_service_time(5.0);
+        let worker = Node::new(NodeId(1), "worker".to_string(), 100.0, 0.9).with_service_time(10.0);
+        let call = Edge::new(EdgeId(0), NodeId(0), NodeId(1), 1.0).with_latency(0.0);
+        let retry = Edge::new(EdgeId(1), NodeId(1), NodeId(1), 1.0).with_latency(0.0);
+        let mut graph = Graph::new(vec![api, worker], vec![call, retry]);
+        let groups = GroupSet::new(vec![Group::new(
+            "group1".to_string(),
+            vec![NodeId(0), NodeId(1)],
+        )]);
+        let snapshot = snapshot(vec![
+            NodeState::new(10.0, 10.0, 0.0, 1.0),
+            NodeState::new(50.0, 50.0, 0.0, 1.0),
+        ]);
+
+        // worker: 10 / (1 - 0.9), api: 5 + 0.5 * 100
+        let latencies = node_latencies(&graph, &groups, &snapshot);
+        let e2e = mean_latency(&graph, &snapshot, &latencies, &[NodeId(0)]);
+        assert_relative_eq!(55.0, e2e.unwrap(), epsilon = 1e-9);
+
+        graph.node_by_id_mut(NodeId(1)).set_gain(1.0);
+        let e2e = mean_latency(&graph, &snapshot, &latencies, &[NodeId(0)]);
+        assert!(e2e.unwrap().is_infinite());
+
+        graph.node_by_id_mut(NodeId(0)).set_gain(0.0);
+        let e2e = mean_latency(&graph, &snapshot, &latencies, &[NodeId(0)]);
+        assert_relative_eq!(5.0, e2e.unwrap());
+    }
+}
@@ -0,0 +1,303 @@
+use crate::analysis::analysis::{calc_cost, calc_health, calc_util};
+use crate::analysis::groups::GroupSet;
+use crate::graph::graph::Graph;
+use crate::state::snapshot::Snapshot;
+use std::io;
+use std::io::{BufRead, Write};
+
+const CSV_HEADER: &str = "turn,group,health,utilization,backlog,cost";
+/// written before costs were tracked, read with zero cost
+const LEGACY_CSV_HEADER: &str = "turn,group,health,utilization,backlog";
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GroupMetrics {
+    health: f64,
+    utilization: f64,
+    backlog: f64,
+    cost: f64,
+}
+
+impl GroupMetrics {
+    pub fn new(health: f64, utilization: f64, backlog: f64, cost: f64) -> Self {
+        Self {
+            health,
+            utilization,
+            backlog,
+            cost,
+        }
+    }
+
+    /// average node health
+    pub fn health(&self) -> f64 {
+        self.health
+    }
+
+    /// served / capacity of healthy nodes
+    pub fn utilization(&self) -> f64 {
+        self.utilization
+    }
+
+    /// total backlog (rps)
+    pub fn backlog(&self) -> f64 {
+        self.backlog
+    }
+
+    /// spend in this turn
+    pub fn cost(&self) -> f64 {
+        self.cost
+    }
+}
+
+pub struct TurnMetrics {
+    turn: usize,
+    groups: Vec<GroupMetrics>,
+}
+
+impl TurnMetrics {
+    pub fn turn(&self) -> usize {
+        self.turn
+    }
+
+    /// indexed like `RunMetrics::groups`
+    pub fn groups(&self) -> &[GroupMetrics] {
+        &self.groups
+    }
+}
+
+/// Per-group health, utilisation, backlog and cost for every turn of a run.
+pub struct RunMetrics {
+    groups: Vec<String>,
+    turns: Vec<TurnMetrics>,
+}
+
+impl RunMetrics {
+    pub fn from_snapshots(graph: &Graph, groups: &GroupSet, snapshots: &[&Snapshot]) -> Self {
+        let mut metrics = Self::new(groups);
+        snapshots
+            .iter()
+            .for_each(|s| metrics.push(graph, groups, s));
+        metrics
+    }
+
+    /// No turns yet, `push` adds them.
+    pub fn new(groups: &GroupSet) -> Self {
+        Self {
+            groups: groups
+                .groups()
+                .iter()
+                .map(|g| g.name().to_string())
+                .collect(),
+            turns: vec![],
+        }
+    }
+
+    /// Adds the turn of `snapshot` after the last one.
+    pub fn push(&mut self, graph: &Graph, groups: &GroupSet, snapshot: &Snapshot) {
+        self.turns.push(TurnMetrics {
+            turn: snapshot.turn(),
+            groups: groups
+                .groups()
+                .iter()
+                .enumerate()
+                .map(|(g_id, g)| {
+                    let backlog = g
+                        .nodes()
+                        .iter()
+                        .map(|id| snapshot.node_states()[id.index()].backlog())
+                        .sum();
+                    GroupMetrics::new(
+                        calc_health(snapshot, g),
+                        calc_util(snapshot, g, graph, g_id),
+                        backlog,
+                        calc_cost(snapshot, g, graph, g_id),
+                    )
+                })
+                .collect(),
+        });
+    }
+
+    pub fn groups(&self) -> &[String] {
+        &self.groups
+    }
+
+    pub fn turns(&self) -> &[TurnMetrics] {
+        &self.turns
+    }
+
+    pub fn at_turn(&self, turn: usize) -> Option<&TurnMetrics> {
+        self.turns
+            .binary_search_by_key(&turn, |t| t.turn)
+            .ok()
+            .map(|i| &self.turns[i])
+    }
+
+    pub fn write_csv(&self, out: &mut impl Write) -> io::Result<()> {
+        writeln!(out, "{}", CSV_HEADER)?;
+        for t in &self.turns {
+            for (name, m) in self.groups.iter().zip(&t.groups) {
+                writeln!(
+                    out,
+                    "{},{},{},{},{},{}",
+                    t.turn, name, m.health, m.utilization, m.backlog, m.cost
+                )?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Reads the format written by `write_csv`, or the one before costs were tracked. Group order
+    /// follows first appearance.
+    pub fn read_csv(input: impl BufRead) -> io::Result<Self> {
+        let invalid = |line: usize, msg: &str| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("line {}: {}", line, msg),
+            )
+        };
+
+        let mut lines = input.lines();
+        let has_cost = match lines.next().transpose()? {
+            Some(header) if header.trim() == CSV_HEADER => true,
+            Some(header) if header.trim() == LEGACY_CSV_HEADER => false,
+            _ => return Err(invalid(1, &format!("expected header '{}'", CSV_HEADER))),
+        };
+
+        let mut groups: Vec<String> = vec![];
+        let mut turns: Vec<TurnMetrics> = vec![];
+        for (i, line) in lines.enumerate() {
+            let line_no = i + 2;
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let fields = line.split(',').map(str::trim).collect::<Vec<&str>>();
+            let (turn, name, health, utilization, backlog, cost) = match (has_cost, &fields[..]) {
+                (true, &[turn, name, health, utilization, backlog, cost]) => {
+                    (turn, name, health, utilization, backlog, cost)
+                }
+                (false, &[turn, name, health, utilization, backlog]) => {
+                    (turn, name, health, utilization, backlog, "0")
+                }
+                _ => {
+                    let expected = if has_cost { 6 } else { 5 };
+                    return Err(invalid(line_no, &format!("expected {} fields", expected)));
+                }
+            };
+            let turn = turn
+                .parse::<usize>()
+                .map_err(|_| invalid(line_no, "invalid turn"))?;
+            let number = |s: &str| {
+                s.parse::<f64>()
+                    .map_err(|_| invalid(line_no, &format!("invalid number '{}'", s)))
+            };
+            let metrics = GroupMetrics::new(
+                number(health)?,
+                number(utilization)?,
+                number(backlog)?,
+                number(cost)?,
+            );
+
+            match turns.last_mut() {
+                Some(last) if last.turn == turn => {}
+                Some(last) if last.turn > turn => {
+                    return Err(invalid(line_no, "turns out of order"));
+                }
+                _ => turns.push(TurnMetrics {
+                    turn,
+                    groups: vec![],
+                }),
+            }
+            let g_id = match groups.iter().position(|g| g == name) {
+                Some(g_id) => g_id,
+                None if turns.len() == 1 => {
+                    groups.push(name.to_string());
+                    groups.len() - 1
+                }
+                None => return Err(invalid(line_no, &format!("unknown group '{}'", name))),
+            };
+            let current = turns.last_mut().unwrap();
+            if current.groups.len() != g_id {
+                return Err(invalid(line_no, &format!("group '{}' out of order", name)));
+            }
+            current.groups.push(metrics);
+        }
+
+        if let Some(t) = turns.iter().find(|t| t.groups.len() != groups.len()) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("turn {}: missing groups", t.turn),
+            ));
+        }
+        Ok(Self { groups, turns })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::groups::Group;
+    use crate::graph::edge::{Edge, EdgeId};
+    use crate::graph::node::{Node, NodeId};
+    use crate::simulation::modifiers::CapacityModifier;
+    use crate::state::edge_state::EdgeState;
+    use crate::state::node_state::NodeState;
+    use approx::assert_relative_eq;
+
+    #[test]
+    fn test_csv_round_trip() {
+        let api = Node::new(NodeId(0), "api".to_string(), 100.0, 1.0);
+        let db = Node::new(NodeId(1), "db".to_string(), 50.0, 0.0);
+        let link = Edge::new(EdgeId(0), NodeId(0), NodeId(1), 1.0);
+        let graph = Graph::new(vec![api, db], vec![link]);
+        let groups = GroupSet::new(vec![
+            Group::new("Api".to_string(), vec![NodeId(0)]),
+            Group::new("Db".to_string(), vec![NodeId(1)]),
+        ]);
+
+        let snapshots = (0..3)
+            .map(|turn| {
+                Snapshot::new(
+                    turn,
+                    vec![
+                        NodeState::new(80.0, 80.0, 0.0, 1.0),
+                        NodeState::new(80.0, 50.0, 30.0 * turn as f64, 1.0 - 0.3 * turn as f64),
+                    ],
+                    vec![EdgeState::new(true)],
+                    vec![CapacityModifier::new(); 2],
+                )
+            })
+            .collect::<Vec<Snapshot>>();
+        let metrics = RunMetrics::from_snapshots(
+            &graph,
+            &groups,
+            &snapshots.iter().collect::<Vec<&Snapshot>>(),
+        );
+
+        let mut csv = vec![];
+        metrics.write_csv(&mut csv).unwrap();
+        let read = RunMetrics::read_csv(csv.as_slice()).unwrap();
+
+        assert_eq!(vec!["Api", "Db"], read.groups());
+        assert_eq!(3, read.turns().len());
+        let last = read.at_turn(2).unwrap().groups();
+        assert_relative_eq!(0.8, last[0].utilization());
+        assert_relative_eq!(1.0, last[1].utilization());
+        assert_relative_eq!(0.4, last[1].health());
+        assert_relative_eq!(60.0, last[1].backlog());
+        // 100 * 0.01 + 50 * 0.01 at default cost
+        assert_relative_eq!(1.0, last[0].cost());
+        assert_relative_eq!(0.5, last[1].cost());
+    }
+
+    #[test]
+    fn test_read_csv_rejects_malformed_rows() {
+        let missing_field = "turn,group,health,utilization,backlog\n0,Api,1.0,0.5\n";
+        let bad_header = "turn,group\n0,Api,1.0,0.5,0.0\n";
+        let missing_group = "turn,group,health,utilization,backlog\n\
+            0,Api,1.0,0.5,0.0\n0,Db,1.0,0.5,0.0\n1,Api,1.0,0.5,0.0\n";
+
+        assert!(RunMetrics::read_csv(missing_field.as_bytes()).is_err());
+        assert!(RunMetrics::read_csv(bad_header.as_bytes()).is_err());
+        assert!(RunMetrics::read_csv(missing_group.as_bytes()).is_err());
+    }
+}
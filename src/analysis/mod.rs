@@ -1,2 +1,17 @@
 pub mod analysis;
+pub mod blast_radius;
+pub mod bottleneck;
+pub mod calibration;
+pub mod cascade;
+pub mod comparison;
+pub mod cycles;
+pub mod flow;
+pub mod goodput;
+pub mod group_graph;
 pub mod groups;
+pub mod latency;
+pub mod metrics;
+pub mod monte_carlo;
+pub mod parallel;
+pub mod sensitivity;
+pub mod slo;
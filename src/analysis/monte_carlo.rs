@@ -0,0 +1,171 @@
+use crate::analysis::groups::GroupHealth;
+use crate::analysis::metrics::RunMetrics;
+use crate::analysis::parallel::parallel_map;
+use crate::simulation::engine::SimulationEngine;
+
+/// Per-group metrics of many runs of the same scenario under different noise seeds.
+pub struct MonteCarlo {
+    seeds: Vec<u64>,
+    runs: Vec<RunMetrics>,
+}
+
+impl MonteCarlo {
+    pub fn seeds(&self) -> &[u64] {
+        &self.seeds
+    }
+
+    pub fn groups(&self) -> &[String] {
+        self.runs.first().map_or(&[], |r| r.groups())
+    }
+
+    /// first turn at which the group is `Failed` in each run
+    pub fn failure_turns(&self, group_id: usize) -> Vec<Option<usize>> {
+        self.runs
+            .iter()
+            .map(|run| {
+                run.turns()
+                    .iter()
+                    .find(|t| {
+                        GroupHealth::classify(t.groups()[group_id].health()) == GroupHealth::Failed
+                    })
+                    .map(|t| t.turn())
+            })
+            .collect()
+    }
+
+    /// share of runs in which the group has failed by `turn`
+    pub fn failure_probability(&self, group_id: usize, turn: usize) -> f64 {
+        if self.runs.is_empty() {
+            return 0.0;
+        }
+        let failed = self
+            .failure_turns(group_id)
+            .iter()
+            .filter(|t| t.is_some_and(|t| t <= turn))
+            .count();
+        failed as f64 / self.runs.len() as f64
+    }
+
+    /// `p`-th percentile (0-100) of the group's health at `turn` across runs
+    pub fn health_percentile(&self, group_id: usize, turn: usize, p: f64) -> Option<f64> {
+        let health = self
+            .runs
+            .iter()
+            .filter_map(|run| run.at_turn(turn))
+            .map(|t| t.groups()[group_id].health())
+            .collect::<Vec<f64>>();
+        percentile(health, p)
+    }
+}
+
+/// Nearest-rank percentile, `p` in [0, 100].
+pub fn percentile(mut values: Vec<f64>, p: f64) -> Option<f64> {
+    if values.is_empty() {
+        return None;
+    }
+    values.sort_by(f64::total_cmp);
+    let rank = (p.clamp(0.0, 100.0) / 100.0 * (values.len() - 1) as f64).round() as usize;
+    Some(values[rank])
+}
+
+/// Runs an engine from `build` for every seed for `turns` and collects per-group metrics.
+pub fn run_monte_carlo(
+    build: impl Fn(u64) -> SimulationEngine + Sync,
+    seeds: Vec<u64>,
+    turns: usize,
+) -> MonteCarlo {
+    let runs = parallel_map(&seeds, |seed| {
+        let mut engine = build(*seed);
+        (0..turns).for_each(|_| engine.step());
+        RunMetrics::from_snapshots(engine.graph(), engine.groups(), &engine.snapshots())
+    });
+    MonteCarlo { seeds, runs }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::groups::{Group, GroupSet};
+    use crate::graph::edge::{Edge, EdgeId};
+    use crate::graph::graph::Graph;
+    use crate::graph::node::{Node, NodeId};
+    use crate::scenario::scenario::TestScenario;
+    use crate::simulation::modifiers::CapacityModifier;
+    use crate::simulation::noise::Noise;
+    use crate::state::edge_state::EdgeState;
+    use crate::state::node_state::NodeState;
+    use crate::state::snapshot::Snapshot;
+    use approx::assert_relative_eq;
+
+    // api → db, db right at its capacity
+    fn engine(noise: Option<Noise>) -> SimulationEngine {
+        let api = Node::new(NodeId(0), "api".to_string(), 200.0, 1.0);
+        let db = Node::new(NodeId(1), "db".to_string(), 50.0, 0.0);
+        let link = Edge::new(EdgeId(0), NodeId(0), NodeId(1), 1.0);
+
+        let graph = Graph::new(vec![api, db], vec![link]);
+        let groups = GroupSet::new(vec![
+            Group::new("Api".to_string(), vec![NodeId(0)]),
+            Group::new("Db".to_string(), vec![NodeId(1)]),
+        ]);
+        let snapshot = Snapshot::new(
+            0,
+            graph
+                .nodes()
+                .iter()
+                .map(|_| NodeState::new(0.0, 0.0, 0.0, 1.0))
+                .collect(),
+            graph.edges().iter().map(|_| EdgeState::new(true)).collect(),
+            vec![CapacityModifier::new(); 2],
+        );
+        let engine = SimulationEngine::new(
+            graph,
+            groups,
+            snapshot,
+            Box::new(TestScenario::new(vec![NodeId(0)], vec![50.0])),
+        );
+        match noise {
+            Some(noise) => engine.with_noise(noise),
+            None => engine,
+        }
+    }
+
+    #[test]
+    fn test_percentile_nearest_rank() {
+        let values = vec![0.5, 0.1, 0.9, 0.3, 0.7];
+
+        assert_relative_eq!(0.1, percentile(values.clone(), 0.0).unwrap());
+        assert_relative_eq!(0.5, percentile(values.clone(), 50.0).unwrap());
+        assert_relative_eq!(0.9, percentile(values.clone(), 100.0).unwrap());
+        assert_eq!(None, percentile(vec![], 50.0));
+    }
+
+    #[test]
+    fn test_without_noise_all_runs_agree() {
+        let mc = run_monte_carlo(|_| engine(None), (0..4).collect(), 30);
+
+        assert_eq!(vec!["Api", "Db"], mc.groups());
+        assert_relative_eq!(0.0, mc.failure_probability(1, 30));
+        assert_relative_eq!(
+            mc.health_percentile(1, 30, 10.0).unwrap(),
+            mc.health_percentile(1, 30, 90.0).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_load_jitter_spreads_outcomes() {
+        let mc = run_monte_carlo(
+            |seed| engine(Some(Noise::new(seed).with_load_jitter(0.05))),
+            (0..40).collect(),
+            60,
+        );
+
+        let p = mc.failure_probability(1, 60);
+        assert!(p > 0.0 && p < 1.0);
+        assert!(mc.failure_probability(1, 10) <= p);
+        assert!(
+            mc.health_percentile(1, 60, 10.0).unwrap() < mc.health_percentile(1, 60, 90.0).unwrap()
+        );
+        assert_relative_eq!(0.0, mc.failure_probability(0, 60));
+    }
+}
@@ -0,0 +1,322 @@
+use crate::analysis::parallel::parallel_map;
+use crate::graph::edge::EdgeId;
+use crate::graph::graph::Graph;
+use crate::graph::node::NodeId;
+use crate::simulation::engine::SimulationEngine;
+use clap::ValueEnum;
+use std::cmp::Ordering;
+
+#[derive(Clone, Copy, Debug, PartialEq, ValueEnum)]
+#[value(rename_all = "lowercase")]
+pub enum ParameterKind {
+    Capacity,
+    Gain,
+    Weight,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Parameter {
+    Capacity(NodeId),
+    Gain(NodeId),
+    Weight(EdgeId),
+}
+
+impl Parameter {
+    pub fn kind(&self) -> ParameterKind {
+        match self {
+            Parameter::Capacity(_) => ParameterKind::Capacity,
+            Parameter::Gain(_) => ParameterKind::Gain,
+            Parameter::Weight(_) => ParameterKind::Weight,
+        }
+    }
+
+    pub fn value(&self, graph: &Graph) -> f64 {
+        match *self {
+            Parameter::Capacity(id) => graph.node_by_id(id).capacity(),
+            Parameter::Gain(id) => graph.node_by_id(id).gain(),
+            Parameter::Weight(id) => graph.edge_by_id(id).weight(),
+        }
+    }
+
+    pub fn scale(&self, graph: &mut Graph, factor: f64) {
+        let value = self.value(graph) * factor;
+        match *self {
+            Parameter::Capacity(id) => graph.node_by_id_mut(id).set_capacity(value),
+            Parameter::Gain(id) => graph.node_by_id_mut(id).set_gain(value),
+            Parameter::Weight(id) => graph.edge_by_id_mut(id).set_weight(value),
+        }
+    }
+
+    pub fn describe(&self, graph: &Graph) -> String {
+        match *self {
+            Parameter::Capacity(id) => format!("capacity {}", graph.node_by_id(id).name()),
+            Parameter::Gain(id) => format!("gain {}", graph.node_by_id(id).name()),
+            Parameter::Weight(id) => {
+                let edge = graph.edge_by_id(id);
+                format!(
+                    "weight {} → {}",
+                    graph.node_by_id(edge.from()).name(),
+                    graph.node_by_id(edge.to()).name()
+                )
+            }
+        }
+    }
+}
+
+/// Parameters whose change can affect a run: non-zero capacities and gains, and the weights
+/// of edges that share their source with another edge (a lone edge always gets all the load).
+pub fn parameters(graph: &Graph) -> Vec<Parameter> {
+    let nodes = graph.nodes().iter().flat_map(|n| {
+        [Parameter::Capacity(*n.id()), Parameter::Gain(*n.id())]
+            .into_iter()
+            .filter(|p| p.value(graph) > 0.0)
+    });
+    let edges = graph
+        .edges()
+        .iter()
+        .filter(|e| e.weight() > 0.0 && graph.outgoing(e.from()).len() > 1)
+        .map(|e| Parameter::Weight(e.id()));
+    nodes.chain(edges).collect()
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RunOutcome {
+    avg_health: f64,
+    failed_nodes: usize,
+    first_failure: Option<usize>,
+}
+
+impl RunOutcome {
+    pub fn of(engine: &SimulationEngine) -> Self {
+        let states = engine.current_snapshot().node_states();
+        let avg_health = if states.is_empty() {
+            0.0
+        } else {
+            states.iter().map(|s| s.health()).sum::<f64>() / states.len() as f64
+        };
+        Self {
+            avg_health,
+            failed_nodes: states.iter().filter(|s| !s.is_healthy()).count(),
+            first_failure: engine
+                .snapshots()
+                .iter()
+                .find(|s| s.node_states().iter().any(|n| !n.is_healthy()))
+                .map(|s| s.turn()),
+        }
+    }
+
+    /// average node health at the end of the run
+    pub fn avg_health(&self) -> f64 {
+        self.avg_health
+    }
+
+    pub fn failed_nodes(&self) -> usize {
+        self.failed_nodes
+    }
+
+    pub fn first_failure(&self) -> Option<usize> {
+        self.first_failure
+    }
+}
+
+pub struct Effect {
+    parameter: Parameter,
+    factor: f64,
+    outcome: RunOutcome,
+}
+
+impl Effect {
+    pub fn parameter(&self) -> Parameter {
+        self.parameter
+    }
+
+    /// multiplier applied to the parameter
+    pub fn factor(&self) -> f64 {
+        self.factor
+    }
+
+    pub fn outcome(&self) -> &RunOutcome {
+        &self.outcome
+    }
+}
+
+pub struct Sensitivity {
+    baseline: RunOutcome,
+    effects: Vec<Effect>,
+}
+
+impl Sensitivity {
+    pub fn baseline(&self) -> &RunOutcome {
+        &self.baseline
+    }
+
+    /// most beneficial first
+    pub fn effects(&self) -> &[Effect] {
+        &self.effects
+    }
+}
+
+fn run(mut engine: SimulationEngine, turns: usize) -> RunOutcome {
+    (0..turns).for_each(|_| engine.step());
+    RunOutcome::of(&engine)
+}
+
+/// Higher final health first, then fewer failed nodes, then a later (or no) first failure.
+fn compare_outcomes(a: &RunOutcome, b: &RunOutcome) -> Ordering {
+    b.avg_health
+        .total_cmp(&a.avg_health)
+        .then(a.failed_nodes.cmp(&b.failed_nodes))
+        .then(match (a.first_failure, b.first_failure) {
+            (None, None) => Ordering::Equal,
+            (None, Some(_)) => Ordering::Less,
+            (Some(_), None) => Ordering::Greater,
+            (Some(a), Some(b)) => b.cmp(&a),
+        })
+}
+
+/// Parses the size of a change in percent, which has to leave every parameter above zero.
+pub fn parse_percent(s: &str) -> Result<f64, String> {
+    let percent = s.parse::<f64>().map_err(|e| e.to_string())?;
+    if percent > 0.0 && percent < 100.0 {
+        Ok(percent)
+    } else {
+        Err(format!("{} is not between 0 and 100", percent))
+    }
+}
+
+/// Scales every parameter up and down by `percent`, one at a time, runs a fresh engine from
+/// `build` for `turns` and ranks the changes by their outcome. Only parameters of the given
+/// `kinds` are changed. Runs are spread across threads.
+pub fn analyse_sensitivity(
+    build: impl Fn() -> SimulationEngine + Sync,
+    kinds: &[ParameterKind],
+    percent: f64,
+    turns: usize,
+) -> Sensitivity {
+    let baseline_engine = build();
+    let changes = parameters(baseline_engine.graph())
+        .into_iter()
+        .filter(|p| kinds.contains(&p.kind()))
+        .flat_map(|p| [1.0 + percent / 100.0, 1.0 - percent / 100.0].map(|f| (p, f)))
+        .collect::<Vec<(Parameter, f64)>>();
+    let baseline = run(baseline_engine, turns);
+
+    let mut effects = parallel_map(&changes, |(parameter, factor)| {
+        let mut engine = build();
+        parameter.scale(engine.graph_mut(), *factor);
+        Effect {
+            parameter: *parameter,
+            factor: *factor,
+            outcome: run(engine, turns),
+        }
+    });
+    effects.sort_by(|a, b| compare_outcomes(&a.outcome, &b.outcome));
+
+    Sensitivity { baseline, effects }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::groups::{Group, GroupSet};
+    use crate::graph::edge::Edge;
+    use crate::graph::node::Node;
+    use crate::scenario::scenario::TestScenario;
+    use crate::simulation::modifiers::CapacityModifier;
+    use crate::state::edge_state::EdgeState;
+    use crate::state::node_state::NodeState;
+    use crate::state::snapshot::Snapshot;
+    use approx::assert_relative_eq;
+
+    // api → db1
+    //   ↘ db2
+    fn engine() -> SimulationEngine {
+        let api = Node::new(NodeId(0), "api".to_string(), 100.0, 1.0);
+        let db1 = Node::new(NodeId(1), "db1".to_string(), 42.0, 0.0);
+        let db2 = Node::new(NodeId(2), "db2".to_string(), 60.0, 0.0);
+
+        let link1 = Edge::new(EdgeId(0), NodeId(0), NodeId(1), 1.0);
+        let link2 = Edge::new(EdgeId(1), NodeId(0), NodeId(2), 1.0);
+
+        let graph = Graph::new(vec![api, db1, db2], vec![link1, link2]);
+        let groups = GroupSet::new(vec![Group::new(
+            "group1".to_string(),
+            vec![NodeId(0), NodeId(1), NodeId(2)],
+        )]);
+        let snapshot = Snapshot::new(
+            0,
+            graph
+                .nodes()
+                .iter()
+                .map(|_| NodeState::new(0.0, 0.0, 0.0, 1.0))
+                .collect(),
+            graph.edges().iter().map(|_| EdgeState::new(true)).collect(),
+            vec![CapacityModifier::new(); 1],
+        );
+        SimulationEngine::new(
+            graph,
+            groups,
+            snapshot,
+            Box::new(TestScenario::new(vec![NodeId(0)], vec![90.0])),
+        )
+    }
+
+    #[test]
+    fn test_parameters_skip_inert_values() {
+        let params = parameters(engine().graph());
+
+        assert_eq!(
+            vec![
+                Parameter::Capacity(NodeId(0)),
+                Parameter::Gain(NodeId(0)),
+                Parameter::Capacity(NodeId(1)),
+                Parameter::Capacity(NodeId(2)),
+                Parameter::Weight(EdgeId(0)),
+                Parameter::Weight(EdgeId(1)),
+            ],
+            params
+        );
+    }
+
+    #[test]
+    fn test_ranks_change_that_prevents_failure_first() {
+        // db1 gets 45 rps against a capacity of 42 and fails
+        let sensitivity = analyse_sensitivity(
+            engine,
+            &[
+                ParameterKind::Capacity,
+                ParameterKind::Gain,
+                ParameterKind::Weight,
+            ],
+            10.0,
+            40,
+        );
+        assert_eq!(1, sensitivity.baseline().failed_nodes());
+
+        let effects = sensitivity.effects();
+        let best = &effects[0];
+        assert_eq!(0, best.outcome().failed_nodes());
+        assert_eq!(None, best.outcome().first_failure());
+        assert_relative_eq!(1.0, best.outcome().avg_health());
+
+        let worst = effects.last().unwrap();
+        assert!(worst.outcome().avg_health() <= sensitivity.baseline().avg_health());
+        assert!(
+            effects
+                .iter()
+                .any(|e| e.parameter() == Parameter::Capacity(NodeId(1))
+                    && e.factor() > 1.0
+                    && e.outcome().failed_nodes() == 0)
+        );
+    }
+
+    #[test]
+    fn test_parse_percent() {
+        assert_eq!(Ok(12.5), parse_percent("12.5"));
+        // 100% down would zero the parameter, more would make it negative
+        assert!(parse_percent("100").is_err());
+        assert!(parse_percent("0").is_err());
+        assert!(parse_percent("-5").is_err());
+        assert!(parse_percent("ten").is_err());
+    }
+}
@@ -0,0 +1,388 @@
+use crate::analysis::groups::GroupSet;
+use crate::graph::graph::Graph;
+use crate::graph::node::NodeId;
+use crate::state::snapshot::Snapshot;
+use std::collections::VecDeque;
+use std::fmt::{Display, Formatter};
+
+const DEFAULT_WINDOW: usize = 20;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SloScope {
+    Group(usize),
+    Node(NodeId),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Indicator {
+    /// share of demand that was served
+    Availability,
+    /// share of turns with every node up and their total backlog at or below the limit
+    BacklogBelow(f64),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Slo {
+    scope: SloScope,
+    indicator: Indicator,
+    /// target share of good events (0.0, 1.0)
+    objective: f64,
+    /// rolling window in turns
+    window: usize,
+}
+
+impl Slo {
+    pub fn new(scope: SloScope, indicator: Indicator, objective: f64, window: usize) -> Self {
+        Self {
+            scope,
+            indicator,
+            objective: objective.clamp(0.0, 1.0),
+            window: window.max(1),
+        }
+    }
+
+    /// Parses `SCOPE:INDICATOR:OBJECTIVE[/WINDOW]`, e.g. `Ingress:availability:99.5/20` or
+    /// `db-1:backlog<50:99`. The scope is a group name, or else a node name (case-insensitive).
+    pub fn parse(spec: &str, graph: &Graph, groups: &GroupSet) -> Result<Self, String> {
+        let invalid = |msg: &str| format!("invalid SLO '{}': {}", spec, msg);
+        let [scope, indicator, objective] = spec.split(':').collect::<Vec<&str>>()[..] else {
+            return Err(invalid("expected SCOPE:INDICATOR:OBJECTIVE[/WINDOW]"));
+        };
+
+        let scope = match groups.group_by_name(scope) {
+            Some(g_id) => SloScope::Group(g_id),
+            None => graph
+                .nodes()
+                .iter()
+                .find(|n| n.name().eq_ignore_ascii_case(scope))
+                .map(|n| SloScope::Node(*n.id()))
+                .ok_or_else(|| invalid(&format!("unknown group or node '{}'", scope)))?,
+        };
+
+        let indicator = match indicator.to_ascii_lowercase().split_once('<') {
+            None if indicator.eq_ignore_ascii_case("availability") => Indicator::Availability,
+            Some(("backlog", limit)) => Indicator::BacklogBelow(
+                limit
+                    .trim_start_matches('=')
+                    .parse::<f64>()
+                    .map_err(|_| invalid("invalid backlog limit"))?,
+            ),
+            _ => return Err(invalid("indicator must be 'availability' or 'backlog<X'")),
+        };
+
+        let (objective, window) = match objective.split_once('/') {
+            Some((objective, window)) => (
+                objective,
+                window
+                    .parse::<usize>()
+                    .map_err(|_| invalid("invalid window"))?,
+            ),
+            None => (objective, DEFAULT_WINDOW),
+        };
+        let objective = objective
+            .trim_end_matches('%')
+            .parse::<f64>()
+            .ok()
+            .filter(|o| *o > 0.0 && *o < 100.0)
+            .ok_or_else(|| invalid("objective must be a percentage between 0 and 100"))?;
+
+        Ok(Self::new(scope, indicator, objective / 100.0, window))
+    }
+
+    pub fn nodes<'a>(&'a self, groups: &'a GroupSet) -> &'a [NodeId] {
+        match &self.scope {
+            SloScope::Group(g_id) => groups.groups()[*g_id].nodes(),
+            SloScope::Node(id) => std::slice::from_ref(id),
+        }
+    }
+
+    /// group the SLO is scoped to, directly or through its node
+    pub fn group_id(&self, groups: &GroupSet) -> usize {
+        match self.scope {
+            SloScope::Group(g_id) => g_id,
+            SloScope::Node(id) => groups.group_by_node_id(id.index()),
+        }
+    }
+
+    pub fn describe(&self, graph: &Graph, groups: &GroupSet) -> String {
+        let scope = match self.scope {
+            SloScope::Group(g_id) => groups.groups()[g_id].name(),
+            SloScope::Node(id) => graph.node_by_id(id).name(),
+        };
+        let indicator = match self.indicator {
+            Indicator::Availability => "availability".to_string(),
+            Indicator::BacklogBelow(limit) => format!("backlog<{}", limit),
+        };
+        format!(
+            "{} {} ≥ {}% / {}",
+            scope,
+            indicator,
+            self.objective * 100.0,
+            self.window
+        )
+    }
+
+    /// (good, total) events of one turn
+    pub fn events(&self, groups: &GroupSet, snapshot: &Snapshot) -> (f64, f64) {
+        let states = self
+            .nodes(groups)
+            .iter()
+            .map(|id| &snapshot.node_states()[id.index()]);
+        match self.indicator {
+            Indicator::Availability => states.fold((0.0, 0.0), |(good, total), s| {
+                (good + s.served().min(s.demand()), total + s.demand())
+            }),
+            Indicator::BacklogBelow(limit) => {
+                let (up, backlog) = states.fold((true, 0.0), |(up, backlog), s| {
+                    (up && s.is_up(), backlog + s.backlog())
+                });
+                (if up && backlog <= limit { 1.0 } else { 0.0 }, 1.0)
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SloTurn {
+    turn: usize,
+    attainment: f64,
+    burn_rate: f64,
+    budget_remaining: f64,
+}
+
+impl SloTurn {
+    pub fn turn(&self) -> usize {
+        self.turn
+    }
+
+    /// share of good events over the rolling window, 1.0 without events
+    pub fn attainment(&self) -> f64 {
+        self.attainment
+    }
+
+    /// rate at which the window consumes error budget, 1.0 uses it up exactly
+    pub fn burn_rate(&self) -> f64 {
+        self.burn_rate
+    }
+
+    /// share of the run's error budget left so far, negative once exceeded
+    pub fn budget_remaining(&self) -> f64 {
+        self.budget_remaining
+    }
+
+    pub fn verdict(&self) -> SloVerdict {
+        if self.budget_remaining < 0.0 {
+            SloVerdict::Breached
+        } else if self.burn_rate > 1.0 {
+            SloVerdict::AtRisk
+        } else {
+            SloVerdict::Met
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum SloVerdict {
+    Met,
+    /// budget left, but burning faster than it allows
+    AtRisk,
+    Breached,
+}
+
+impl Display for SloVerdict {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            SloVerdict::Met => "Met",
+            SloVerdict::AtRisk => "At risk",
+            SloVerdict::Breached => "Breached",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+#[derive(Default)]
+pub struct SloTrack {
+    turns: Vec<SloTurn>,
+    /// (good, total) events of the turns in the rolling window, oldest first
+    window: VecDeque<(f64, f64)>,
+    run_good: f64,
+    run_total: f64,
+}
+
+impl SloTrack {
+    /// Tracks `snapshot` as the turn after the last tracked one.
+    pub fn push(&mut self, slo: &Slo, groups: &GroupSet, snapshot: &Snapshot) {
+        let events = slo.events(groups, snapshot);
+        self.run_good += events.0;
+        self.run_total += events.1;
+        self.window.push_back(events);
+        if self.window.len() > slo.window {
+            self.window.pop_front();
+        }
+
+        let allowed = 1.0 - slo.objective;
+        let (good, total) = self
+            .window
+            .iter()
+            .fold((0.0, 0.0), |(g, t), (eg, et)| (g + eg, t + et));
+        let attainment = if total > 0.0 { good / total } else { 1.0 };
+        let budget_remaining = if self.run_total > 0.0 && allowed > 0.0 {
+            1.0 - (self.run_total - self.run_good) / (allowed * self.run_total)
+        } else {
+            1.0
+        };
+        self.turns.push(SloTurn {
+            turn: snapshot.turn(),
+            attainment,
+            burn_rate: if allowed > 0.0 {
+                (1.0 - attainment) / allowed
+            } else {
+                0.0
+            },
+            budget_remaining,
+        });
+    }
+
+    pub fn turns(&self) -> &[SloTurn] {
+        &self.turns
+    }
+
+    pub fn last(&self) -> Option<&SloTurn> {
+        self.turns.last()
+    }
+
+    pub fn at_turn(&self, turn: usize) -> Option<&SloTurn> {
+        self.turns
+            .binary_search_by_key(&turn, |t| t.turn)
+            .ok()
+            .map(|i| &self.turns[i])
+    }
+
+    /// verdict of the last turn, met before any
+    pub fn verdict(&self) -> SloVerdict {
+        self.last().map_or(SloVerdict::Met, SloTurn::verdict)
+    }
+}
+
+/// Attainment, burn rate and remaining budget of `slo` after every snapshot.
+pub fn track_slo(slo: &Slo, groups: &GroupSet, snapshots: &[&Snapshot]) -> SloTrack {
+    let mut track = SloTrack::default();
+    snapshots.iter().for_each(|s| track.push(slo, groups, s));
+    track
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::groups::Group;
+    use crate::graph::edge::{Edge, EdgeId};
+    use crate::graph::node::Node;
+    use crate::simulation::modifiers::CapacityModifier;
+    use crate::state::edge_state::EdgeState;
+    use crate::state::node_state::NodeState;
+    use approx::assert_relative_eq;
+
+    fn topology() -> (Graph, GroupSet) {
+        let api = Node::new(NodeId(0), "api".to_string(), 100.0, 1.0);
+        let db = Node::new(NodeId(1), "db".to_string(), 50.0, 0.0);
+        let link = Edge::new(EdgeId(0), NodeId(0), NodeId(1), 1.0);
+        let graph = Graph::new(vec![api, db], vec![link]);
+        let groups = GroupSet::new(vec![
+            Group::new("Api".to_string(), vec![NodeId(0)]),
+            Group::new("Database".to_string(), vec![NodeId(1)]),
+        ]);
+        (graph, groups)
+    }
+
+    fn snapshot(turn: usize, db_demand: f64, db_served: f64, db_backlog: f64) -> Snapshot {
+        Snapshot::new(
+            turn,
+            vec![
+                NodeState::new(50.0, 50.0, 0.0, 1.0),
+                NodeState::new(db_demand, db_served, db_backlog, 1.0),
+            ],
+            vec![EdgeState::new(true)],
+            vec![CapacityModifier::new(); 2],
+        )
+    }
+
+    #[test]
+    fn test_parse_specs() {
+        let (graph, groups) = topology();
+
+        assert_eq!(
+            Slo::new(SloScope::Group(1), Indicator::Availability, 0.995, 20),
+            Slo::parse("database:availability:99.5", &graph, &groups).unwrap()
+        );
+        assert_eq!(
+            Slo::new(
+                SloScope::Node(NodeId(1)),
+                Indicator::BacklogBelow(50.0),
+                0.99,
+                10
+            ),
+            Slo::parse("db:backlog<50:99%/10", &graph, &groups).unwrap()
+        );
+        assert!(Slo::parse("nope:availability:99", &graph, &groups).is_err());
+        assert!(Slo::parse("Api:latency:99", &graph, &groups).is_err());
+        assert!(Slo::parse("Api:availability:100", &graph, &groups).is_err());
+    }
+
+    #[test]
+    fn test_availability_burn_and_budget() {
+        let (_, groups) = topology();
+        let slo = Slo::new(SloScope::Group(1), Indicator::Availability, 0.9, 2);
+        let snapshots = [
+            snapshot(0, 50.0, 50.0, 0.0),
+            snapshot(1, 50.0, 50.0, 0.0),
+            snapshot(2, 50.0, 40.0, 10.0),
+            snapshot(3, 50.0, 30.0, 30.0),
+        ];
+        let track = track_slo(&slo, &groups, &snapshots.iter().collect::<Vec<&Snapshot>>());
+        let turns = track.turns();
+
+        assert_relative_eq!(1.0, turns[1].attainment());
+        assert_relative_eq!(0.0, turns[1].burn_rate());
+        // window of turns 1-2: 90 / 100
+        assert_relative_eq!(0.9, turns[2].attainment());
+        assert_relative_eq!(1.0, turns[2].burn_rate(), epsilon = 1e-9);
+        // 10 bad of 150, 15 allowed
+        assert_relative_eq!(
+            1.0 - 10.0 / 15.0,
+            turns[2].budget_remaining(),
+            epsilon = 1e-9
+        );
+        // window of turns 2-3: 70 / 100; 30 bad of 200, 20 allowed
+        assert_relative_eq!(0.7, turns[3].attainment());
+        assert_relative_eq!(3.0, turns[3].burn_rate(), epsilon = 1e-9);
+        assert_relative_eq!(-0.5, turns[3].budget_remaining(), epsilon = 1e-9);
+        assert_eq!(SloVerdict::Breached, track.verdict());
+    }
+
+    #[test]
+    fn test_backlog_verdicts() {
+        let (_, groups) = topology();
+        let slo = Slo::new(SloScope::Group(1), Indicator::BacklogBelow(20.0), 0.5, 2);
+        let mut snapshots = vec![
+            snapshot(0, 50.0, 50.0, 0.0),
+            snapshot(1, 50.0, 50.0, 0.0),
+            snapshot(2, 50.0, 50.0, 0.0),
+            snapshot(3, 60.0, 50.0, 30.0),
+        ];
+        let refs = snapshots.iter().collect::<Vec<&Snapshot>>();
+        let track = track_slo(&slo, &groups, &refs);
+        assert_relative_eq!(0.5, track.last().unwrap().attainment());
+        assert_eq!(SloVerdict::Met, track.verdict());
+
+        snapshots.push(snapshot(4, 60.0, 50.0, 40.0));
+        let refs = snapshots.iter().collect::<Vec<&Snapshot>>();
+        let track = track_slo(&slo, &groups, &refs);
+        assert_relative_eq!(2.0, track.last().unwrap().burn_rate());
+        assert_relative_eq!(
+            0.2,
+            track.last().unwrap().budget_remaining(),
+            epsilon = 1e-9
+        );
+        assert_eq!(SloVerdict::AtRisk, track.verdict());
+    }
+}
@@ -0,0 +1,100 @@
+use crate::analysis::blast_radius::{FailureTarget, blast_radii};
+use crate::analysis::bottleneck::rank_bottlenecks;
+use crate::graph::graph::Graph;
+use crate::graph::node::NodeId;
+use crate::simulation::engine::SimulationEngine;
+use std::io;
+use std::io::Write;
+
+const LISTED_NODES: usize = 6;
+
+fn node_list(graph: &Graph, nodes: &[NodeId]) -> String {
+    let mut names = nodes
+        .iter()
+        .take(LISTED_NODES)
+        .map(|id| graph.node_by_id(*id).name())
+        .collect::<Vec<&str>>()
+        .join(", ");
+    if nodes.len() > LISTED_NODES {
+        names.push_str(&format!(" +{} more", nodes.len() - LISTED_NODES));
+    }
+    names
+}
+
+/// `load` defaults to 80% of the entry load at which the intact topology first saturates a
+/// node, or the scenario's entry load of the first turn when no node ever saturates.
+pub fn print_blast_radius(
+    engine: &SimulationEngine,
+    load: Option<f64>,
+    top: usize,
+    out: &mut impl Write,
+) -> io::Result<()> {
+    let graph = engine.graph();
+    let groups = engine.groups();
+    let entry_nodes = engine.scenario().entry_nodes();
+    let load = load.unwrap_or_else(|| {
+        // unbounded bottlenecks come first and saturate at any load
+        rank_bottlenecks(graph, entry_nodes)
+            .iter()
+            .find(|b| !b.is_unbounded())
+            .map_or_else(
+                || {
+                    entry_nodes
+                        .iter()
+                        .map(|id| engine.scenario().load(*id, 0))
+                        .sum()
+                },
+                |b| b.saturation_load() * 0.8,
+            )
+    });
+
+    writeln!(
+        out,
+        "Failures ranked by blast radius at {:.1} rps of entry load",
+        load
+    )?;
+    writeln!(
+        out,
+        "Upstream nodes move a failed node's share to their other targets, which the simulation does not do"
+    )?;
+    writeln!(out)?;
+    writeln!(
+        out,
+        "{:>4}  {:<30} {:>8} {:>11}",
+        "#", "Failed", "Starved", "Overloaded"
+    )?;
+
+    for (pos, radius) in blast_radii(graph, groups, entry_nodes, load)
+        .iter()
+        .take(top)
+        .enumerate()
+    {
+        let failed = match radius.target() {
+            FailureTarget::Node(id) => format!("node {}", graph.node_by_id(id).name()),
+            FailureTarget::Group(g_id) => format!("group {}", groups.groups()[g_id].name()),
+        };
+        writeln!(
+            out,
+            "{:>4}  {:<30} {:>8} {:>11}",
+            pos + 1,
+            failed,
+            radius.starved().len(),
+            radius.overloaded().len()
+        )?;
+        if !radius.starved().is_empty() {
+            writeln!(
+                out,
+                "        starved:    {}",
+                node_list(graph, radius.starved())
+            )?;
+        }
+        if !radius.overloaded().is_empty() {
+            writeln!(
+                out,
+                "        overloaded: {}",
+                node_list(graph, radius.overloaded())
+            )?;
+        }
+    }
+    Ok(())
+}
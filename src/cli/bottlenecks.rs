@@ -0,0 +1,66 @@
+use crate::analysis::bottleneck::rank_bottlenecks;
+use crate::simulation::engine::SimulationEngine;
+use std::io;
+use std::io::Write;
+
+pub fn print_bottlenecks(
+    engine: &SimulationEngine,
+    top: usize,
+    out: &mut impl Write,
+) -> io::Result<()> {
+    let graph = engine.graph();
+    let groups = engine.groups();
+    let entry_nodes = engine.scenario().entry_nodes();
+    let ranked = rank_bottlenecks(graph, entry_nodes);
+
+    writeln!(
+        out,
+        "Static bottlenecks for 1 rps of entry load split across {} entry nodes",
+        entry_nodes.len()
+    )?;
+    writeln!(out)?;
+    writeln!(
+        out,
+        "{:>4}  {:<20} {:<15} {:>10} {:>8} {:>14}",
+        "#", "Node", "Group", "Amplif.", "Cap", "Saturates at"
+    )?;
+
+    for (pos, b) in ranked.iter().take(top).enumerate() {
+        let node = graph.node_by_id(b.node_id());
+        let group = &groups.groups()[groups.group_by_node_id(b.node_id().index())];
+        let (amplification, saturation) = if b.is_unbounded() {
+            ("unbounded".to_string(), "-".to_string())
+        } else {
+            (
+                format!("{:.3}", b.amplification()),
+                format!("{:.1} rps", b.saturation_load()),
+            )
+        };
+        writeln!(
+            out,
+            "{:>4}  {:<20} {:<15} {:>10} {:>8.1} {:>14}",
+            pos + 1,
+            node.name(),
+            group.name(),
+            amplification,
+            b.capacity(),
+            saturation
+        )?;
+    }
+
+    writeln!(out)?;
+    match ranked.first() {
+        Some(b) if b.is_unbounded() => writeln!(
+            out,
+            "{} is fed by an amplifying loop and saturates under any entry load",
+            graph.node_by_id(b.node_id()).name()
+        ),
+        Some(b) => writeln!(
+            out,
+            "{} saturates first at {:.1} rps of entry load",
+            graph.node_by_id(b.node_id()).name(),
+            b.saturation_load()
+        ),
+        None => writeln!(out, "No node is reachable from the entry nodes"),
+    }
+}
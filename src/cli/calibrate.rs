@@ -0,0 +1,93 @@
+use crate::analysis::calibration::{RateSeries, calibrate};
+use crate::graph::graph::Graph;
+use crate::graph::node::NodeId;
+use crate::simulation::engine::SimulationEngine;
+use std::io;
+use std::io::Write;
+
+/// calls per request along each edge, `gain * weight / total weight` of its caller
+fn calls_per_request(graph: &Graph) -> Vec<f64> {
+    graph
+        .edges()
+        .iter()
+        .map(|edge| {
+            let total_weight = graph
+                .outgoing(edge.from())
+                .iter()
+                .map(|e_id| graph.edge_by_id(*e_id).weight())
+                .sum::<f64>();
+            if total_weight > 0.0 {
+                graph.node_by_id(edge.from()).gain() * edge.weight() / total_weight
+            } else {
+                0.0
+            }
+        })
+        .collect()
+}
+
+/// Fits the gains and edge weights of `engine`'s graph to `rates`, applies them and prints the
+/// fit of every node and the calls per request before and after.
+pub fn print_calibration(
+    engine: &mut SimulationEngine,
+    rates: &RateSeries,
+    out: &mut impl Write,
+) -> io::Result<()> {
+    let calibration = calibrate(engine.graph(), engine.scenario().entry_nodes(), rates);
+    let before = calls_per_request(engine.graph());
+    calibration.apply(engine.graph_mut());
+    let graph = engine.graph();
+    let after = calls_per_request(graph);
+
+    let unknown = rates
+        .services()
+        .iter()
+        .filter(|s| !graph.nodes().iter().any(|n| n.name() == s.as_str()))
+        .map(String::as_str)
+        .collect::<Vec<_>>();
+    if !unknown.is_empty() {
+        writeln!(out, "Ignored series: {}", unknown.join(", "))?;
+        writeln!(out)?;
+    }
+
+    writeln!(
+        out,
+        "{:<20} {:>8} {:>8} {:>10}",
+        "Node", "Samples", "R²", "RMSE"
+    )?;
+    for fit in calibration.fits() {
+        writeln!(
+            out,
+            "{:<20} {:>8} {:>8.3} {:>10.2}",
+            graph.node_by_id(fit.node_id()).name(),
+            fit.samples(),
+            fit.r_squared(),
+            fit.rmse()
+        )?;
+    }
+    for (node_id, reason) in calibration.skipped() {
+        writeln!(
+            out,
+            "{:<20} skipped: {}",
+            graph.node_by_id(*node_id).name(),
+            reason
+        )?;
+    }
+
+    writeln!(out)?;
+    writeln!(
+        out,
+        "{:<32} {:>10} {:>10}",
+        "Calls per request", "Before", "After"
+    )?;
+    for (edge, (before, after)) in graph.edges().iter().zip(before.iter().zip(&after)) {
+        let name = |id: NodeId| graph.node_by_id(id).name();
+        writeln!(
+            out,
+            "{:<32} {:>10.3} {:>10.3}",
+            format!("{} → {}", name(edge.from()), name(edge.to())),
+            before,
+            after
+        )?;
+    }
+    Ok(())
+}
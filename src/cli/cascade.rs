@@ -0,0 +1,39 @@
+use crate::analysis::cascade::reconstruct_cascade;
+use crate::simulation::engine::SimulationEngine;
+use std::io;
+use std::io::Write;
+
+pub fn print_cascade(
+    engine: &mut SimulationEngine,
+    turns: usize,
+    out: &mut impl Write,
+) -> io::Result<()> {
+    (0..turns).for_each(|_| engine.step());
+
+    let graph = engine.graph();
+    let groups = engine.groups();
+    let tree = reconstruct_cascade(graph, groups, &engine.snapshots());
+
+    let Some(root) = tree.root_overload() else {
+        return writeln!(out, "No node failed in {} turns", turns);
+    };
+    writeln!(
+        out,
+        "{} nodes failed in {} turns, root overload: {} ({}) at turn {}",
+        tree.events().len(),
+        turns,
+        graph.node_by_id(root.node_id()).name(),
+        groups.groups()[groups.group_by_node_id(root.node_id().index())].name(),
+        root.overloaded_at()
+    )?;
+    writeln!(out)?;
+    for (depth, event) in tree.walk() {
+        let indent = if depth == 0 {
+            String::new()
+        } else {
+            format!("{}└─ ", "   ".repeat(depth - 1))
+        };
+        writeln!(out, "{}{}", indent, event.describe(graph, groups))?;
+    }
+    Ok(())
+}
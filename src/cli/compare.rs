@@ -0,0 +1,90 @@
+use crate::analysis::comparison::{GroupDiff, Outcome, compare_runs};
+use crate::analysis::metrics::RunMetrics;
+use crate::simulation::engine::SimulationEngine;
+use crate::simulation::schedule::Schedule;
+use std::fs::File;
+use std::io;
+use std::io::{BufReader, Write};
+use std::path::Path;
+
+/// Runs `engine` headless for `turns`, applying `schedule` before each step.
+pub fn record_run(engine: &mut SimulationEngine, schedule: &Schedule, turns: usize) -> RunMetrics {
+    schedule.run(engine, turns);
+    RunMetrics::from_snapshots(engine.graph(), engine.groups(), &engine.snapshots())
+}
+
+pub fn read_run(path: &Path) -> io::Result<RunMetrics> {
+    RunMetrics::read_csv(BufReader::new(File::open(path)?))
+        .map_err(|e| io::Error::new(e.kind(), format!("{}: {}", path.display(), e)))
+}
+
+pub fn print_comparison(
+    baseline: &RunMetrics,
+    variant: &RunMetrics,
+    out: &mut impl Write,
+) -> io::Result<()> {
+    let comparison = compare_runs(baseline, variant);
+    let Some(last_turn) = comparison.last_turn() else {
+        return writeln!(out, "The runs have no turns in common");
+    };
+
+    match comparison.diverged_at() {
+        Some(turn) => writeln!(
+            out,
+            "Runs diverge at turn {}, compared up to turn {}",
+            turn, last_turn
+        )?,
+        None => writeln!(out, "Runs are identical up to turn {}", last_turn)?,
+    }
+    writeln!(out)?;
+    writeln!(
+        out,
+        "{:<20} {:>13} {:>8} {:>8} {:>13} {:>8} {:>15} {:>8} {:>9} {:>8}",
+        "Group", "Health", "Δ", "Max Δ", "Util", "Δ", "Backlog", "Δ", "Spend Δ", "Outcome"
+    )?;
+
+    for diff in comparison.groups() {
+        let (b, v) = (diff.baseline(), diff.variant());
+        let outcome = match diff.outcome() {
+            Outcome::Saved => "saved",
+            Outcome::Lost => "lost",
+            Outcome::Unchanged => "",
+        };
+        writeln!(
+            out,
+            "{:<20} {:>5.2} → {:>5.2} {:>+8.2} {:>8.2} {:>4.0}% → {:>4.0}% {:>+7.0}% {:>6.1} → {:>6.1} {:>+8.1} {:>+9.1} {:>8}",
+            diff.name(),
+            b.health(),
+            v.health(),
+            diff.health_diff(),
+            diff.max_health_gap(),
+            b.utilization() * 100.0,
+            v.utilization() * 100.0,
+            diff.utilization_diff() * 100.0,
+            b.backlog(),
+            v.backlog(),
+            diff.backlog_diff(),
+            diff.spend_diff(),
+            outcome
+        )?;
+    }
+
+    let count = |outcome: Outcome| {
+        comparison
+            .groups()
+            .iter()
+            .filter(|d| *d.outcome() == outcome)
+            .count()
+    };
+    writeln!(out)?;
+    let spend = |f: fn(&GroupDiff) -> f64| comparison.groups().iter().map(f).sum::<f64>();
+    writeln!(
+        out,
+        "{} groups saved, {} groups lost, spend {:.1} → {:.1} ({:+.1})",
+        count(Outcome::Saved),
+        count(Outcome::Lost),
+        spend(GroupDiff::baseline_spend),
+        spend(GroupDiff::variant_spend),
+        comparison.spend_diff()
+    )
+}
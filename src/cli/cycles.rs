@@ -0,0 +1,62 @@
+use crate::analysis::cycles::find_feedback_loops;
+use crate::simulation::engine::SimulationEngine;
+use std::io;
+use std::io::Write;
+
+/// Prints every feedback loop and returns how many of them are unstable.
+pub fn print_feedback_loops(engine: &SimulationEngine, out: &mut impl Write) -> io::Result<usize> {
+    let graph = engine.graph();
+    let groups = engine.groups();
+    let loops = find_feedback_loops(graph);
+
+    if loops.is_empty() {
+        writeln!(
+            out,
+            "No feedback loops, no load can come back to where it was sent from"
+        )?;
+        return Ok(0);
+    }
+
+    for (pos, l) in loops.iter().enumerate() {
+        let group_names = groups
+            .groups_of(l.nodes())
+            .iter()
+            .map(|g_id| groups.groups()[*g_id].name())
+            .collect::<Vec<&str>>()
+            .join(", ");
+
+        writeln!(
+            out,
+            "{:>4}  {:<8}  loop gain {:.3}  {} nodes",
+            pos + 1,
+            if l.is_unstable() {
+                "UNSTABLE"
+            } else {
+                "stable"
+            },
+            l.loop_gain(),
+            l.nodes().len()
+        )?;
+        writeln!(out, "      groups: {}", group_names)?;
+        writeln!(
+            out,
+            "      cycle:  {} (x{:.3} per round trip)",
+            l.cycle()
+                .iter()
+                .map(|id| graph.node_by_id(*id).name())
+                .collect::<Vec<&str>>()
+                .join(" → "),
+            l.cycle_gain()
+        )?;
+    }
+
+    let unstable = loops.iter().filter(|l| l.is_unstable()).count();
+    writeln!(out)?;
+    writeln!(
+        out,
+        "{} feedback loops, {} unstable (loop gain >= 1 amplifies load without bound)",
+        loops.len(),
+        unstable
+    )?;
+    Ok(unstable)
+}
@@ -0,0 +1,71 @@
+use crate::analysis::analysis::calc_health;
+use crate::analysis::groups::GroupHealth;
+use crate::simulation::environment::Environment;
+use crate::simulation::policy::PolicyKind;
+use std::io;
+use std::io::Write;
+
+/// Plays an episode per seed with each policy and prints their returns side by side.
+pub fn print_episodes(
+    env: &mut Environment,
+    policies: &[PolicyKind],
+    seeds: &[u64],
+    out: &mut impl Write,
+) -> io::Result<()> {
+    writeln!(
+        out,
+        "{} episodes, {} features per observation, {} actions",
+        seeds.len(),
+        env.observation_size(),
+        env.actions().len()
+    )?;
+    writeln!(out)?;
+    writeln!(
+        out,
+        "{:<10} {:>9} {:>9} {:>9} {:>12} {:>8} {:>7}",
+        "Policy", "Return", "Min", "Max", "Mean reward", "Actions", "Failed"
+    )?;
+
+    for kind in policies {
+        let (mut returns, mut turns, mut applied, mut failed) = (vec![], 0, 0, 0);
+        for seed in seeds {
+            let mut policy = kind.build(*seed);
+            env.reset(*seed);
+            let mut total = 0.0;
+            while !env.is_done() {
+                for action in policy.decide(env.engine(), &env.summaries()) {
+                    if env.act(action) {
+                        applied += 1;
+                    }
+                }
+                total += env.step();
+                turns += 1;
+            }
+            let engine = env.engine();
+            failed += engine
+                .groups()
+                .groups()
+                .iter()
+                .filter(|g| {
+                    GroupHealth::classify(calc_health(engine.current_snapshot(), g))
+                        == GroupHealth::Failed
+                })
+                .count();
+            returns.push(total);
+        }
+
+        let episodes = seeds.len().max(1) as f64;
+        writeln!(
+            out,
+            "{:<10} {:>9.2} {:>9.2} {:>9.2} {:>12.3} {:>8.1} {:>7.1}",
+            kind.build(0).name(),
+            returns.iter().sum::<f64>() / episodes,
+            returns.iter().copied().fold(f64::INFINITY, f64::min),
+            returns.iter().copied().fold(f64::NEG_INFINITY, f64::max),
+            returns.iter().sum::<f64>() / turns.max(1) as f64,
+            applied as f64 / episodes,
+            failed as f64 / episodes
+        )?;
+    }
+    Ok(())
+}
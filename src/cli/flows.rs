@@ -0,0 +1,40 @@
+use crate::analysis::flow::flow_matrix;
+use crate::simulation::engine::SimulationEngine;
+use std::io;
+use std::io::Write;
+
+/// Prints the group-to-group flow matrix of the current turn, sources as rows.
+pub fn print_flows(engine: &SimulationEngine, out: &mut impl Write) -> io::Result<()> {
+    let names = engine
+        .groups()
+        .groups()
+        .iter()
+        .map(|g| g.name())
+        .collect::<Vec<&str>>();
+    let matrix = flow_matrix(engine.graph(), engine.groups(), engine.current_snapshot());
+
+    writeln!(
+        out,
+        "Load between groups at turn {} (rps, from row to column)",
+        engine.current_snapshot().turn()
+    )?;
+    writeln!(out)?;
+    write!(out, "{:<20}", "")?;
+    for name in &names {
+        write!(out, " {:>12}", name)?;
+    }
+    writeln!(out, " {:>12}", "Total")?;
+
+    for (from, row) in matrix.iter().enumerate() {
+        write!(out, "{:<20}", names[from])?;
+        for load in row {
+            if *load > 0.0 {
+                write!(out, " {:>12.1}", load)?;
+            } else {
+                write!(out, " {:>12}", "·")?;
+            }
+        }
+        writeln!(out, " {:>12.1}", row.iter().sum::<f64>())?;
+    }
+    Ok(())
+}
@@ -0,0 +1,57 @@
+use crate::simulation::game::{Game, HighScore, Outcome, POINTS_PER_OP};
+use std::io;
+use std::io::Write;
+
+/// Prints the outcome and score of a finished `game` and the high scores it was ranked in.
+pub fn print_game(
+    game: &Game,
+    scores: &[HighScore],
+    rank: Option<usize>,
+    out: &mut impl Write,
+) -> io::Result<()> {
+    match game.outcome() {
+        Some(Outcome::Won) => writeln!(out, "Won after {} turns", game.turns())?,
+        Some(Outcome::Lost(reason)) => writeln!(out, "Lost: {}", reason)?,
+        None => writeln!(out, "Stopped at turn {}", game.turns())?,
+    }
+    for objective in game.objectives().describe() {
+        writeln!(out, "  · {}", objective)?;
+    }
+    writeln!(out)?;
+    writeln!(out, "{:<20} {:>8.0}", "Health", game.health_points())?;
+    writeln!(out, "{:<20} {:>8.0}", "Goodput", game.goodput_points())?;
+    writeln!(
+        out,
+        "{:<20} {:>8.0}",
+        format!("Ops spent ({})", game.ops_spent()),
+        0.0 - POINTS_PER_OP * game.ops_spent() as f64
+    )?;
+    writeln!(out, "{:<20} {:>8.0}", "Score", game.score())?;
+
+    writeln!(out)?;
+    writeln!(
+        out,
+        "{:>3}  {:<12} {:>8} {:>6} {:>5}",
+        "#", "Player", "Score", "Turns", "Won"
+    )?;
+    for (pos, score) in scores.iter().enumerate() {
+        writeln!(
+            out,
+            "{:>3}  {:<12} {:>8.0} {:>6} {:>5}{}",
+            pos + 1,
+            score.player,
+            score.score,
+            score.turns,
+            if score.won { "yes" } else { "no" },
+            if Some(pos) == rank {
+                "  ← this run"
+            } else {
+                ""
+            }
+        )?;
+    }
+    if rank.is_none() {
+        writeln!(out, "This run did not make the high scores")?;
+    }
+    Ok(())
+}
@@ -0,0 +1,40 @@
+use crate::analysis::goodput::goodput;
+use crate::simulation::engine::SimulationEngine;
+use std::io;
+use std::io::Write;
+
+/// Runs `engine` for `turns` and prints where the entry load went every `every` turns.
+pub fn print_goodput(
+    engine: &mut SimulationEngine,
+    turns: usize,
+    every: usize,
+    out: &mut impl Write,
+) -> io::Result<()> {
+    (0..turns).for_each(|_| engine.step());
+
+    writeln!(
+        out,
+        "{:>5} {:>9} {:>9} {:>8} {:>9} {:>9} {:>9}",
+        "Turn", "Offered", "Goodput", "Success", "Failed", "Disabled", "Backlog"
+    )?;
+    for snapshot in engine
+        .snapshots()
+        .iter()
+        .skip(every.max(1))
+        .step_by(every.max(1))
+    {
+        let result = goodput(engine.graph(), snapshot, engine.scenario().entry_nodes());
+        writeln!(
+            out,
+            "{:>5} {:>9.1} {:>9.1} {:>7.1}% {:>9.1} {:>9.1} {:>9.1}",
+            snapshot.turn(),
+            result.offered(),
+            result.goodput(),
+            result.success_rate() * 100.0,
+            result.lost_failed(),
+            result.lost_disabled(),
+            result.lost_backlog()
+        )?;
+    }
+    Ok(())
+}
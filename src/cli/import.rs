@@ -0,0 +1,49 @@
+use crate::scenario::topology::{DEFAULT_CAPACITY, Topology};
+use std::io;
+use std::io::Write;
+
+/// Summarises an imported topology and lists the capacities and entry loads still to fill in.
+pub fn print_import(topology: &Topology, out: &mut impl Write) -> io::Result<()> {
+    writeln!(
+        out,
+        "{} services in {} groups, {} edges, {} entry services",
+        topology.groups.iter().map(|g| g.nodes.len()).sum::<usize>(),
+        topology.groups.len(),
+        topology.edges.len(),
+        topology.entry.len()
+    )?;
+    let unset = topology.unset_capacities();
+    if !unset.is_empty() {
+        writeln!(
+            out,
+            "No capacity for {} services, {} rps until filled in: {}",
+            unset.len(),
+            DEFAULT_CAPACITY,
+            unset.join(", ")
+        )?;
+    }
+    let scaled_to_zero = topology
+        .groups
+        .iter()
+        .flat_map(|g| &g.nodes)
+        .filter(|n| n.capacity == Some(0.0))
+        .map(|n| n.name.as_str())
+        .collect::<Vec<_>>();
+    if !scaled_to_zero.is_empty() {
+        writeln!(
+            out,
+            "No capacity, scaled to zero: {}",
+            scaled_to_zero.join(", ")
+        )?;
+    }
+    let idle = topology
+        .entry
+        .iter()
+        .filter(|e| e.load == 0.0)
+        .map(|e| e.node.as_str())
+        .collect::<Vec<_>>();
+    if !idle.is_empty() {
+        writeln!(out, "No entry load for: {}", idle.join(", "))?;
+    }
+    Ok(())
+}
@@ -0,0 +1,62 @@
+use crate::analysis::latency::{mean_latency, node_latencies};
+use crate::simulation::engine::SimulationEngine;
+use std::io;
+use std::io::Write;
+
+fn format_ms(latency: Option<f64>) -> String {
+    match latency {
+        Some(ms) if ms.is_finite() => format!("{:.1}", ms),
+        Some(_) => "∞".to_string(),
+        None => "-".to_string(),
+    }
+}
+
+/// Runs `engine` for `turns` and prints the mean end-to-end latency, the slowest node and the
+/// average health every `every` turns.
+pub fn print_latency(
+    engine: &mut SimulationEngine,
+    turns: usize,
+    every: usize,
+    out: &mut impl Write,
+) -> io::Result<()> {
+    (0..turns).for_each(|_| engine.step());
+
+    writeln!(
+        out,
+        "{:>5} {:>10} {:<20} {:>10} {:>9}",
+        "Turn", "Mean ms", "Slowest node", "ms", "Health %"
+    )?;
+    for snapshot in engine
+        .snapshots()
+        .iter()
+        .skip(every.max(1))
+        .step_by(every.max(1))
+    {
+        let latencies = node_latencies(engine.graph(), engine.groups(), snapshot);
+        let e2e = mean_latency(
+            engine.graph(),
+            snapshot,
+            &latencies,
+            engine.scenario().entry_nodes(),
+        );
+        let slowest = latencies
+            .iter()
+            .enumerate()
+            .filter_map(|(i, l)| l.map(|l| (i, l)))
+            .max_by(|a, b| a.1.total_cmp(&b.1));
+        let states = snapshot.node_states();
+        let avg_health =
+            states.iter().map(|s| s.health()).sum::<f64>() / states.len().max(1) as f64;
+
+        writeln!(
+            out,
+            "{:>5} {:>10} {:<20} {:>10} {:>9.1}",
+            snapshot.turn(),
+            format_ms(e2e),
+            slowest.map_or("-", |(i, _)| engine.graph().nodes()[i].name()),
+            format_ms(slowest.map(|(_, l)| l)),
+            avg_health * 100.0
+        )?;
+    }
+    Ok(())
+}
@@ -0,0 +1,15 @@
+pub mod blast_radius;
+pub mod bottlenecks;
+pub mod calibrate;
+pub mod cascade;
+pub mod compare;
+pub mod cycles;
+pub mod episodes;
+pub mod flows;
+pub mod game;
+pub mod goodput;
+pub mod import;
+pub mod latency;
+pub mod monte_carlo;
+pub mod sensitivity;
+pub mod slo;
@@ -0,0 +1,76 @@
+use crate::analysis::monte_carlo::{MonteCarlo, percentile};
+use std::io;
+use std::io::Write;
+
+const PERCENTILES: [f64; 3] = [10.0, 50.0, 90.0];
+
+fn median_failure_turn(mc: &MonteCarlo, group_id: usize) -> String {
+    let turns = mc
+        .failure_turns(group_id)
+        .into_iter()
+        .flatten()
+        .map(|t| t as f64)
+        .collect::<Vec<f64>>();
+    percentile(turns, 50.0).map_or("-".to_string(), |t| format!("{:.0}", t))
+}
+
+/// Prints failure probabilities by `by_turn` and p10/p50/p90 health every `interval` turns.
+pub fn print_monte_carlo(
+    mc: &MonteCarlo,
+    turns: usize,
+    by_turn: usize,
+    interval: usize,
+    out: &mut impl Write,
+) -> io::Result<()> {
+    let groups = mc.groups();
+    let seeds = mc.seeds();
+    writeln!(
+        out,
+        "{} runs over {} turns, seeds {}..={}",
+        seeds.len(),
+        turns,
+        seeds.first().copied().unwrap_or_default(),
+        seeds.last().copied().unwrap_or_default()
+    )?;
+    writeln!(out)?;
+    writeln!(
+        out,
+        "{:<20} {:>16} {:>14}",
+        "Group",
+        format!("P(failed by {})", by_turn),
+        "Median turn"
+    )?;
+    for (g_id, name) in groups.iter().enumerate() {
+        writeln!(
+            out,
+            "{:<20} {:>15.1}% {:>14}",
+            name,
+            mc.failure_probability(g_id, by_turn) * 100.0,
+            median_failure_turn(mc, g_id)
+        )?;
+    }
+
+    writeln!(out)?;
+    writeln!(out, "Health % (p10 / p50 / p90)")?;
+    write!(out, "{:>5}", "Turn")?;
+    for name in groups {
+        write!(out, "  {:>14}", name)?;
+    }
+    writeln!(out)?;
+    for turn in (0..=turns).step_by(interval.max(1)) {
+        write!(out, "{:>5}", turn)?;
+        for g_id in 0..groups.len() {
+            let cell = PERCENTILES
+                .iter()
+                .map(|p| {
+                    mc.health_percentile(g_id, turn, *p)
+                        .map_or("-".to_string(), |h| format!("{:.0}", h * 100.0))
+                })
+                .collect::<Vec<String>>()
+                .join("/");
+            write!(out, "  {:>14}", cell)?;
+        }
+        writeln!(out)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,64 @@
+use crate::analysis::sensitivity::{ParameterKind, RunOutcome, analyse_sensitivity};
+use crate::scenario::kind::ScenarioSource;
+use std::io;
+use std::io::Write;
+
+fn first_failure(outcome: &RunOutcome) -> String {
+    outcome
+        .first_failure()
+        .map_or("never".to_string(), |turn| turn.to_string())
+}
+
+pub fn print_sensitivity(
+    scenario: &ScenarioSource,
+    kinds: &[ParameterKind],
+    percent: f64,
+    turns: usize,
+    top: usize,
+    out: &mut impl Write,
+) -> io::Result<()> {
+    let sensitivity = analyse_sensitivity(|| scenario.engine(), kinds, percent, turns);
+    let engine = scenario.engine();
+    let graph = engine.graph();
+    let baseline = sensitivity.baseline();
+
+    writeln!(
+        out,
+        "Single parameter changes of ±{}% ranked by outcome after {} turns",
+        percent, turns
+    )?;
+    writeln!(
+        out,
+        "Baseline: avg health {:.1}%, {} failed nodes, first failure at turn {}",
+        baseline.avg_health() * 100.0,
+        baseline.failed_nodes(),
+        first_failure(baseline)
+    )?;
+    writeln!(out, "{} changes tried", sensitivity.effects().len())?;
+    writeln!(out)?;
+    writeln!(
+        out,
+        "{:>4}  {:<36} {:>6} {:>10} {:>8} {:>8} {:>14}",
+        "#", "Parameter", "Change", "Health %", "Δ", "Failed", "First failure"
+    )?;
+
+    for (pos, effect) in sensitivity.effects().iter().take(top).enumerate() {
+        let outcome = effect.outcome();
+        writeln!(
+            out,
+            "{:>4}  {:<36} {:>+5.0}% {:>10.1} {:>+8.1} {:>8} {:>14}",
+            pos + 1,
+            effect.parameter().describe(graph),
+            (effect.factor() - 1.0) * 100.0,
+            outcome.avg_health() * 100.0,
+            (outcome.avg_health() - baseline.avg_health()) * 100.0,
+            format!(
+                "{} ({:+})",
+                outcome.failed_nodes(),
+                outcome.failed_nodes() as i64 - baseline.failed_nodes() as i64
+            ),
+            first_failure(outcome)
+        )?;
+    }
+    Ok(())
+}
@@ -0,0 +1,71 @@
+use crate::analysis::slo::SloVerdict;
+use crate::simulation::engine::SimulationEngine;
+use std::io;
+use std::io::Write;
+
+/// Runs `engine` for `turns` and prints each SLO's verdict. Returns the number of breached SLOs.
+pub fn print_slos(
+    engine: &mut SimulationEngine,
+    turns: usize,
+    out: &mut impl Write,
+) -> io::Result<usize> {
+    (0..turns).for_each(|_| engine.step());
+
+    if engine.slos().is_empty() {
+        writeln!(out, "The scenario declares no SLOs, add some with --slo")?;
+        return Ok(0);
+    }
+    writeln!(out, "SLOs after {} turns", turns)?;
+    writeln!(out)?;
+    writeln!(
+        out,
+        "{:<40} {:>11} {:>6} {:>8} {:>14} {:>9}",
+        "SLO", "Attainment", "Burn", "Budget", "First breach", "Verdict"
+    )?;
+
+    let mut breached = 0;
+    for (slo, track) in engine.slos().iter().zip(engine.slo_tracks()) {
+        let Some(last) = track.last() else {
+            continue;
+        };
+        let first_breach = track
+            .turns()
+            .iter()
+            .find(|t| t.budget_remaining() < 0.0)
+            .map_or("-".to_string(), |t| t.turn().to_string());
+        let verdict = track.verdict();
+        if verdict == SloVerdict::Breached {
+            breached += 1;
+        }
+        writeln!(
+            out,
+            "{:<40} {:>10.2}% {:>6.1} {:>7.0}% {:>14} {:>9}",
+            slo.describe(engine.graph(), engine.groups()),
+            last.attainment() * 100.0,
+            last.burn_rate(),
+            last.budget_remaining().max(-9.99) * 100.0,
+            first_breach,
+            verdict.to_string()
+        )?;
+    }
+    Ok(breached)
+}
+
+pub fn write_slo_csv(engine: &SimulationEngine, out: &mut impl Write) -> io::Result<()> {
+    writeln!(out, "turn,slo,attainment,burn_rate,budget_remaining")?;
+    for (slo, track) in engine.slos().iter().zip(engine.slo_tracks()) {
+        let name = slo.describe(engine.graph(), engine.groups());
+        for t in track.turns() {
+            writeln!(
+                out,
+                "{},\"{}\",{},{},{}",
+                t.turn(),
+                name,
+                t.attainment(),
+                t.burn_rate(),
+                t.budget_remaining()
+            )?;
+        }
+    }
+    Ok(())
+}
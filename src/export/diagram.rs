@@ -0,0 +1,256 @@
+use crate::analysis::groups::{GroupHealth, GroupSet};
+use crate::graph::graph::Graph;
+use crate::state::snapshot::Snapshot;
+use clap::ValueEnum;
+use std::io;
+use std::io::Write;
+
+#[derive(Clone, Copy, Debug, PartialEq, ValueEnum)]
+pub enum DiagramFormat {
+    Dot,
+    Mermaid,
+}
+
+/// fill colour of a node or group with `health`, by `GroupHealth` class
+pub fn health_color(health: f64) -> &'static str {
+    match GroupHealth::classify(health) {
+        GroupHealth::Ok => "#a6e3a1",
+        GroupHealth::Degraded => "#f9e2af",
+        GroupHealth::Critical => "#f38ba8",
+        GroupHealth::Failed => "#9399b2",
+    }
+}
+
+/// Edge loads in `snapshot` and the largest of them, for scaling line widths.
+pub fn edge_loads(graph: &Graph, snapshot: &Snapshot) -> (Vec<f64>, f64) {
+    let loads = graph
+        .edges()
+        .iter()
+        .map(|e| snapshot.edge_load(e.id(), graph))
+        .collect::<Vec<f64>>();
+    let max = loads.iter().fold(0.0, |acc: f64, l| acc.max(*l));
+    (loads, max)
+}
+
+/// line width from 1 to 5 by share of the largest edge load
+pub fn line_width(load: f64, max: f64) -> f64 {
+    if max > 0.0 {
+        1.0 + 4.0 * load / max
+    } else {
+        1.0
+    }
+}
+
+fn node_label(graph: &Graph, snapshot: Option<&Snapshot>, i: usize) -> String {
+    let node = &graph.nodes()[i];
+    match snapshot {
+        Some(s) => format!(
+            "{}\\n{:.0}/{:.0} rps · {:.0}%",
+            node.name(),
+            s.node_states()[i].served(),
+            node.capacity(),
+            s.node_states()[i].health() * 100.0
+        ),
+        None => format!("{}\\n{:.0} rps", node.name(), node.capacity()),
+    }
+}
+
+/// Graphviz DOT with groups as clusters. With a `snapshot`, nodes are coloured by health, edges
+/// are as thick as their share of the largest edge load and disabled edges are dashed.
+pub fn write_dot(
+    graph: &Graph,
+    groups: &GroupSet,
+    snapshot: Option<&Snapshot>,
+    out: &mut impl Write,
+) -> io::Result<()> {
+    writeln!(out, "digraph faultgraph {{")?;
+    writeln!(out, "  rankdir=LR;")?;
+    writeln!(
+        out,
+        "  node [shape=box, style=\"rounded,filled\", fillcolor=white];"
+    )?;
+    for (g_id, group) in groups.groups().iter().enumerate() {
+        writeln!(out, "  subgraph cluster_{} {{", g_id)?;
+        writeln!(out, "    label=\"{}\";", group.name().replace('"', "\\\""))?;
+        for id in group.nodes() {
+            let i = id.index();
+            let label = node_label(graph, snapshot, i).replace('"', "\\\"");
+            match snapshot {
+                Some(s) => writeln!(
+                    out,
+                    "    n{} [label=\"{}\", fillcolor=\"{}\"];",
+                    i,
+                    label,
+                    health_color(s.node_states()[i].health())
+                )?,
+                None => writeln!(out, "    n{} [label=\"{}\"];", i, label)?,
+            }
+        }
+        writeln!(out, "  }}")?;
+    }
+
+    let loads = snapshot.map(|s| (s, edge_loads(graph, s)));
+    for edge in graph.edges() {
+        let (from, to) = (edge.from().index(), edge.to().index());
+        match &loads {
+            Some((s, (loads, max))) => {
+                let load = loads[edge.id().index()];
+                let style = if s.edge_states()[edge.id().index()].is_enabled() {
+                    "solid"
+                } else {
+                    "dashed"
+                };
+                writeln!(
+                    out,
+                    "  n{} -> n{} [label=\"{:.1}\", penwidth={:.2}, style={}];",
+                    from,
+                    to,
+                    load,
+                    line_width(load, *max),
+                    style
+                )?
+            }
+            None => writeln!(out, "  n{} -> n{} [label=\"{}\"];", from, to, edge.weight())?,
+        }
+    }
+    writeln!(out, "}}")
+}
+
+/// Mermaid flowchart with groups as subgraphs, styled like `write_dot`.
+pub fn write_mermaid(
+    graph: &Graph,
+    groups: &GroupSet,
+    snapshot: Option<&Snapshot>,
+    out: &mut impl Write,
+) -> io::Result<()> {
+    writeln!(out, "flowchart LR")?;
+    for (g_id, group) in groups.groups().iter().enumerate() {
+        writeln!(
+            out,
+            "  subgraph g{} [\"{}\"]",
+            g_id,
+            group.name().replace('"', "#quot;")
+        )?;
+        for id in group.nodes() {
+            let label = node_label(graph, snapshot, id.index())
+                .replace("\\n", "<br/>")
+                .replace('"', "#quot;");
+            writeln!(out, "    n{}[\"{}\"]", id.index(), label)?;
+        }
+        writeln!(out, "  end")?;
+    }
+
+    let loads = snapshot.map(|s| (s, edge_loads(graph, s)));
+    for edge in graph.edges() {
+        let (from, to) = (edge.from().index(), edge.to().index());
+        match &loads {
+            Some((s, (loads, _))) => {
+                let arrow = if s.edge_states()[edge.id().index()].is_enabled() {
+                    "-->"
+                } else {
+                    "-.->"
+                };
+                let load = loads[edge.id().index()];
+                writeln!(out, "  n{} {}|{:.1}| n{}", from, arrow, load, to)?
+            }
+            None => writeln!(out, "  n{} -->|{}| n{}", from, edge.weight(), to)?,
+        }
+    }
+
+    if let Some((s, (loads, max))) = &loads {
+        for (i, state) in s.node_states().iter().enumerate() {
+            writeln!(out, "  style n{} fill:{}", i, health_color(state.health()))?;
+        }
+        // links are numbered in the order they were declared, which is edge order
+        for (i, load) in loads.iter().enumerate() {
+            writeln!(
+                out,
+                "  linkStyle {} stroke-width:{:.1}px",
+                i,
+                line_width(*load, *max)
+            )?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::groups::Group;
+    use crate::graph::edge::{Edge, EdgeId};
+    use crate::graph::node::{Node, NodeId};
+    use crate::simulation::modifiers::CapacityModifier;
+    use crate::state::edge_state::EdgeState;
+    use crate::state::node_state::NodeState;
+
+    // api → db1
+    //   ↘ db2 (disabled)
+    fn setup() -> (Graph, GroupSet, Snapshot) {
+        let api = Node::new(NodeId(0), "api".to_string(), 100.0, 1.0);
+        let db1 = Node::new(NodeId(1), "db1".to_string(), 50.0, 0.0);
+        let db2 = Node::new(NodeId(2), "db2".to_string(), 50.0, 0.0);
+        let graph = Graph::new(
+            vec![api, db1, db2],
+            vec![
+                Edge::new(EdgeId(0), NodeId(0), NodeId(1), 1.0),
+                Edge::new(EdgeId(1), NodeId(0), NodeId(2), 1.0),
+            ],
+        );
+        let groups = GroupSet::new(vec![
+            Group::new("Api".to_string(), vec![NodeId(0)]),
+            Group::new("Db".to_string(), vec![NodeId(1), NodeId(2)]),
+        ]);
+        let snapshot = Snapshot::new(
+            3,
+            vec![
+                NodeState::new(60.0, 60.0, 0.0, 1.0),
+                NodeState::new(60.0, 50.0, 10.0, 0.5),
+                NodeState::new(0.0, 0.0, 0.0, 0.0),
+            ],
+            vec![EdgeState::new(true), EdgeState::new(false)],
+            vec![CapacityModifier::new(); 2],
+        );
+        (graph, groups, snapshot)
+    }
+
+    fn render(
+        write: fn(&Graph, &GroupSet, Option<&Snapshot>, &mut Vec<u8>) -> io::Result<()>,
+        styled: bool,
+    ) -> String {
+        let (graph, groups, snapshot) = setup();
+        let mut out = vec![];
+        write(&graph, &groups, styled.then_some(&snapshot), &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_dot_clusters_and_state() {
+        let plain = render(write_dot, false);
+        assert!(
+            plain.contains(
+                "subgraph cluster_1 {\n    label=\"Db\";\n    n1 [label=\"db1\\n50 rps\"];"
+            )
+        );
+        assert!(plain.contains("n0 -> n2 [label=\"1\"];"));
+
+        let styled = render(write_dot, true);
+        assert!(styled.contains("n1 [label=\"db1\\n50/50 rps · 50%\", fillcolor=\"#f9e2af\"];"));
+        assert!(styled.contains("n0 -> n1 [label=\"60.0\", penwidth=5.00, style=solid];"));
+        assert!(styled.contains("n0 -> n2 [label=\"0.0\", penwidth=1.00, style=dashed];"));
+    }
+
+    #[test]
+    fn test_mermaid_subgraphs_and_state() {
+        let plain = render(write_mermaid, false);
+        assert!(plain.starts_with(
+            "flowchart LR\n  subgraph g0 [\"Api\"]\n    n0[\"api<br/>100 rps\"]\n  end\n"
+        ));
+        assert!(!plain.contains("style"));
+
+        let styled = render(write_mermaid, true);
+        assert!(styled.contains("  n0 -->|60.0| n1\n  n0 -.->|0.0| n2\n"));
+        assert!(styled.contains("  style n2 fill:#9399b2\n"));
+        assert!(styled.contains("  linkStyle 0 stroke-width:5.0px\n"));
+    }
+}
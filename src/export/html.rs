@@ -0,0 +1,444 @@
+use crate::analysis::analysis::aggregate_groups;
+use crate::analysis::cascade::reconstruct_cascade;
+use crate::analysis::groups::{GroupSet, GroupTrend};
+use crate::analysis::metrics::{GroupMetrics, RunMetrics};
+use crate::export::diagram::{edge_loads, health_color, line_width};
+use crate::graph::graph::Graph;
+use crate::state::snapshot::Snapshot;
+use std::io;
+use std::io::Write;
+
+/// line colours of the groups in the charts, repeated when there are more groups
+const PALETTE: [&str; 8] = [
+    "#1e66f5", "#d20f39", "#40a02b", "#df8e1d", "#8839ef", "#179299", "#fe640b", "#7287fd",
+];
+
+const STYLE: &str = "body{font-family:sans-serif;margin:2em;color:#222}\
+table{border-collapse:collapse}\
+th,td{padding:2px 12px;text-align:right;border-bottom:1px solid #ddd}\
+th:first-child,td:first-child{text-align:left}\
+svg{display:block;margin:0.5em 0 1.5em}\
+.legend span{margin-right:1.5em}";
+
+const MARGIN: f64 = 40.0;
+const CHART_WIDTH: f64 = 720.0;
+const CHART_HEIGHT: f64 = 180.0;
+const NODE_WIDTH: f64 = 120.0;
+const NODE_HEIGHT: f64 = 36.0;
+const COLUMN_GAP: f64 = 80.0;
+const ROW_GAP: f64 = 14.0;
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn trend(trend: &GroupTrend) -> &'static str {
+    match trend {
+        GroupTrend::Up => "↗",
+        GroupTrend::Down => "↘",
+        GroupTrend::Flat => "→",
+    }
+}
+
+/// Capacity modifiers that became active, as `(turn, group, factor)`.
+fn operator_actions(snapshots: &[&Snapshot]) -> Vec<(usize, usize, f64)> {
+    snapshots
+        .windows(2)
+        .flat_map(|pair| {
+            let (previous, current) = (pair[0], pair[1]);
+            current
+                .capacity_mods()
+                .iter()
+                .enumerate()
+                .filter(|(g_id, m)| m.is_active() && !previous.capacity_mods()[*g_id].is_active())
+                .map(|(g_id, m)| (current.turn(), g_id, m.factor()))
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+/// Groups as columns in declaration order, nodes coloured by health and edges sized by load
+/// in `snapshot`.
+fn write_topology(
+    graph: &Graph,
+    groups: &GroupSet,
+    snapshot: &Snapshot,
+    out: &mut impl Write,
+) -> io::Result<()> {
+    let column = NODE_WIDTH + COLUMN_GAP;
+    let row = NODE_HEIGHT + ROW_GAP;
+    let mut positions = vec![(0.0, 0.0); graph.node_count()];
+    for (g_id, group) in groups.groups().iter().enumerate() {
+        for (k, id) in group.nodes().iter().enumerate() {
+            positions[id.index()] = (MARGIN + g_id as f64 * column, MARGIN + k as f64 * row);
+        }
+    }
+    let rows = groups.groups().iter().map(|g| g.nodes().len()).max();
+    let width = 2.0 * MARGIN + groups.groups().len() as f64 * column - COLUMN_GAP;
+    let height = 2.0 * MARGIN + rows.unwrap_or(0) as f64 * row;
+
+    writeln!(
+        out,
+        "<svg width=\"{:.0}\" height=\"{:.0}\" font-size=\"12\">",
+        width, height
+    )?;
+    writeln!(
+        out,
+        "<defs><marker id=\"arrow\" viewBox=\"0 0 10 10\" refX=\"10\" refY=\"5\" markerWidth=\"6\" markerHeight=\"6\" orient=\"auto\"><path d=\"M0,0 L10,5 L0,10 z\" fill=\"#888\"/></marker></defs>"
+    )?;
+    for (g_id, group) in groups.groups().iter().enumerate() {
+        writeln!(
+            out,
+            "<text x=\"{:.0}\" y=\"{:.0}\" font-weight=\"bold\">{}</text>",
+            MARGIN + g_id as f64 * column,
+            MARGIN - 10.0,
+            escape(group.name())
+        )?;
+    }
+
+    let (loads, max) = edge_loads(graph, snapshot);
+    for edge in graph.edges() {
+        let (x1, y1) = positions[edge.from().index()];
+        let (x2, y2) = positions[edge.to().index()];
+        let dash = if snapshot.edge_states()[edge.id().index()].is_enabled() {
+            ""
+        } else {
+            " stroke-dasharray=\"4 3\""
+        };
+        let load = loads[edge.id().index()];
+        writeln!(
+            out,
+            "<line x1=\"{:.0}\" y1=\"{:.0}\" x2=\"{:.0}\" y2=\"{:.0}\" stroke=\"#888\" stroke-width=\"{:.1}\"{} marker-end=\"url(#arrow)\"><title>{:.1} rps</title></line>",
+            x1 + NODE_WIDTH,
+            y1 + NODE_HEIGHT / 2.0,
+            x2,
+            y2 + NODE_HEIGHT / 2.0,
+            line_width(load, max),
+            dash,
+            load
+        )?;
+    }
+
+    for node in graph.nodes() {
+        let (x, y) = positions[node.id().index()];
+        let state = &snapshot.node_states()[node.id().index()];
+        writeln!(
+            out,
+            "<rect x=\"{:.0}\" y=\"{:.0}\" width=\"{:.0}\" height=\"{:.0}\" rx=\"6\" fill=\"{}\" stroke=\"#555\"/>",
+            x,
+            y,
+            NODE_WIDTH,
+            NODE_HEIGHT,
+            health_color(state.health())
+        )?;
+        writeln!(
+            out,
+            "<text x=\"{:.0}\" y=\"{:.0}\">{}</text><text x=\"{:.0}\" y=\"{:.0}\">{:.0}/{:.0} rps · {:.0}%</text>",
+            x + 6.0,
+            y + 15.0,
+            escape(node.name()),
+            x + 6.0,
+            y + 29.0,
+            state.served(),
+            node.capacity(),
+            state.health() * 100.0
+        )?;
+    }
+    writeln!(out, "</svg>")
+}
+
+/// One line per group over all turns, with operator actions as dashed verticals.
+fn write_chart(
+    metrics: &RunMetrics,
+    title: &str,
+    value: fn(&GroupMetrics) -> f64,
+    actions: &[usize],
+    out: &mut impl Write,
+) -> io::Result<()> {
+    let (left, right) = (MARGIN, CHART_WIDTH - 10.0);
+    let (top, bottom) = (10.0, CHART_HEIGHT - 25.0);
+    let turns = metrics.turns();
+    let last = turns.last().map_or(0, |t| t.turn()).max(1) as f64;
+    let max = turns
+        .iter()
+        .flat_map(|t| t.groups().iter().map(value))
+        .fold(0.0, f64::max);
+    let max = if max > 0.0 { max } else { 1.0 };
+    let x = |turn: usize| left + turn as f64 / last * (right - left);
+    let y = |v: f64| bottom - v / max * (bottom - top);
+
+    writeln!(out, "<h3>{}</h3>", title)?;
+    writeln!(
+        out,
+        "<svg width=\"{:.0}\" height=\"{:.0}\" font-size=\"11\">",
+        CHART_WIDTH, CHART_HEIGHT
+    )?;
+    writeln!(
+        out,
+        "<polyline points=\"{left:.0},{top:.0} {left:.0},{bottom:.0} {right:.0},{bottom:.0}\" fill=\"none\" stroke=\"#999\"/>"
+    )?;
+    writeln!(
+        out,
+        "<text x=\"{:.0}\" y=\"{:.0}\" text-anchor=\"end\">{:.2}</text><text x=\"{:.0}\" y=\"{:.0}\" text-anchor=\"end\">0</text>",
+        left - 4.0,
+        top + 8.0,
+        max,
+        left - 4.0,
+        bottom
+    )?;
+    writeln!(
+        out,
+        "<text x=\"{:.0}\" y=\"{:.0}\">0</text><text x=\"{:.0}\" y=\"{:.0}\" text-anchor=\"end\">turn {:.0}</text>",
+        left,
+        bottom + 15.0,
+        right,
+        bottom + 15.0,
+        last
+    )?;
+    for turn in actions {
+        writeln!(
+            out,
+            "<line x1=\"{0:.1}\" y1=\"{top:.0}\" x2=\"{0:.1}\" y2=\"{bottom:.0}\" stroke=\"#555\" stroke-dasharray=\"3 3\"/>",
+            x(*turn)
+        )?;
+    }
+    for (g_id, name) in metrics.groups().iter().enumerate() {
+        let points = turns
+            .iter()
+            .map(|t| format!("{:.1},{:.1}", x(t.turn()), y(value(&t.groups()[g_id]))))
+            .collect::<Vec<_>>()
+            .join(" ");
+        writeln!(
+            out,
+            "<polyline points=\"{}\" fill=\"none\" stroke=\"{}\" stroke-width=\"1.5\"><title>{}</title></polyline>",
+            points,
+            PALETTE[g_id % PALETTE.len()],
+            escape(name)
+        )?;
+    }
+    writeln!(out, "</svg>")
+}
+
+/// Writes a single HTML page for a run: the topology at the last turn, per-group charts,
+/// failures, operator actions and the final group summary. Everything is inline, so the file
+/// opens without network access. `snapshots` must not be empty.
+pub fn write_report(
+    graph: &Graph,
+    groups: &GroupSet,
+    snapshots: &[&Snapshot],
+    out: &mut impl Write,
+) -> io::Result<()> {
+    let current = snapshots[snapshots.len() - 1];
+    let previous = snapshots[snapshots.len().saturating_sub(2)];
+    let metrics = RunMetrics::from_snapshots(graph, groups, snapshots);
+    let cascade = reconstruct_cascade(graph, groups, snapshots);
+    let actions = operator_actions(snapshots);
+    let group_name = |g_id: usize| escape(groups.groups()[g_id].name());
+
+    writeln!(out, "<!DOCTYPE html>")?;
+    writeln!(out, "<html lang=\"en\">")?;
+    writeln!(
+        out,
+        "<head><meta charset=\"utf-8\"><title>Faultgraph run</title>"
+    )?;
+    writeln!(out, "<style>{}</style></head>", STYLE)?;
+    writeln!(out, "<body>")?;
+    writeln!(out, "<h1>Faultgraph run</h1>")?;
+    writeln!(
+        out,
+        "<p>{} turns, {} nodes in {} groups, {} failures, {} operator actions</p>",
+        current.turn(),
+        graph.node_count(),
+        groups.groups().len(),
+        cascade.events().len(),
+        actions.len()
+    )?;
+
+    writeln!(out, "<h2>Topology at turn {}</h2>", current.turn())?;
+    write_topology(graph, groups, current, out)?;
+
+    writeln!(out, "<h2>Groups over time</h2>")?;
+    write!(out, "<p class=\"legend\">")?;
+    for (g_id, group) in groups.groups().iter().enumerate() {
+        write!(
+            out,
+            "<span style=\"color:{}\">■ {}</span>",
+            PALETTE[g_id % PALETTE.len()],
+            escape(group.name())
+        )?;
+    }
+    writeln!(out, "</p>")?;
+    let action_turns = actions.iter().map(|(turn, _, _)| *turn).collect::<Vec<_>>();
+    write_chart(&metrics, "Health", GroupMetrics::health, &action_turns, out)?;
+    write_chart(
+        &metrics,
+        "Utilisation",
+        GroupMetrics::utilization,
+        &action_turns,
+        out,
+    )?;
+    write_chart(
+        &metrics,
+        "Backlog",
+        GroupMetrics::backlog,
+        &action_turns,
+        out,
+    )?;
+
+    writeln!(out, "<h2>Failures</h2>")?;
+    if cascade.events().is_empty() {
+        writeln!(out, "<p>No node failed.</p>")?;
+    } else {
+        writeln!(
+            out,
+            "<table><tr><th>Turn</th><th>Group</th><th>Cause</th></tr>"
+        )?;
+        for event in cascade.events() {
+            writeln!(
+                out,
+                "<tr><td>{}</td><td>{}</td><td>{}</td></tr>",
+                event.failed_at(),
+                group_name(groups.group_by_node_id(event.node_id().index())),
+                escape(&event.describe(graph, groups))
+            )?;
+        }
+        writeln!(out, "</table>")?;
+    }
+
+    writeln!(out, "<h2>Operator actions</h2>")?;
+    if actions.is_empty() {
+        writeln!(out, "<p>None.</p>")?;
+    } else {
+        writeln!(
+            out,
+            "<table><tr><th>Turn</th><th>Action</th><th>Group</th><th>Factor</th></tr>"
+        )?;
+        for (turn, g_id, factor) in &actions {
+            let action = if *factor < 1.0 { "Throttle" } else { "Boost" };
+            writeln!(
+                out,
+                "<tr><td>{}</td><td>{}</td><td>{}</td><td>{:.2}</td></tr>",
+                turn,
+                action,
+                group_name(*g_id),
+                factor
+            )?;
+        }
+        writeln!(out, "</table>")?;
+    }
+
+    writeln!(out, "<h2>Groups at turn {}</h2>", current.turn())?;
+    writeln!(
+        out,
+        "<table><tr><th>Group</th><th>Status</th><th>Health</th><th>Utilisation</th><th>Healthy nodes</th><th>Backlog</th></tr>"
+    )?;
+    let last = &metrics.turns()[metrics.turns().len() - 1];
+    for (g_id, summary) in aggregate_groups(groups, current, previous, graph)
+        .iter()
+        .enumerate()
+    {
+        writeln!(
+            out,
+            "<tr><td>{}</td><td style=\"background:{}\">{}</td><td>{:.2} {}</td><td>{:.0}% {}</td><td>{}/{}</td><td>{:.1}</td></tr>",
+            escape(summary.name()),
+            health_color(summary.raw_health()),
+            summary.health(),
+            summary.raw_health(),
+            trend(summary.health_trend()),
+            summary.avg_utilization() * 100.0,
+            trend(summary.utilization_trend()),
+            summary.healthy_nodes(),
+            summary.node_count(),
+            last.groups()[g_id].backlog()
+        )?;
+    }
+    writeln!(out, "</table>")?;
+    writeln!(out, "</body>")?;
+    writeln!(out, "</html>")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::groups::Group;
+    use crate::graph::edge::{Edge, EdgeId};
+    use crate::graph::node::{Node, NodeId};
+    use crate::simulation::modifiers::CapacityModifier;
+    use crate::state::edge_state::EdgeState;
+    use crate::state::node_state::NodeState;
+
+    // api → db
+    fn setup() -> (Graph, GroupSet, Vec<Snapshot>) {
+        let api = Node::new(NodeId(0), "api".to_string(), 100.0, 1.0);
+        let db = Node::new(NodeId(1), "db".to_string(), 50.0, 0.0);
+        let link = Edge::new(EdgeId(0), NodeId(0), NodeId(1), 1.0);
+        let graph = Graph::new(vec![api, db], vec![link]);
+        let groups = GroupSet::new(vec![
+            Group::new("Api & Web".to_string(), vec![NodeId(0)]),
+            Group::new("Db".to_string(), vec![NodeId(1)]),
+        ]);
+
+        let snapshot = |turn: usize, db: NodeState| {
+            Snapshot::new(
+                turn,
+                vec![NodeState::new(80.0, 80.0, 0.0, 1.0), db],
+                vec![EdgeState::new(true)],
+                vec![CapacityModifier::new(); 2],
+            )
+        };
+        let mut snapshots = vec![
+            snapshot(0, NodeState::new(80.0, 50.0, 30.0, 0.6)),
+            snapshot(1, NodeState::new(80.0, 50.0, 60.0, 0.2)),
+            snapshot(2, NodeState::new(80.0, 0.0, 0.0, 0.0)),
+        ];
+        snapshots[1].update_capacity(1, 1.5);
+        (graph, groups, snapshots)
+    }
+
+    fn report() -> String {
+        let (graph, groups, snapshots) = setup();
+        let mut out = vec![];
+        write_report(
+            &graph,
+            &groups,
+            &snapshots.iter().collect::<Vec<_>>(),
+            &mut out,
+        )
+        .unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_report_is_self_contained() {
+        let report = report();
+
+        assert!(report.starts_with("<!DOCTYPE html>"));
+        assert!(report.trim_end().ends_with("</html>"));
+        assert!(!report.contains("src="));
+        assert!(!report.contains("<link"));
+        assert!(!report.contains("Api & Web"));
+        assert!(report.contains("Api &amp; Web"));
+        // the topology and three charts
+        assert_eq!(4, report.matches("<svg").count());
+        assert_eq!(6, report.matches("stroke-width=\"1.5\"").count());
+    }
+
+    #[test]
+    fn test_timeline_and_summary() {
+        let report = report();
+
+        assert!(
+            report.contains("<p>2 turns, 2 nodes in 2 groups, 1 failures, 1 operator actions</p>")
+        );
+        assert!(report.contains("<tr><td>2</td><td>Db</td><td>db failed at turn 2"));
+        assert!(report.contains("<tr><td>1</td><td>Boost</td><td>Db</td><td>1.50</td></tr>"));
+        assert!(report.contains(
+            "<tr><td>Db</td><td style=\"background:#9399b2\">Failed</td><td>0.00 ↘</td>"
+        ));
+        // one dashed marker for the boost in each chart
+        assert_eq!(3, report.matches("stroke-dasharray=\"3 3\"").count());
+    }
+}
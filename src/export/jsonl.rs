@@ -0,0 +1,384 @@
+use crate::analysis::analysis::aggregate_groups;
+use crate::analysis::groups::{GroupHealth, GroupSet, GroupTrend};
+use crate::analysis::slo::{Slo, SloTrack, SloVerdict};
+use crate::graph::graph::Graph;
+use crate::graph::node::NodeId;
+use crate::state::snapshot::Snapshot;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::io;
+use std::io::Write;
+
+/// Bumped on any change to the records below that could break a consumer. Adding a field is
+/// not such a change, renaming or removing one is. The schema is documented in the README.
+pub const SCHEMA_VERSION: u32 = 1;
+
+#[derive(Serialize)]
+struct TurnRecord<'a> {
+    schema: u32,
+    turn: usize,
+    nodes: Vec<NodeRecord<'a>>,
+    edges: Vec<EdgeRecord>,
+    capacity_modifiers: Vec<ModifierRecord<'a>>,
+    groups: Vec<GroupRecord>,
+    slos: Vec<SloRecord<'a>>,
+    events: Vec<EventRecord<'a>>,
+}
+
+#[derive(Serialize)]
+struct NodeRecord<'a> {
+    id: usize,
+    name: &'a str,
+    group: &'a str,
+    demand: f64,
+    served: f64,
+    backlog: f64,
+    health: f64,
+    outage: usize,
+}
+
+#[derive(Serialize)]
+struct EdgeRecord {
+    id: usize,
+    from: usize,
+    to: usize,
+    enabled: bool,
+}
+
+#[derive(Serialize)]
+struct ModifierRecord<'a> {
+    group: &'a str,
+    active: bool,
+    factor: f64,
+    remaining_turns: u8,
+}
+
+#[derive(Serialize)]
+struct GroupRecord {
+    name: String,
+    utilization: f64,
+    utilization_trend: &'static str,
+    health: f64,
+    status: &'static str,
+    health_trend: &'static str,
+    nodes: usize,
+    healthy_nodes: usize,
+    /// load received per source group
+    pressure: BTreeMap<String, f64>,
+}
+
+#[derive(Serialize)]
+struct SloRecord<'a> {
+    name: String,
+    group: &'a str,
+    attainment: f64,
+    burn_rate: f64,
+    budget_remaining: f64,
+    verdict: &'static str,
+}
+
+#[derive(Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+enum EventRecord<'a> {
+    NodeFailed { node: &'a str },
+    OutageStarted { node: &'a str, turns: usize },
+    ModifierApplied { group: &'a str, factor: f64 },
+}
+
+fn trend(trend: &GroupTrend) -> &'static str {
+    match trend {
+        GroupTrend::Up => "up",
+        GroupTrend::Down => "down",
+        GroupTrend::Flat => "flat",
+    }
+}
+
+fn status(health: &GroupHealth) -> &'static str {
+    match health {
+        GroupHealth::Ok => "ok",
+        GroupHealth::Degraded => "degraded",
+        GroupHealth::Critical => "critical",
+        GroupHealth::Failed => "failed",
+    }
+}
+
+fn verdict(verdict: &SloVerdict) -> &'static str {
+    match verdict {
+        SloVerdict::Met => "met",
+        SloVerdict::AtRisk => "at_risk",
+        SloVerdict::Breached => "breached",
+    }
+}
+
+fn turn_record<'a>(
+    graph: &'a Graph,
+    groups: &'a GroupSet,
+    slos: &[Slo],
+    tracks: &[SloTrack],
+    previous: &Snapshot,
+    current: &Snapshot,
+) -> TurnRecord<'a> {
+    let group_name = |g_id: usize| groups.groups()[g_id].name();
+    let node_name = |i: usize| graph.node_by_id(NodeId(i)).name();
+
+    let nodes = current
+        .node_states()
+        .iter()
+        .enumerate()
+        .map(|(i, s)| NodeRecord {
+            id: i,
+            name: node_name(i),
+            group: group_name(groups.group_by_node_id(i)),
+            demand: s.demand(),
+            served: s.served(),
+            backlog: s.backlog(),
+            health: s.health(),
+            outage: s.outage(),
+        })
+        .collect();
+    let edges = graph
+        .edges()
+        .iter()
+        .map(|e| EdgeRecord {
+            id: e.id().index(),
+            from: e.from().index(),
+            to: e.to().index(),
+            enabled: current.edge_states()[e.id().index()].is_enabled(),
+        })
+        .collect();
+    let capacity_modifiers = current
+        .capacity_mods()
+        .iter()
+        .enumerate()
+        .map(|(g_id, m)| ModifierRecord {
+            group: group_name(g_id),
+            active: m.is_active(),
+            factor: m.factor(),
+            remaining_turns: m.remaining_turns(),
+        })
+        .collect();
+    let summaries = aggregate_groups(groups, current, previous, graph)
+        .into_iter()
+        .map(|s| GroupRecord {
+            name: s.name().to_string(),
+            utilization: s.avg_utilization(),
+            utilization_trend: trend(s.utilization_trend()),
+            health: s.raw_health(),
+            status: status(s.health()),
+            health_trend: trend(s.health_trend()),
+            nodes: s.node_count(),
+            healthy_nodes: s.healthy_nodes(),
+            pressure: s
+                .pressure()
+                .iter()
+                .enumerate()
+                .filter(|(_, load)| **load > 0.0)
+                .map(|(source, load)| (group_name(source).to_string(), *load))
+                .collect(),
+        })
+        .collect();
+    let slos = slos
+        .iter()
+        .zip(tracks)
+        .filter_map(|(slo, track)| {
+            let t = track.at_turn(current.turn())?;
+            Some(SloRecord {
+                name: slo.describe(graph, groups),
+                group: group_name(slo.group_id(groups)),
+                attainment: t.attainment(),
+                burn_rate: t.burn_rate(),
+                budget_remaining: t.budget_remaining(),
+                verdict: verdict(&t.verdict()),
+            })
+        })
+        .collect();
+
+    let before = previous.node_states();
+    let mut events = vec![];
+    for (i, s) in current.node_states().iter().enumerate() {
+        if before[i].is_healthy() && !s.is_healthy() {
+            events.push(EventRecord::NodeFailed { node: node_name(i) });
+        }
+        if before[i].outage() == 0 && s.outage() > 0 {
+            events.push(EventRecord::OutageStarted {
+                node: node_name(i),
+                turns: s.outage(),
+            });
+        }
+    }
+    for (g_id, m) in current.capacity_mods().iter().enumerate() {
+        if m.is_active() && !previous.capacity_mods()[g_id].is_active() {
+            events.push(EventRecord::ModifierApplied {
+                group: group_name(g_id),
+                factor: m.factor(),
+            });
+        }
+    }
+
+    TurnRecord {
+        schema: SCHEMA_VERSION,
+        turn: current.turn(),
+        nodes,
+        edges,
+        capacity_modifiers,
+        groups: summaries,
+        slos,
+        events,
+    }
+}
+
+/// Writes `current` as one JSON object on its own line. `previous` is the snapshot before it,
+/// used for trends and events; pass `current` again for the first turn. `tracks` are indexed
+/// like `slos`, an SLO whose track lacks the turn is left out.
+pub fn write_turn(
+    graph: &Graph,
+    groups: &GroupSet,
+    slos: &[Slo],
+    tracks: &[SloTrack],
+    previous: &Snapshot,
+    current: &Snapshot,
+    out: &mut impl Write,
+) -> io::Result<()> {
+    let record = turn_record(graph, groups, slos, tracks, previous, current);
+    serde_json::to_writer(&mut *out, &record)?;
+    writeln!(out)
+}
+
+/// The record `write_turn` writes, as a JSON value.
+pub fn turn_value(
+    graph: &Graph,
+    groups: &GroupSet,
+    slos: &[Slo],
+    tracks: &[SloTrack],
+    previous: &Snapshot,
+    current: &Snapshot,
+) -> serde_json::Value {
+    serde_json::to_value(turn_record(graph, groups, slos, tracks, previous, current))
+        .expect("turn records serialize")
+}
+
+/// Writes every snapshot of a run, oldest first, one line each.
+pub fn write_run(
+    graph: &Graph,
+    groups: &GroupSet,
+    slos: &[Slo],
+    tracks: &[SloTrack],
+    snapshots: &[&Snapshot],
+    out: &mut impl Write,
+) -> io::Result<()> {
+    for (i, current) in snapshots.iter().enumerate() {
+        let previous = snapshots[i.saturating_sub(1)];
+        write_turn(graph, groups, slos, tracks, previous, current, out)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::groups::Group;
+    use crate::analysis::slo::{Indicator, SloScope, track_slo};
+    use crate::graph::edge::{Edge, EdgeId};
+    use crate::graph::node::Node;
+    use crate::simulation::modifiers::CapacityModifier;
+    use crate::state::edge_state::EdgeState;
+    use crate::state::node_state::NodeState;
+    use approx::assert_relative_eq;
+    use serde_json::Value;
+
+    fn setup() -> (Graph, GroupSet, Vec<Snapshot>) {
+        let api = Node::new(NodeId(0), "api".to_string(), 100.0, 1.0);
+        let db = Node::new(NodeId(1), "db".to_string(), 50.0, 0.0);
+        let link = Edge::new(EdgeId(0), NodeId(0), NodeId(1), 1.0);
+        let graph = Graph::new(vec![api, db], vec![link]);
+        let groups = GroupSet::new(vec![
+            Group::new("Api".to_string(), vec![NodeId(0)]),
+            Group::new("Db".to_string(), vec![NodeId(1)]),
+        ]);
+
+        let first = Snapshot::new(
+            0,
+            vec![
+                NodeState::new(40.0, 40.0, 0.0, 1.0),
+                NodeState::new(40.0, 40.0, 0.0, 0.1),
+            ],
+            vec![EdgeState::new(true)],
+            vec![CapacityModifier::new(); 2],
+        );
+        let mut second = Snapshot::new(
+            1,
+            vec![
+                NodeState::new(40.0, 40.0, 0.0, 1.0),
+                NodeState::new(40.0, 0.0, 0.0, 0.0),
+            ],
+            vec![EdgeState::new(true)],
+            vec![CapacityModifier::new(); 2],
+        );
+        second.update_capacity(0, 0.5);
+        (graph, groups, vec![first, second])
+    }
+
+    /// with a 90% availability SLO on the database over two turns
+    fn records(graph: &Graph, groups: &GroupSet, snapshots: &[Snapshot]) -> Vec<Value> {
+        let snapshots = snapshots.iter().collect::<Vec<_>>();
+        let slos = [Slo::new(
+            SloScope::Group(1),
+            Indicator::Availability,
+            0.9,
+            2,
+        )];
+        let tracks = [track_slo(&slos[0], groups, &snapshots)];
+        let mut out = vec![];
+        write_run(graph, groups, &slos, &tracks, &snapshots, &mut out).unwrap();
+        String::from_utf8(out)
+            .unwrap()
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_one_versioned_record_per_turn() {
+        let (graph, groups, snapshots) = setup();
+        let records = records(&graph, &groups, &snapshots);
+
+        assert_eq!(2, records.len());
+        let first = &records[0];
+        assert_eq!(SCHEMA_VERSION as u64, first["schema"]);
+        assert_eq!(0, first["turn"]);
+        assert_eq!("db", first["nodes"][1]["name"]);
+        assert_eq!("Db", first["nodes"][1]["group"]);
+        assert_eq!(40.0, first["nodes"][1]["served"]);
+        assert_eq!(true, first["edges"][0]["enabled"]);
+        assert_eq!(40.0, first["groups"][1]["pressure"]["Api"]);
+        assert_eq!("critical", first["groups"][1]["status"]);
+        assert_eq!("Db", first["slos"][0]["group"]);
+        assert_eq!(1.0, first["slos"][0]["attainment"]);
+        assert_eq!("met", first["slos"][0]["verdict"]);
+        assert_eq!(0, first["events"].as_array().unwrap().len());
+    }
+
+    #[test]
+    fn test_events_from_previous_turn() {
+        let (graph, groups, snapshots) = setup();
+        let second = &records(&graph, &groups, &snapshots)[1];
+
+        assert_eq!("failed", second["groups"][1]["status"]);
+        assert_eq!(0.5, second["capacity_modifiers"][0]["factor"]);
+        // 40 of 80 served over the window, 8 bad events allowed in the run
+        assert_eq!(0.5, second["slos"][0]["attainment"]);
+        assert_relative_eq!(
+            -4.0,
+            second["slos"][0]["budget_remaining"].as_f64().unwrap(),
+            epsilon = 1e-9
+        );
+        assert_eq!("breached", second["slos"][0]["verdict"]);
+        assert_eq!(
+            serde_json::json!([
+                {"kind": "node_failed", "node": "db"},
+                {"kind": "modifier_applied", "group": "Api", "factor": 0.5},
+            ]),
+            second["events"]
+        );
+    }
+}
@@ -0,0 +1,5 @@
+pub mod diagram;
+pub mod html;
+pub mod jsonl;
+pub mod openmetrics;
+pub mod serve;
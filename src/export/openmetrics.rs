@@ -0,0 +1,375 @@
+use crate::analysis::analysis::{calc_health, calc_util};
+use crate::analysis::flow::flow_matrix;
+use crate::analysis::groups::GroupSet;
+use crate::analysis::slo::{Slo, SloTrack, SloTurn};
+use crate::graph::graph::Graph;
+use crate::graph::node::NodeId;
+use crate::state::snapshot::Snapshot;
+use std::io;
+use std::io::Write;
+
+pub const CONTENT_TYPE: &str = "application/openmetrics-text; version=1.0.0; charset=utf-8";
+
+struct Sample {
+    labels: Vec<(&'static str, String)>,
+    value: f64,
+}
+
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// Writes one gauge family with the samples of every snapshot, one series after the other.
+/// With `start` each sample is stamped `start + turn` seconds, without it the samples carry no
+/// timestamp.
+fn write_family(
+    out: &mut impl Write,
+    name: &str,
+    help: &str,
+    snapshots: &[&Snapshot],
+    start: Option<u64>,
+    samples: impl Fn(&Snapshot) -> Vec<Sample>,
+) -> io::Result<()> {
+    writeln!(out, "# TYPE faultgraph_{} gauge", name)?;
+    writeln!(out, "# HELP faultgraph_{} {}", name, help)?;
+
+    let turns = snapshots.iter().map(|s| samples(s)).collect::<Vec<_>>();
+    for series in 0..turns.first().map_or(0, |t| t.len()) {
+        for (snapshot, samples) in snapshots.iter().zip(&turns) {
+            let sample = &samples[series];
+            write!(out, "faultgraph_{}", name)?;
+            if !sample.labels.is_empty() {
+                let labels = sample
+                    .labels
+                    .iter()
+                    .map(|(k, v)| format!("{}=\"{}\"", k, escape(v)))
+                    .collect::<Vec<String>>()
+                    .join(",");
+                write!(out, "{{{}}}", labels)?;
+            }
+            write!(out, " {}", sample.value)?;
+            match start {
+                Some(start) => writeln!(out, " {}", start + snapshot.turn() as u64)?,
+                None => writeln!(out)?,
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Writes node, group and SLO metrics of `snapshots` in OpenMetrics text format, see
+/// `write_family` for `start`. `tracks` are indexed like `slos` and cover the snapshots.
+pub fn write_openmetrics(
+    graph: &Graph,
+    groups: &GroupSet,
+    slos: &[Slo],
+    tracks: &[SloTrack],
+    snapshots: &[&Snapshot],
+    start: Option<u64>,
+    out: &mut impl Write,
+) -> io::Result<()> {
+    let node_labels = |i: usize| {
+        vec![
+            ("node", graph.node_by_id(NodeId(i)).name().to_string()),
+            (
+                "group",
+                groups.groups()[groups.group_by_node_id(i)]
+                    .name()
+                    .to_string(),
+            ),
+        ]
+    };
+    let node_family =
+        |out: &mut _, name, help, value: fn(&Snapshot, &Graph, &GroupSet, usize) -> f64| {
+            write_family(out, name, help, snapshots, start, |s| {
+                (0..graph.node_count())
+                    .map(|i| Sample {
+                        labels: node_labels(i),
+                        value: value(s, graph, groups, i),
+                    })
+                    .collect()
+            })
+        };
+    let group_family =
+        |out: &mut _, name, help, value: fn(&Snapshot, &Graph, &GroupSet, usize) -> f64| {
+            write_family(out, name, help, snapshots, start, |s| {
+                groups
+                    .groups()
+                    .iter()
+                    .enumerate()
+                    .map(|(g_id, g)| Sample {
+                        labels: vec![("group", g.name().to_string())],
+                        value: value(s, graph, groups, g_id),
+                    })
+                    .collect()
+            })
+        };
+    let slo_family = |out: &mut _, name, help, value: fn(&SloTurn) -> f64| {
+        write_family(out, name, help, snapshots, start, |s| {
+            slos.iter()
+                .zip(tracks)
+                .map(|(slo, track)| Sample {
+                    labels: vec![
+                        ("slo", slo.describe(graph, groups)),
+                        (
+                            "group",
+                            groups.groups()[slo.group_id(groups)].name().to_string(),
+                        ),
+                    ],
+                    value: track.at_turn(s.turn()).map_or(f64::NAN, value),
+                })
+                .collect()
+        })
+    };
+
+    write_family(out, "turn", "Simulation turn.", snapshots, start, |s| {
+        vec![Sample {
+            labels: vec![],
+            value: s.turn() as f64,
+        }]
+    })?;
+
+    node_family(
+        out,
+        "node_demand",
+        "Load arriving at the node in rps.",
+        |s, _, _, i| s.node_states()[i].demand(),
+    )?;
+    node_family(
+        out,
+        "node_served",
+        "Load served by the node in rps.",
+        |s, _, _, i| s.node_states()[i].served(),
+    )?;
+    node_family(
+        out,
+        "node_backlog",
+        "Load queued at the node in rps.",
+        |s, _, _, i| s.node_states()[i].backlog(),
+    )?;
+    node_family(
+        out,
+        "node_health",
+        "Node health from 0 to 1.",
+        |s, _, _, i| s.node_states()[i].health(),
+    )?;
+    node_family(
+        out,
+        "node_utilization",
+        "Served load over the node's current capacity.",
+        |s, graph, groups, i| {
+            let factor = s.capacity_mod(groups.group_by_node_id(i)).factor();
+            let capacity = graph.node_by_id(NodeId(i)).capacity() * factor;
+            if capacity > 0.0 {
+                s.node_states()[i].served() / capacity
+            } else {
+                0.0
+            }
+        },
+    )?;
+
+    group_family(
+        out,
+        "group_health",
+        "Average node health of the group.",
+        |s, _, groups, g_id| calc_health(s, &groups.groups()[g_id]),
+    )?;
+    group_family(
+        out,
+        "group_utilization",
+        "Served load over the capacity of the group's healthy nodes.",
+        |s, graph, groups, g_id| calc_util(s, &groups.groups()[g_id], graph, g_id),
+    )?;
+    group_family(
+        out,
+        "group_backlog",
+        "Load queued in the group in rps.",
+        |s, _, groups, g_id| {
+            groups.groups()[g_id]
+                .nodes()
+                .iter()
+                .map(|id| s.node_states()[id.index()].backlog())
+                .sum()
+        },
+    )?;
+    group_family(
+        out,
+        "group_capacity_factor",
+        "Capacity modifier applied to the group, 1 without one.",
+        |s, _, _, g_id| s.capacity_mod(g_id).factor(),
+    )?;
+
+    write_family(
+        out,
+        "group_pressure",
+        "Load the group receives from the source group in rps.",
+        snapshots,
+        start,
+        |s| {
+            let names = groups
+                .groups()
+                .iter()
+                .map(|g| g.name())
+                .collect::<Vec<&str>>();
+            flow_matrix(graph, groups, s)
+                .iter()
+                .enumerate()
+                .flat_map(|(from, row)| {
+                    let names = &names;
+                    row.iter().enumerate().map(move |(to, load)| Sample {
+                        labels: vec![
+                            ("group", names[to].to_string()),
+                            ("source", names[from].to_string()),
+                        ],
+                        value: *load,
+                    })
+                })
+                .collect()
+        },
+    )?;
+
+    slo_family(
+        out,
+        "slo_attainment",
+        "Share of good events over the SLO's rolling window.",
+        SloTurn::attainment,
+    )?;
+    slo_family(
+        out,
+        "slo_burn_rate",
+        "Rate at which the window consumes error budget, 1 uses it up exactly.",
+        SloTurn::burn_rate,
+    )?;
+    slo_family(
+        out,
+        "slo_budget_remaining",
+        "Share of the run's error budget left, negative once exceeded.",
+        SloTurn::budget_remaining,
+    )?;
+
+    writeln!(out, "# EOF")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::groups::Group;
+    use crate::analysis::slo::{Indicator, SloScope, track_slo};
+    use crate::graph::edge::{Edge, EdgeId};
+    use crate::graph::node::Node;
+    use crate::simulation::modifiers::CapacityModifier;
+    use crate::state::edge_state::EdgeState;
+    use crate::state::node_state::NodeState;
+
+    fn setup() -> (Graph, GroupSet, Vec<Snapshot>) {
+        let api = Node::new(NodeId(0), "api \"v2\"".to_string(), 100.0, 1.0);
+        let db = Node::new(NodeId(1), "db".to_string(), 50.0, 0.0);
+        let link = Edge::new(EdgeId(0), NodeId(0), NodeId(1), 1.0);
+        let graph = Graph::new(vec![api, db], vec![link]);
+        let groups = GroupSet::new(vec![
+            Group::new("Api".to_string(), vec![NodeId(0)]),
+            Group::new("Db".to_string(), vec![NodeId(1)]),
+        ]);
+        let snapshots = (0..2)
+            .map(|turn| {
+                let load = 40.0 * turn as f64;
+                Snapshot::new(
+                    turn,
+                    vec![
+                        NodeState::new(load, load, 0.0, 1.0),
+                        NodeState::new(load, load, 0.0, 1.0),
+                    ],
+                    vec![EdgeState::new(true)],
+                    vec![CapacityModifier::new(); 2],
+                )
+            })
+            .collect();
+        (graph, groups, snapshots)
+    }
+
+    fn lines(exposition: &[u8], prefix: &str) -> Vec<String> {
+        String::from_utf8(exposition.to_vec())
+            .unwrap()
+            .lines()
+            .filter(|l| l.starts_with(prefix))
+            .map(str::to_string)
+            .collect()
+    }
+
+    #[test]
+    fn test_series_are_contiguous_and_stamped() {
+        let (graph, groups, snapshots) = setup();
+        let mut out = vec![];
+        write_openmetrics(
+            &graph,
+            &groups,
+            &[],
+            &[],
+            &snapshots.iter().collect::<Vec<_>>(),
+            Some(1000),
+            &mut out,
+        )
+        .unwrap();
+
+        assert_eq!(
+            vec![
+                "faultgraph_node_served{node=\"api \\\"v2\\\"\",group=\"Api\"} 0 1000",
+                "faultgraph_node_served{node=\"api \\\"v2\\\"\",group=\"Api\"} 40 1001",
+                "faultgraph_node_served{node=\"db\",group=\"Db\"} 0 1000",
+                "faultgraph_node_served{node=\"db\",group=\"Db\"} 40 1001",
+            ],
+            lines(&out, "faultgraph_node_served")
+        );
+        assert_eq!(
+            Some("# EOF"),
+            String::from_utf8(out).unwrap().lines().last()
+        );
+    }
+
+    #[test]
+    fn test_latest_turn_without_timestamps() {
+        let (graph, groups, snapshots) = setup();
+        let mut out = vec![];
+        let slos = [Slo::new(
+            SloScope::Group(1),
+            Indicator::Availability,
+            0.5,
+            20,
+        )];
+        let tracks = [track_slo(
+            &slos[0],
+            &groups,
+            &snapshots.iter().collect::<Vec<_>>(),
+        )];
+        write_openmetrics(
+            &graph,
+            &groups,
+            &slos,
+            &tracks,
+            &[&snapshots[1]],
+            None,
+            &mut out,
+        )
+        .unwrap();
+
+        assert_eq!(vec!["faultgraph_turn 1"], lines(&out, "faultgraph_turn"));
+        assert_eq!(
+            vec![
+                "faultgraph_group_pressure{group=\"Api\",source=\"Api\"} 0",
+                "faultgraph_group_pressure{group=\"Db\",source=\"Api\"} 40",
+                "faultgraph_group_pressure{group=\"Api\",source=\"Db\"} 0",
+                "faultgraph_group_pressure{group=\"Db\",source=\"Db\"} 0",
+            ],
+            lines(&out, "faultgraph_group_pressure")
+        );
+        assert_eq!(
+            vec![
+                "faultgraph_slo_budget_remaining{slo=\"Db availability ≥ 50% / 20\",group=\"Db\"} 1"
+            ],
+            lines(&out, "faultgraph_slo_budget_remaining")
+        );
+    }
+}
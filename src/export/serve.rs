@@ -0,0 +1,44 @@
+use crate::export::openmetrics::CONTENT_TYPE;
+use crate::remote::http::READ_TIMEOUT;
+use std::io;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+fn respond(stream: TcpStream, latest: &Mutex<String>) -> io::Result<()> {
+    let mut request_line = String::new();
+    BufReader::new(&stream).read_line(&mut request_line)?;
+    let mut stream = stream;
+
+    let path = request_line.split_whitespace().nth(1).unwrap_or("");
+    if path != "/metrics" {
+        return write!(
+            stream,
+            "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+        );
+    }
+    let body = latest.lock().unwrap().clone();
+    write!(
+        stream,
+        "HTTP/1.1 200 OK\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        CONTENT_TYPE,
+        body.len(),
+        body
+    )
+}
+
+/// Serves the exposition in `latest` at `/metrics` on `addr` from a background thread until the
+/// process exits.
+pub fn serve_metrics(addr: impl ToSocketAddrs, latest: Arc<Mutex<String>>) -> io::Result<()> {
+    let listener = TcpListener::bind(addr)?;
+    thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            // a client that never sends its request line would hold up every scrape after it
+            let _ = stream.set_read_timeout(Some(READ_TIMEOUT));
+            // a client that hangs up early is not our problem
+            let _ = respond(stream, &latest);
+        }
+    });
+    Ok(())
+}
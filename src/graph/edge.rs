@@ -1,6 +1,6 @@
 use crate::graph::node::NodeId;
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub struct EdgeId(pub usize);
 
 impl EdgeId {
@@ -9,12 +9,17 @@ impl EdgeId {
     }
 }
 
+/// network latency of an edge that doesn't set one
+const DEFAULT_LATENCY: f64 = 1.0;
+
 pub struct Edge {
     id: EdgeId,
     from: NodeId,
     to: NodeId,
     /// weight >= 0.0
     weight: f64,
+    /// network latency in ms, >= 0.0
+    latency: f64,
 }
 
 impl Edge {
@@ -24,9 +29,15 @@ impl Edge {
             from,
             to,
             weight,
+            latency: DEFAULT_LATENCY,
         }
     }
 
+    pub fn with_latency(mut self, ms: f64) -> Self {
+        self.latency = ms.max(0.0);
+        self
+    }
+
     pub fn id(&self) -> EdgeId {
         self.id
     }
@@ -42,4 +53,12 @@ impl Edge {
     pub fn weight(&self) -> f64 {
         self.weight
     }
+
+    pub fn latency(&self) -> f64 {
+        self.latency
+    }
+
+    pub fn set_weight(&mut self, weight: f64) {
+        self.weight = weight.max(0.0);
+    }
 }
@@ -40,6 +40,14 @@ impl Graph {
         &self.edges[id.index()]
     }
 
+    pub fn node_by_id_mut(&mut self, id: NodeId) -> &mut Node {
+        &mut self.nodes[id.index()]
+    }
+
+    pub fn edge_by_id_mut(&mut self, id: EdgeId) -> &mut Edge {
+        &mut self.edges[id.index()]
+    }
+
     pub fn outgoing(&self, id: NodeId) -> &[EdgeId] {
         &self.outgoing[id.index()]
     }
@@ -1,4 +1,4 @@
-#[derive(Clone, Copy, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub struct NodeId(pub usize);
 
 impl NodeId {
@@ -7,6 +7,11 @@ impl NodeId {
     }
 }
 
+/// service time of a node that doesn't set one
+const DEFAULT_SERVICE_TIME: f64 = 10.0;
+/// cost of a node that doesn't set one
+const DEFAULT_COST: f64 = 0.01;
+
 pub struct Node {
     id: NodeId,
     name: String,
@@ -14,6 +19,10 @@ pub struct Node {
     capacity: f64,
     /// gain >= 0.0
     gain: f64,
+    /// time to handle one request in ms, >= 0.0
+    service_time: f64,
+    /// cost per unit of capacity per turn, >= 0.0
+    cost: f64,
 }
 
 impl Node {
@@ -23,9 +32,21 @@ impl Node {
             name: name.into(),
             capacity,
             gain,
+            service_time: DEFAULT_SERVICE_TIME,
+            cost: DEFAULT_COST,
         }
     }
 
+    pub fn with_service_time(mut self, ms: f64) -> Self {
+        self.service_time = ms.max(0.0);
+        self
+    }
+
+    pub fn with_cost(mut self, cost: f64) -> Self {
+        self.cost = cost.max(0.0);
+        self
+    }
+
     pub fn id(&self) -> &NodeId {
         &self.id
     }
@@ -41,4 +62,20 @@ impl Node {
     pub fn gain(&self) -> f64 {
         self.gain
     }
+
+    pub fn service_time(&self) -> f64 {
+        self.service_time
+    }
+
+    pub fn cost(&self) -> f64 {
+        self.cost
+    }
+
+    pub fn set_capacity(&mut self, capacity: f64) {
+        self.capacity = capacity.max(0.0);
+    }
+
+    pub fn set_gain(&mut self, gain: f64) {
+        self.gain = gain.max(0.0);
+    }
 }
@@ -0,0 +1,144 @@
+use crate::import::services::{
+    CAPACITY_KEY, DEPENDS_ON_KEY, GAIN_KEY, GROUP_KEY, LOAD_KEY, Service,
+};
+use serde_yaml::Value;
+
+const DEFAULT_PROJECT: &str = "default";
+
+/// Labels as a mapping or as a list of `key=value`.
+fn label<'a>(service: &'a Value, key: &str) -> Option<&'a str> {
+    match service.get("labels")? {
+        Value::Mapping(m) => m.get(key).and_then(Value::as_str),
+        Value::Sequence(s) => s
+            .iter()
+            .filter_map(Value::as_str)
+            .filter_map(|l| l.split_once('='))
+            .find(|(k, _)| *k == key)
+            .map(|(_, v)| v),
+        _ => None,
+    }
+}
+
+/// `depends_on` as a list of names or as a mapping with conditions.
+fn depends_on(service: &Value) -> Vec<String> {
+    match service.get("depends_on") {
+        Some(Value::Sequence(s)) => s
+            .iter()
+            .filter_map(Value::as_str)
+            .map(str::to_string)
+            .collect(),
+        Some(Value::Mapping(m)) => m
+            .keys()
+            .filter_map(Value::as_str)
+            .map(str::to_string)
+            .collect(),
+        _ => vec![],
+    }
+}
+
+/// Reads the services of a compose file. They are grouped by their `faultgraph/group` label,
+/// or the project `name`. Capacity is the `faultgraph/capacity` label per replica times
+/// `deploy.replicas` or `scale`. Dependencies come from `depends_on` and the
+/// `faultgraph/depends-on` label, and services publishing ports are exposed.
+pub fn services_from_compose(doc: &Value) -> Result<Vec<Service>, String> {
+    let project = doc
+        .get("name")
+        .and_then(Value::as_str)
+        .unwrap_or(DEFAULT_PROJECT);
+    let Some(definitions) = doc.get("services").and_then(Value::as_mapping) else {
+        return Err("no services".to_string());
+    };
+
+    let mut services = vec![];
+    for (name, service) in definitions {
+        let Some(name) = name.as_str() else {
+            continue;
+        };
+        let number = |key: &str| {
+            label(service, key)
+                .map(|v| {
+                    v.trim()
+                        .parse::<f64>()
+                        .map_err(|_| format!("{}: invalid {} '{}'", name, key, v))
+                })
+                .transpose()
+        };
+        let mut dependencies = depends_on(service);
+        if let Some(listed) = label(service, DEPENDS_ON_KEY) {
+            listed
+                .split(',')
+                .map(str::trim)
+                .filter(|s| !s.is_empty())
+                .for_each(|s| dependencies.push(s.to_string()));
+        }
+        let replicas = service
+            .get("deploy")
+            .and_then(|d| d.get("replicas"))
+            .or(service.get("scale"))
+            .and_then(Value::as_u64)
+            .unwrap_or(1);
+
+        services.push(Service {
+            group: label(service, GROUP_KEY).unwrap_or(project).to_string(),
+            name: name.to_string(),
+            replicas: replicas as u32,
+            capacity: number(CAPACITY_KEY)?,
+            gain: number(GAIN_KEY)?,
+            depends_on: dependencies,
+            exposed: service
+                .get("ports")
+                .and_then(Value::as_sequence)
+                .is_some_and(|p| !p.is_empty()),
+            load: number(LOAD_KEY)?,
+        });
+    }
+    Ok(services)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::import::services::{topology_from_services, yaml_documents};
+
+    #[test]
+    fn test_depends_on_and_labels() {
+        let text = r#"
+name: shop
+services:
+  web:
+    ports: ["8080:80"]
+    depends_on: [api]
+    labels: ["faultgraph/capacity=100", "faultgraph/load=60"]
+  api:
+    deploy: {replicas: 2}
+    depends_on:
+      db: {condition: service_healthy}
+    labels: {faultgraph/capacity: "80"}
+  db:
+    labels: {faultgraph/group: data}
+"#;
+        let docs = yaml_documents(text).unwrap();
+        let topology = topology_from_services(&services_from_compose(&docs[0]).unwrap()).unwrap();
+
+        let nodes = topology
+            .groups
+            .iter()
+            .flat_map(|g| {
+                g.nodes
+                    .iter()
+                    .map(|n| (g.name.as_str(), n.name.as_str(), n.capacity))
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec![
+                ("shop", "web", Some(100.0)),
+                ("shop", "api", Some(160.0)),
+                ("data", "db", None)
+            ],
+            nodes
+        );
+        assert_eq!(2, topology.edges.len());
+        assert_eq!("web", topology.entry[0].node);
+        assert_eq!(60.0, topology.entry[0].load);
+    }
+}
@@ -0,0 +1,389 @@
+use crate::import::services::{CAPACITY_KEY, DEPENDS_ON_KEY, GAIN_KEY, LOAD_KEY, Service};
+use serde_yaml::Value;
+use std::collections::HashMap;
+
+const DEFAULT_NAMESPACE: &str = "default";
+
+fn at<'a>(value: &'a Value, path: &[&str]) -> Option<&'a Value> {
+    path.iter().try_fold(value, |v, key| v.get(key))
+}
+
+fn str_at<'a>(value: &'a Value, path: &[&str]) -> Option<&'a str> {
+    at(value, path).and_then(Value::as_str)
+}
+
+fn namespace(doc: &Value) -> &str {
+    str_at(doc, &["metadata", "namespace"]).unwrap_or(DEFAULT_NAMESPACE)
+}
+
+/// string pairs of a mapping such as labels or a selector
+fn pairs(value: Option<&Value>) -> Vec<(&str, &str)> {
+    value
+        .and_then(Value::as_mapping)
+        .map(|m| {
+            m.iter()
+                .filter_map(|(k, v)| Some((k.as_str()?, v.as_str()?)))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+struct Workload<'a> {
+    doc: &'a Value,
+    name: &'a str,
+    namespace: &'a str,
+}
+
+impl Workload<'_> {
+    /// pod template annotations win over the workload's own
+    fn annotation(&self, key: &str) -> Option<&str> {
+        str_at(
+            self.doc,
+            &["spec", "template", "metadata", "annotations", key],
+        )
+        .or_else(|| str_at(self.doc, &["metadata", "annotations", key]))
+    }
+
+    fn number(&self, key: &str) -> Result<Option<f64>, String> {
+        self.annotation(key)
+            .map(|v| {
+                v.trim()
+                    .parse::<f64>()
+                    .map_err(|_| format!("{}: invalid {} '{}'", self.name, key, v))
+            })
+            .transpose()
+    }
+
+    fn selected_by(&self, selector: &[(&str, &str)]) -> bool {
+        let labels = pairs(at(self.doc, &["spec", "template", "metadata", "labels"]));
+        !selector.is_empty() && selector.iter().all(|pair| labels.contains(pair))
+    }
+
+    /// literal values of the containers' environment variables
+    fn env_values(&self) -> Vec<&str> {
+        at(self.doc, &["spec", "template", "spec", "containers"])
+            .and_then(Value::as_sequence)
+            .into_iter()
+            .flatten()
+            .filter_map(|c| c.get("env").and_then(Value::as_sequence))
+            .flatten()
+            .filter_map(|e| e.get("value").and_then(Value::as_str))
+            .collect()
+    }
+}
+
+/// Reads Deployments and StatefulSets as services, grouped by namespace. Capacity is the
+/// `faultgraph/capacity` annotation per pod times the replicas. A workload depends on the
+/// workloads behind every Service whose name appears as a host in its environment variables,
+/// e.g. `http://orders:8080` or `orders.shop.svc`, and on those listed in `faultgraph/depends-on`,
+/// resolved the same way or as `namespace/name`.
+/// Workloads behind LoadBalancer or NodePort Services or Ingress backends are exposed.
+pub fn services_from_manifests(docs: &[&Value]) -> Result<Vec<Service>, String> {
+    let kind = |doc: &Value| str_at(doc, &["kind"]).unwrap_or_default().to_string();
+    let workloads = docs
+        .iter()
+        .filter(|d| matches!(kind(d).as_str(), "Deployment" | "StatefulSet"))
+        .filter_map(|doc| {
+            Some(Workload {
+                doc,
+                name: str_at(doc, &["metadata", "name"])?,
+                namespace: namespace(doc),
+            })
+        })
+        .collect::<Vec<_>>();
+
+    // names must be unique across namespaces, so clashing ones are qualified by their namespace
+    let mut counts = HashMap::new();
+    workloads
+        .iter()
+        .for_each(|w| *counts.entry(w.name).or_insert(0) += 1);
+    let node_name = |w: &Workload| {
+        if counts[w.name] > 1 {
+            format!("{}/{}", w.namespace, w.name)
+        } else {
+            w.name.to_string()
+        }
+    };
+
+    // (namespace, service name) → indices of the workloads it selects
+    let mut backends = HashMap::new();
+    let mut exposed = vec![false; workloads.len()];
+    for doc in docs.iter().filter(|d| kind(d) == "Service") {
+        let Some(name) = str_at(doc, &["metadata", "name"]) else {
+            continue;
+        };
+        let selector = pairs(at(doc, &["spec", "selector"]));
+        let selected = workloads
+            .iter()
+            .enumerate()
+            .filter(|(_, w)| w.namespace == namespace(doc) && w.selected_by(&selector))
+            .map(|(i, _)| i)
+            .collect::<Vec<_>>();
+        if matches!(
+            str_at(doc, &["spec", "type"]),
+            Some("LoadBalancer" | "NodePort")
+        ) {
+            selected.iter().for_each(|i| exposed[*i] = true);
+        }
+        backends.insert((namespace(doc), name), selected);
+    }
+    for doc in docs.iter().filter(|d| kind(d) == "Ingress") {
+        let rules = at(doc, &["spec", "rules"]).and_then(Value::as_sequence);
+        let paths = rules
+            .into_iter()
+            .flatten()
+            .filter_map(|r| at(r, &["http", "paths"]).and_then(Value::as_sequence))
+            .flatten();
+        let services = paths
+            .filter_map(|p| str_at(p, &["backend", "service", "name"]))
+            .chain(str_at(doc, &["spec", "defaultBackend", "service", "name"]));
+        for service in services {
+            if let Some(selected) = backends.get(&(namespace(doc), service)) {
+                selected.iter().for_each(|i| exposed[*i] = true);
+            }
+        }
+    }
+
+    // workloads behind the Service `name` in `namespace`, else the workload of that name
+    let resolve = |namespace: &str, name: &str| {
+        backends
+            .get(&(namespace, name))
+            .cloned()
+            .unwrap_or_else(|| {
+                workloads
+                    .iter()
+                    .position(|w| w.namespace == namespace && w.name == name)
+                    .into_iter()
+                    .collect()
+            })
+    };
+
+    let mut services = vec![];
+    for (i, workload) in workloads.iter().enumerate() {
+        let mut depends_on = vec![];
+        for value in workload.env_values() {
+            let is_host = |c: char| c.is_ascii_alphanumeric() || c == '-' || c == '.';
+            for host in value.split(|c: char| !is_host(c)) {
+                let labels = host.split('.').collect::<Vec<_>>();
+                let target = labels
+                    .get(1)
+                    .and_then(|ns| backends.get(&(*ns, labels[0])))
+                    .or_else(|| backends.get(&(workload.namespace, labels[0])));
+                for j in target.into_iter().flatten().filter(|j| **j != i) {
+                    depends_on.push(node_name(&workloads[*j]));
+                }
+            }
+        }
+        if let Some(listed) = workload.annotation(DEPENDS_ON_KEY) {
+            for entry in listed.split(',').map(str::trim).filter(|s| !s.is_empty()) {
+                // `name`, `name.namespace` or `namespace/name`, in the workload's namespace
+                // unless it names one
+                let (namespace, name) = match entry.split_once('/') {
+                    Some((namespace, name)) => (namespace, name),
+                    None => match entry.split_once('.') {
+                        Some((name, rest)) => (rest.split('.').next().unwrap_or(rest), name),
+                        None => (workload.namespace, entry),
+                    },
+                };
+                let targets = resolve(namespace, name);
+                if targets.is_empty() {
+                    // left for the topology to match by node name or report
+                    depends_on.push(entry.to_string());
+                }
+                for j in targets.into_iter().filter(|j| *j != i) {
+                    depends_on.push(node_name(&workloads[j]));
+                }
+            }
+        }
+
+        services.push(Service {
+            group: workload.namespace.to_string(),
+            name: node_name(workload),
+            replicas: at(workload.doc, &["spec", "replicas"])
+                .and_then(Value::as_u64)
+                .unwrap_or(1) as u32,
+            capacity: workload.number(CAPACITY_KEY)?,
+            gain: workload.number(GAIN_KEY)?,
+            depends_on,
+            exposed: exposed[i],
+            load: workload.number(LOAD_KEY)?,
+        });
+    }
+    Ok(services)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::import::services::{topology_from_services, yaml_documents};
+
+    const MANIFESTS: &str = r#"
+apiVersion: apps/v1
+kind: Deployment
+metadata:
+  name: web
+  namespace: shop
+  annotations:
+    faultgraph/capacity: "50"
+    faultgraph/load: "120"
+spec:
+  replicas: 3
+  template:
+    metadata:
+      labels: {app: web}
+    spec:
+      containers:
+        - name: web
+          env:
+            - {name: ORDERS_URL, value: "http://orders:8080/api"}
+            - {name: AUTH_HOST, value: "auth.identity.svc.cluster.local"}
+---
+apiVersion: apps/v1
+kind: Deployment
+metadata: {name: orders, namespace: shop}
+spec:
+  template:
+    metadata:
+      labels: {app: orders}
+      annotations: {faultgraph/depends-on: auth}
+---
+apiVersion: apps/v1
+kind: Deployment
+metadata: {name: auth, namespace: identity}
+spec:
+  replicas: 2
+  template:
+    metadata:
+      labels: {app: auth}
+      annotations: {faultgraph/capacity: "40"}
+---
+apiVersion: v1
+kind: Service
+metadata: {name: orders, namespace: shop}
+spec:
+  selector: {app: orders}
+---
+apiVersion: v1
+kind: Service
+metadata: {name: auth, namespace: identity}
+spec:
+  selector: {app: auth}
+---
+apiVersion: networking.k8s.io/v1
+kind: Ingress
+metadata: {name: shop, namespace: shop}
+spec:
+  defaultBackend:
+    service: {name: web-svc}
+---
+apiVersion: v1
+kind: Service
+metadata: {name: web-svc, namespace: shop}
+spec:
+  selector: {app: web}
+"#;
+
+    fn services(text: &str) -> Result<Vec<Service>, String> {
+        let docs = yaml_documents(text).unwrap();
+        services_from_manifests(&docs.iter().collect::<Vec<_>>())
+    }
+
+    #[test]
+    fn test_dependencies_from_env_and_annotations() {
+        let topology = topology_from_services(&services(MANIFESTS).unwrap()).unwrap();
+
+        let groups = topology
+            .groups
+            .iter()
+            .map(|g| (g.name.as_str(), g.nodes.len()))
+            .collect::<Vec<_>>();
+        assert_eq!(vec![("shop", 2), ("identity", 1)], groups);
+        let web = &topology.groups[0].nodes[0];
+        assert_eq!(Some(150.0), web.capacity);
+        assert_eq!(1.0, web.gain);
+        assert_eq!(Some(80.0), topology.groups[1].nodes[0].capacity);
+        assert_eq!(vec!["orders"], topology.unset_capacities());
+
+        let edges = topology
+            .edges
+            .iter()
+            .map(|e| (e.from.as_str(), e.to.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec![("web", "orders"), ("web", "auth"), ("orders", "auth")],
+            edges
+        );
+        // exposed through the ingress, although the service itself is ClusterIP
+        assert_eq!(1, topology.entry.len());
+        assert_eq!(
+            ("web", 120.0),
+            (topology.entry[0].node.as_str(), topology.entry[0].load)
+        );
+    }
+
+    #[test]
+    fn test_invalid_annotations() {
+        let unknown = MANIFESTS.replace("depends-on: auth", "depends-on: payments");
+        let result = topology_from_services(&services(&unknown).unwrap());
+        assert_eq!(
+            Err("orders depends on unknown service 'payments'".to_string()),
+            result.map(|_| ())
+        );
+
+        let invalid = MANIFESTS.replace("\"40\"", "forty");
+        assert_eq!(
+            Err("auth: invalid faultgraph/capacity 'forty'".to_string()),
+            services(&invalid).map(|_| ())
+        );
+    }
+
+    #[test]
+    fn test_same_name_in_two_namespaces() {
+        let shop_auth = r#"---
+apiVersion: apps/v1
+kind: Deployment
+metadata: {name: auth, namespace: shop}
+spec:
+  replicas: 0
+  template:
+    metadata:
+      labels: {app: shop-auth}
+"#;
+        let clash = MANIFESTS.replace("depends-on: auth", "depends-on: auth.identity") + shop_auth;
+        let topology = topology_from_services(&services(&clash).unwrap()).unwrap();
+
+        let nodes = topology
+            .groups
+            .iter()
+            .flat_map(|g| g.nodes.iter().map(|n| n.name.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(vec!["web", "orders", "shop/auth", "identity/auth"], nodes);
+        // scaled to zero
+        assert_eq!(Some(0.0), topology.groups[0].nodes[2].capacity);
+        // auth.identity.svc still resolves to the identity namespace
+        let edges = topology
+            .edges
+            .iter()
+            .map(|e| (e.from.as_str(), e.to.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec![
+                ("web", "orders"),
+                ("web", "identity/auth"),
+                ("orders", "identity/auth")
+            ],
+            edges
+        );
+
+        // a bare name means the workload's own namespace, like an env host
+        let topology =
+            topology_from_services(&services(&(MANIFESTS.to_string() + shop_auth)).unwrap())
+                .unwrap();
+        assert!(
+            topology
+                .edges
+                .iter()
+                .any(|e| e.from == "orders" && e.to == "shop/auth")
+        );
+    }
+}
@@ -0,0 +1,4 @@
+pub mod compose;
+pub mod kubernetes;
+pub mod services;
+pub mod traces;
@@ -0,0 +1,132 @@
+use crate::import::compose::services_from_compose;
+use crate::import::kubernetes::services_from_manifests;
+use crate::scenario::topology::{EdgeSpec, EntrySpec, GroupSpec, NodeSpec, Topology};
+use serde::Deserialize;
+use serde_yaml::Value;
+
+/// annotation or label with the capacity of one replica in rps
+pub const CAPACITY_KEY: &str = "faultgraph/capacity";
+/// annotation or label with the gain, 1.0 for services with dependencies otherwise
+pub const GAIN_KEY: &str = "faultgraph/gain";
+/// annotation or label with the entry load in rps of a service exposed outside
+pub const LOAD_KEY: &str = "faultgraph/load";
+/// annotation or label with extra comma-separated dependencies
+pub const DEPENDS_ON_KEY: &str = "faultgraph/depends-on";
+/// label with the group of a compose service
+pub const GROUP_KEY: &str = "faultgraph/group";
+
+/// A deployed service as described by a manifest, before it becomes a node.
+pub struct Service {
+    pub group: String,
+    pub name: String,
+    pub replicas: u32,
+    /// per replica
+    pub capacity: Option<f64>,
+    pub gain: Option<f64>,
+    pub depends_on: Vec<String>,
+    /// reachable from outside, e.g. through a load balancer or published port
+    pub exposed: bool,
+    pub load: Option<f64>,
+}
+
+/// Every YAML document in `text`.
+pub fn yaml_documents(text: &str) -> Result<Vec<Value>, String> {
+    serde_yaml::Deserializer::from_str(text)
+        .map(Value::deserialize)
+        .filter(|doc| !matches!(doc, Ok(Value::Null)))
+        .collect::<Result<_, _>>()
+        .map_err(|e| e.to_string())
+}
+
+/// Reads compose files, recognised by their `services` mapping, and Kubernetes manifests.
+/// Manifests are read together, as Services and the workloads they select are often in
+/// different files.
+pub fn services_from_documents(docs: &[Value]) -> Result<Vec<Service>, String> {
+    let (compose, manifests): (Vec<&Value>, Vec<&Value>) = docs
+        .iter()
+        .partition(|doc| doc.get("services").is_some_and(Value::is_mapping));
+    let mut services = vec![];
+    for doc in compose {
+        services.extend(services_from_compose(doc)?);
+    }
+    services.extend(services_from_manifests(&manifests)?);
+    Ok(services)
+}
+
+/// Turns `services` into a topology with one node per service and one group per `group`, in
+/// order of first appearance. Capacity is replicas times the per-replica capacity, left empty
+/// when unknown, and 0 for a service scaled to zero. Every dependency is an edge of weight 1. Exposed services are the entry
+/// nodes, or the services nothing depends on when none is exposed.
+pub fn topology_from_services(services: &[Service]) -> Result<Topology, String> {
+    let mut edges = vec![];
+    for service in services {
+        for dependency in &service.depends_on {
+            if !services.iter().any(|s| s.name == *dependency) {
+                return Err(format!(
+                    "{} depends on unknown service '{}'",
+                    service.name, dependency
+                ));
+            }
+            if edges
+                .iter()
+                .any(|e: &EdgeSpec| e.from == service.name && e.to == *dependency)
+            {
+                continue;
+            }
+            edges.push(EdgeSpec {
+                from: service.name.clone(),
+                to: dependency.clone(),
+                weight: 1.0,
+                latency: None,
+            });
+        }
+    }
+
+    let mut groups: Vec<GroupSpec> = vec![];
+    for service in services {
+        let node = NodeSpec {
+            name: service.name.clone(),
+            capacity: match service.replicas {
+                0 => Some(0.0),
+                replicas => service.capacity.map(|c| c * replicas as f64),
+            },
+            gain: service.gain.unwrap_or(if service.depends_on.is_empty() {
+                0.0
+            } else {
+                1.0
+            }),
+            service_time: None,
+            cost: None,
+        };
+        match groups.iter_mut().find(|g| g.name == service.group) {
+            Some(group) => group.nodes.push(node),
+            None => groups.push(GroupSpec {
+                name: service.group.clone(),
+                nodes: vec![node],
+            }),
+        }
+    }
+
+    let exposed = services.iter().any(|s| s.exposed);
+    let entry = services
+        .iter()
+        .filter(|s| {
+            if exposed {
+                s.exposed
+            } else {
+                !edges.iter().any(|e| e.to == s.name)
+            }
+        })
+        .map(|s| EntrySpec {
+            node: s.name.clone(),
+            load: s.load.unwrap_or(0.0),
+        })
+        .collect();
+
+    Ok(Topology {
+        groups,
+        edges,
+        entry,
+        ops_per_turn: 1,
+    })
+}
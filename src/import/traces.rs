@@ -0,0 +1,461 @@
+use crate::scenario::topology::{EdgeSpec, EntrySpec, GroupSpec, NodeSpec, Topology};
+use clap::ValueEnum;
+use serde::Deserialize;
+use serde::de::DeserializeOwned;
+use std::collections::{BTreeMap, HashMap};
+
+/// group of services without a namespace
+const DEFAULT_NAMESPACE: &str = "default";
+
+#[derive(Clone, Copy, Debug, PartialEq, ValueEnum)]
+pub enum TraceFormat {
+    /// OTLP JSON if the file mentions `resourceSpans`, Jaeger JSON otherwise
+    Auto,
+    /// OTLP JSON, one export request per file or per line
+    Otlp,
+    /// the JSON the Jaeger UI and query API return
+    Jaeger,
+}
+
+/// A span reduced to what the importer needs.
+pub struct TraceSpan {
+    trace_id: String,
+    span_id: String,
+    parent_id: Option<String>,
+    service: String,
+    namespace: Option<String>,
+    /// ns since the epoch
+    start: u64,
+    end: u64,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct OtlpExport {
+    #[serde(default)]
+    resource_spans: Vec<OtlpResourceSpans>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct OtlpResourceSpans {
+    #[serde(default)]
+    resource: OtlpResource,
+    #[serde(default, alias = "instrumentationLibrarySpans")]
+    scope_spans: Vec<OtlpScopeSpans>,
+}
+
+#[derive(Default, Deserialize)]
+struct OtlpResource {
+    #[serde(default)]
+    attributes: Vec<OtlpAttribute>,
+}
+
+#[derive(Deserialize)]
+struct OtlpAttribute {
+    key: String,
+    value: OtlpValue,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct OtlpValue {
+    string_value: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct OtlpScopeSpans {
+    #[serde(default)]
+    spans: Vec<OtlpSpan>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct OtlpSpan {
+    trace_id: String,
+    span_id: String,
+    #[serde(default)]
+    parent_span_id: String,
+    start_time_unix_nano: Nanos,
+    end_time_unix_nano: Nanos,
+}
+
+/// OTLP JSON writes 64-bit integers as strings, some exporters don't
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Nanos {
+    Text(String),
+    Number(u64),
+}
+
+impl Nanos {
+    fn value(&self) -> Result<u64, String> {
+        match self {
+            Nanos::Text(s) => s.parse().map_err(|_| format!("invalid timestamp '{s}'")),
+            Nanos::Number(n) => Ok(*n),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct JaegerExport {
+    data: Vec<JaegerTrace>,
+}
+
+#[derive(Deserialize)]
+struct JaegerTrace {
+    spans: Vec<JaegerSpan>,
+    processes: HashMap<String, JaegerProcess>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct JaegerSpan {
+    #[serde(rename = "traceID")]
+    trace_id: String,
+    #[serde(rename = "spanID")]
+    span_id: String,
+    #[serde(default)]
+    references: Vec<JaegerReference>,
+    /// µs since the epoch
+    start_time: u64,
+    /// µs
+    duration: u64,
+    #[serde(rename = "processID")]
+    process_id: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct JaegerReference {
+    ref_type: String,
+    #[serde(rename = "spanID")]
+    span_id: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct JaegerProcess {
+    service_name: String,
+    #[serde(default)]
+    tags: Vec<JaegerTag>,
+}
+
+#[derive(Deserialize)]
+struct JaegerTag {
+    key: String,
+    value: serde_json::Value,
+}
+
+/// Every JSON document in `text`, so both single documents and JSON Lines work.
+fn documents<T: DeserializeOwned>(text: &str) -> Result<Vec<T>, String> {
+    serde_json::Deserializer::from_str(text)
+        .into_iter::<T>()
+        .collect::<Result<_, _>>()
+        .map_err(|e| e.to_string())
+}
+
+fn otlp_spans(text: &str) -> Result<Vec<TraceSpan>, String> {
+    let mut spans = vec![];
+    for export in documents::<OtlpExport>(text)? {
+        for resource_spans in export.resource_spans {
+            let attribute = |key: &str| {
+                resource_spans
+                    .resource
+                    .attributes
+                    .iter()
+                    .find(|a| a.key == key)
+                    .and_then(|a| a.value.string_value.clone())
+            };
+            let service = attribute("service.name").ok_or("resource without service.name")?;
+            let namespace = attribute("service.namespace");
+            for span in resource_spans.scope_spans.iter().flat_map(|s| &s.spans) {
+                spans.push(TraceSpan {
+                    trace_id: span.trace_id.clone(),
+                    span_id: span.span_id.clone(),
+                    parent_id: Some(span.parent_span_id.clone()).filter(|id| !id.is_empty()),
+                    service: service.clone(),
+                    namespace: namespace.clone(),
+                    start: span.start_time_unix_nano.value()?,
+                    end: span.end_time_unix_nano.value()?,
+                });
+            }
+        }
+    }
+    Ok(spans)
+}
+
+fn jaeger_spans(text: &str) -> Result<Vec<TraceSpan>, String> {
+    let mut spans = vec![];
+    for export in documents::<JaegerExport>(text)? {
+        for trace in export.data {
+            for span in trace.spans {
+                let process = trace
+                    .processes
+                    .get(&span.process_id)
+                    .ok_or_else(|| format!("span {} has an unknown process", span.span_id))?;
+                let parent = span
+                    .references
+                    .iter()
+                    .find(|r| r.ref_type == "CHILD_OF")
+                    .or(span.references.first());
+                spans.push(TraceSpan {
+                    parent_id: parent.map(|r| r.span_id.clone()),
+                    service: process.service_name.clone(),
+                    namespace: process
+                        .tags
+                        .iter()
+                        .find(|t| t.key == "service.namespace")
+                        .and_then(|t| t.value.as_str().map(str::to_string)),
+                    start: span.start_time * 1000,
+                    end: (span.start_time + span.duration) * 1000,
+                    trace_id: span.trace_id,
+                    span_id: span.span_id,
+                });
+            }
+        }
+    }
+    Ok(spans)
+}
+
+/// Parses the spans of one exported trace file.
+pub fn read_spans(text: &str, format: TraceFormat) -> Result<Vec<TraceSpan>, String> {
+    match format {
+        TraceFormat::Auto if text.contains("\"resourceSpans\"") => otlp_spans(text),
+        TraceFormat::Auto => jaeger_spans(text),
+        TraceFormat::Otlp => otlp_spans(text),
+        TraceFormat::Jaeger => jaeger_spans(text),
+    }
+}
+
+fn round(value: f64, decimals: i32) -> f64 {
+    let scale = 10f64.powi(decimals);
+    (value * scale).round() / scale
+}
+
+/// (namespace, service) of the span
+fn service(span: &TraceSpan) -> (&str, &str) {
+    (
+        span.namespace.as_deref().unwrap_or(DEFAULT_NAMESPACE),
+        span.service.as_str(),
+    )
+}
+
+/// Derives a topology from `spans`. Each service becomes a node grouped by its namespace, named
+/// `namespace/service` when another namespace has a service of the same name. A request to a
+/// service is a span whose parent belongs to another service or is missing; spans under a
+/// parent of the same service are internal work. Edge weights are the calls a request makes to
+/// the callee, and their sum is the node's gain. Services receiving root spans become entry
+/// nodes with the observed root span rate as load, which undercounts sampled traces.
+/// Capacities are left empty.
+pub fn topology_from_spans(spans: &[TraceSpan]) -> Topology {
+    let by_id = spans
+        .iter()
+        .map(|s| ((s.trace_id.as_str(), s.span_id.as_str()), s))
+        .collect::<HashMap<_, _>>();
+
+    // services are keyed by (namespace, service), which keeps the output stable and grouped
+    let mut services = BTreeMap::new();
+    let mut requests = HashMap::new();
+    let mut roots = HashMap::new();
+    let mut calls = BTreeMap::new();
+    for span in spans {
+        let callee = service(span);
+        services.insert(callee, ());
+        let caller = span
+            .parent_id
+            .as_deref()
+            .and_then(|id| by_id.get(&(span.trace_id.as_str(), id)))
+            .map(|parent| service(parent));
+        if caller == Some(callee) {
+            continue;
+        }
+        *requests.entry(callee).or_insert(0usize) += 1;
+        match caller {
+            Some(caller) => *calls.entry((caller, callee)).or_insert(0usize) += 1,
+            None => *roots.entry(callee).or_insert(0usize) += 1,
+        }
+    }
+
+    // a service name in more than one namespace is qualified by its namespace
+    let mut namespaces = HashMap::new();
+    services
+        .keys()
+        .for_each(|(_, name)| *namespaces.entry(*name).or_insert(0) += 1);
+    let node_name = |(namespace, name): (&str, &str)| {
+        if namespaces[name] > 1 {
+            format!("{}/{}", namespace, name)
+        } else {
+            name.to_string()
+        }
+    };
+
+    let weight = |caller, count: usize| round(count as f64 / requests[&caller] as f64, 3);
+    let mut groups: Vec<GroupSpec> = vec![];
+    for &(namespace, name) in services.keys() {
+        let gain = calls
+            .iter()
+            .filter(|((caller, _), _)| *caller == (namespace, name))
+            .map(|((caller, _), count)| weight(*caller, *count))
+            .fold(0.0, |acc, w| acc + w);
+        let node = NodeSpec {
+            name: node_name((namespace, name)),
+            capacity: None,
+            gain: round(gain, 3),
+            service_time: None,
+            cost: None,
+        };
+        match groups.last_mut() {
+            Some(group) if group.name == namespace => group.nodes.push(node),
+            _ => groups.push(GroupSpec {
+                name: namespace.to_string(),
+                nodes: vec![node],
+            }),
+        }
+    }
+
+    let start = spans.iter().map(|s| s.start).min().unwrap_or(0);
+    let end = spans.iter().map(|s| s.end).max().unwrap_or(0);
+    let seconds = end.saturating_sub(start) as f64 / 1e9;
+    let mut entry = roots
+        .iter()
+        .map(|(service, count)| EntrySpec {
+            node: node_name(*service),
+            load: round(*count as f64 / seconds.max(1.0), 2),
+        })
+        .collect::<Vec<_>>();
+    entry.sort_by(|a, b| a.node.cmp(&b.node));
+
+    Topology {
+        groups,
+        edges: calls
+            .iter()
+            .map(|((caller, callee), count)| EdgeSpec {
+                from: node_name(*caller),
+                to: node_name(*callee),
+                weight: weight(*caller, *count),
+                latency: None,
+            })
+            .collect(),
+        entry,
+        ops_per_turn: 1,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+
+    fn otlp_resource(service: &str, namespace: &str, spans: &[(&str, &str, &str)]) -> String {
+        let spans = spans
+            .iter()
+            .map(|(trace, id, parent)| {
+                format!(
+                    r#"{{"traceId":"{trace}","spanId":"{id}","parentSpanId":"{parent}","name":"op","startTimeUnixNano":"1000000000","endTimeUnixNano":"3000000000"}}"#
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(",");
+        format!(
+            r#"{{"resource":{{"attributes":[{{"key":"service.name","value":{{"stringValue":"{service}"}}}},{{"key":"service.namespace","value":{{"stringValue":"{namespace}"}}}}]}},"scopeSpans":[{{"spans":[{spans}]}}]}}"#
+        )
+    }
+
+    #[test]
+    fn test_otlp_calls_per_request() {
+        // two requests to api; the first calls db twice through an internal span, the second once
+        let api = otlp_resource(
+            "api",
+            "edge",
+            &[("t1", "a1", ""), ("t1", "a2", "a1"), ("t2", "a3", "")],
+        );
+        let db = otlp_resource(
+            "db",
+            "data",
+            &[("t1", "d1", "a2"), ("t1", "d2", "a2"), ("t2", "d3", "a3")],
+        );
+        let text = format!(r#"{{"resourceSpans":[{api},{db}]}}"#);
+
+        let spans = read_spans(&text, TraceFormat::Auto).unwrap();
+        assert_eq!(6, spans.len());
+        let topology = topology_from_spans(&spans);
+
+        let names = |g: &GroupSpec| g.nodes.iter().map(|n| n.name.clone()).collect::<Vec<_>>();
+        assert_eq!("data", topology.groups[0].name);
+        assert_eq!(vec!["db"], names(&topology.groups[0]));
+        assert_eq!("edge", topology.groups[1].name);
+        assert_eq!(None, topology.groups[1].nodes[0].capacity);
+        assert_relative_eq!(1.5, topology.groups[1].nodes[0].gain);
+        assert_relative_eq!(0.0, topology.groups[0].nodes[0].gain);
+
+        assert_eq!(1, topology.edges.len());
+        assert_eq!(
+            ("api", "db"),
+            (
+                topology.edges[0].from.as_str(),
+                topology.edges[0].to.as_str()
+            )
+        );
+        assert_relative_eq!(1.5, topology.edges[0].weight);
+        // two root spans over two seconds
+        assert_eq!("api", topology.entry[0].node);
+        assert_relative_eq!(1.0, topology.entry[0].load);
+        assert!(topology.build().is_ok());
+    }
+
+    #[test]
+    fn test_jaeger_default_namespace() {
+        let text = r#"{"data":[{"traceID":"t1","spans":[
+            {"traceID":"t1","spanID":"s1","operationName":"GET","references":[],"startTime":0,"duration":500000,"processID":"p1"},
+            {"traceID":"t1","spanID":"s2","operationName":"auth","references":[{"refType":"CHILD_OF","traceID":"t1","spanID":"s1"}],"startTime":100,"duration":100,"processID":"p2"},
+            {"traceID":"t1","spanID":"s3","operationName":"cart","references":[{"refType":"CHILD_OF","traceID":"t1","spanID":"s1"}],"startTime":200,"duration":100,"processID":"p3"}
+        ],"processes":{
+            "p1":{"serviceName":"frontend","tags":[]},
+            "p2":{"serviceName":"auth","tags":[{"key":"service.namespace","type":"string","value":"identity"}]},
+            "p3":{"serviceName":"cart","tags":[]}
+        }}]}"#;
+
+        let topology = topology_from_spans(&read_spans(text, TraceFormat::Auto).unwrap());
+
+        let groups = topology
+            .groups
+            .iter()
+            .map(|g| (g.name.as_str(), g.nodes.len()))
+            .collect::<Vec<_>>();
+        assert_eq!(vec![("default", 2), ("identity", 1)], groups);
+        assert_relative_eq!(2.0, topology.groups[0].nodes[1].gain);
+        assert_eq!(2, topology.edges.len());
+        assert_eq!(1, topology.entry.len());
+        assert_eq!("frontend", topology.entry[0].node);
+    }
+
+    #[test]
+    fn test_same_service_in_two_namespaces() {
+        // api calls the db of its own namespace and the shared one
+        let api = otlp_resource("api", "edge", &[("t1", "a1", "")]);
+        let edge_db = otlp_resource("db", "edge", &[("t1", "d1", "a1")]);
+        let data_db = otlp_resource("db", "data", &[("t1", "d2", "a1"), ("t1", "d3", "d2")]);
+        let text = format!(r#"{{"resourceSpans":[{api},{edge_db},{data_db}]}}"#);
+
+        let topology = topology_from_spans(&read_spans(&text, TraceFormat::Auto).unwrap());
+
+        let nodes = topology
+            .groups
+            .iter()
+            .flat_map(|g| g.nodes.iter().map(|n| n.name.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(vec!["data/db", "api", "edge/db"], nodes);
+        let edges = topology
+            .edges
+            .iter()
+            .map(|e| (e.from.as_str(), e.to.as_str(), e.weight))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec![("api", "data/db", 1.0), ("api", "edge/db", 1.0)],
+            edges
+        );
+        assert_relative_eq!(2.0, topology.groups[1].nodes[0].gain);
+        assert!(topology.build().is_ok());
+    }
+}
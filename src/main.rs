@@ -1,47 +1,789 @@
-use crate::scenario::basic::BasicScenario;
-use crate::scenario::random::RandomStressScenario;
-use crate::scenario::stress::StressScenario;
+use crate::analysis::calibration::RateSeries;
+use crate::analysis::flow::FlowSeries;
+use crate::analysis::monte_carlo::run_monte_carlo;
+use crate::analysis::sensitivity::{ParameterKind, parse_percent};
+use crate::analysis::slo::Slo;
+use crate::cli::blast_radius::print_blast_radius;
+use crate::cli::bottlenecks::print_bottlenecks;
+use crate::cli::calibrate::print_calibration;
+use crate::cli::cascade::print_cascade;
+use crate::cli::compare::{print_comparison, read_run, record_run};
+use crate::cli::cycles::print_feedback_loops;
+use crate::cli::episodes::print_episodes;
+use crate::cli::flows::print_flows;
+use crate::cli::game::print_game;
+use crate::cli::goodput::print_goodput;
+use crate::cli::import::print_import;
+use crate::cli::latency::print_latency;
+use crate::cli::monte_carlo::print_monte_carlo;
+use crate::cli::sensitivity::print_sensitivity;
+use crate::cli::slo::{print_slos, write_slo_csv};
+use crate::export::diagram::{DiagramFormat, write_dot, write_mermaid};
+use crate::export::html::write_report;
+use crate::export::jsonl::{write_run, write_turn};
+use crate::export::openmetrics::write_openmetrics;
+use crate::export::serve::serve_metrics;
+use crate::import::services::{services_from_documents, topology_from_services, yaml_documents};
+use crate::import::traces::{TraceFormat, read_spans, topology_from_spans};
+use crate::remote::http::serve_http;
+use crate::remote::protocol::{serve_lines, serve_socket};
+use crate::scenario::kind::{ScenarioKind, ScenarioSource};
+use crate::scenario::topology::Topology;
 use crate::simulation::engine::SimulationEngine;
-use crate::tui::app::App;
+use crate::simulation::environment::Environment;
+use crate::simulation::game::{Game, HighScores, Outcome};
+use crate::simulation::noise::Noise;
+use crate::simulation::policy::{PolicyKind, act};
+use crate::simulation::schedule::Schedule;
+use crate::tui::app::{App, View};
 use crate::tui::draw::draw_app;
-use clap::{Parser, ValueEnum};
+use clap::{Parser, Subcommand, ValueEnum};
 use crossterm::event::KeyCode::{Down, Up};
 use crossterm::event::{Event, KeyCode, KeyEventKind};
+use std::fs::File;
 use std::io;
+use std::io::{BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
 mod analysis;
+mod cli;
+mod export;
 mod graph;
+mod import;
+mod remote;
 mod scenario;
 mod simulation;
 mod state;
 mod tui;
 
-#[derive(Clone, Debug, ValueEnum)]
-#[value(rename_all = "lowercase")]
-enum ScenarioKind {
-    Basic,
-    Random,
-    Stress,
-}
-
 #[derive(Parser, Debug)]
 struct Args {
     #[arg(long, short, value_enum, default_value_t = ScenarioKind::Basic)]
     scenario: ScenarioKind,
+
+    /// Load the topology from a file written by an `import-*` command instead of a scenario
+    #[arg(long, value_name = "PATH", conflicts_with = "scenario")]
+    topology: Option<PathBuf>,
+
+    /// Extra SLO as SCOPE:INDICATOR:OBJECTIVE[/WINDOW], e.g. `Ingress:availability:99.5/20`
+    /// or `Database:backlog<50:99`
+    #[arg(long, global = true)]
+    slo: Vec<String>,
+
+    /// Serve the latest turn's metrics in OpenMetrics format at http://ADDR/metrics while
+    /// the TUI runs
+    #[arg(long, value_name = "ADDR")]
+    metrics_addr: Option<String>,
+
+    /// Let a built-in policy act before every step in the TUI
+    #[arg(long, value_enum, value_name = "POLICY")]
+    policy: Option<PolicyKind>,
+
+    /// Play the TUI as a game against the scenario's objectives, with noise seeded by `--seed`
+    #[arg(long)]
+    game: bool,
+
+    /// Noise seed of `--game`, high scores are kept per scenario and seed
+    #[arg(long, default_value_t = 0, requires = "game")]
+    seed: u64,
+
+    /// High-score file of `--game` and `play`
+    #[arg(
+        long,
+        global = true,
+        value_name = "PATH",
+        default_value = "faultgraph-scores.json"
+    )]
+    scores: PathBuf,
+
+    /// Also write every turn in this format, see the README for the schema
+    #[arg(long, global = true, value_enum)]
+    emit: Option<Emit>,
+
+    /// File for `--emit`, stdout by default; required in the TUI
+    #[arg(long, global = true, value_name = "PATH")]
+    emit_out: Option<PathBuf>,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum Emit {
+    /// one JSON object per turn
+    Jsonl,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Run the scenario headless without printing anything, e.g. to `--emit` the run
+    Run {
+        #[arg(long, short, default_value_t = 100)]
+        turns: usize,
+    },
+    /// Rank nodes by the entry load at which they saturate, without running the simulation
+    Bottlenecks {
+        #[arg(long, short, default_value_t = 10)]
+        top: usize,
+    },
+    /// Find feedback loops and fail if any of them amplifies load without bound
+    Cycles,
+    /// Fail each node and group in turn and rank them by how many nodes are starved or
+    /// overloaded once upstream nodes route around the failure
+    BlastRadius {
+        /// Total entry load in rps, defaults to 80% of the load that first saturates a node or
+        /// else the scenario's entry load
+        #[arg(long, short)]
+        load: Option<f64>,
+        #[arg(long, short, default_value_t = 10)]
+        top: usize,
+    },
+    /// Run the scenario headless and reconstruct which failure caused which
+    Cascade {
+        #[arg(long, short, default_value_t = 100)]
+        turns: usize,
+    },
+    /// Scale each capacity, gain and edge weight in turn and rank the changes by outcome
+    Sensitivity {
+        /// Size of each change in percent, applied both up and down, below 100
+        #[arg(long, short, default_value_t = 10.0, value_parser = parse_percent)]
+        percent: f64,
+        /// Parameter kinds to change; edge weights dominate the run time on dense topologies
+        #[arg(long, short, value_enum, value_delimiter = ',', default_values_t = [ParameterKind::Capacity, ParameterKind::Gain, ParameterKind::Weight])]
+        kinds: Vec<ParameterKind>,
+        #[arg(long, default_value_t = 100)]
+        turns: usize,
+        #[arg(long, short, default_value_t = 10)]
+        top: usize,
+    },
+    /// Run the scenario under many noise seeds and report failure probabilities and health spread
+    MonteCarlo {
+        #[arg(long, short, default_value_t = 100)]
+        runs: u64,
+        /// First seed, runs use consecutive seeds from here
+        #[arg(long, default_value_t = 0)]
+        seed: u64,
+        #[arg(long, short, default_value_t = 100)]
+        turns: usize,
+        /// Relative jitter on entry load, e.g. 0.1 for ±10%
+        #[arg(long, default_value_t = 0.1)]
+        load_jitter: f64,
+        /// Relative per-turn noise on node capacity
+        #[arg(long, default_value_t = 0.05)]
+        capacity_noise: f64,
+        /// Per-node, per-turn probability of a transient failure
+        #[arg(long, default_value_t = 0.0)]
+        failure_rate: f64,
+        /// Length of a transient failure in turns
+        #[arg(long, default_value_t = 3)]
+        outage: usize,
+        /// Turn for the failure probabilities, defaults to the last turn
+        #[arg(long)]
+        by: Option<usize>,
+        /// Turns between rows of the health percentile table
+        #[arg(long, default_value_t = 10)]
+        every: usize,
+    },
+    /// Play episodes of the scenario as a control environment with each policy and compare
+    /// their returns
+    Episodes {
+        /// Policies to compare, all of them by default
+        #[arg(long, short, value_enum)]
+        policy: Vec<PolicyKind>,
+        #[arg(long, short, default_value_t = 10)]
+        episodes: u64,
+        /// First seed, episodes use consecutive seeds from here
+        #[arg(long, default_value_t = 0)]
+        seed: u64,
+        #[arg(long, short, default_value_t = 100)]
+        turns: usize,
+        /// Relative jitter on entry load, e.g. 0.1 for ±10%
+        #[arg(long, default_value_t = 0.1)]
+        load_jitter: f64,
+        /// Relative per-turn noise on node capacity
+        #[arg(long, default_value_t = 0.05)]
+        capacity_noise: f64,
+    },
+    /// Play the scenario as a game with a built-in policy and record its score
+    Play {
+        #[arg(long, short, value_enum, default_value_t = PolicyKind::Noop)]
+        policy: PolicyKind,
+        /// Noise seed, high scores are kept per scenario and seed
+        #[arg(long, default_value_t = 0)]
+        seed: u64,
+    },
+    /// Run the scenario headless and report attainment, burn rate and budget of every SLO
+    Slo {
+        #[arg(long, short, default_value_t = 100)]
+        turns: usize,
+    },
+    /// Run the scenario headless and show how much load each group pushes into each group
+    Flows {
+        #[arg(long, short, default_value_t = 100)]
+        turns: usize,
+        /// Also write the flows of every turn as CSV
+        #[arg(long, short)]
+        out: Option<PathBuf>,
+    },
+    /// Run the scenario headless and trace how much entry load is served end to end
+    Goodput {
+        #[arg(long, short, default_value_t = 100)]
+        turns: usize,
+        /// Turns between rows
+        #[arg(long, default_value_t = 10)]
+        every: usize,
+    },
+    /// Run the scenario headless and estimate latency from service times and queueing
+    Latency {
+        #[arg(long, short, default_value_t = 100)]
+        turns: usize,
+        /// Turns between rows
+        #[arg(long, default_value_t = 10)]
+        every: usize,
+    },
+    /// Run the scenario headless and write node and group metrics of every turn in
+    /// OpenMetrics text format
+    Openmetrics {
+        #[arg(long, short, default_value_t = 100)]
+        turns: usize,
+        /// Timestamp of turn 0 in Unix seconds, each turn is one second later
+        #[arg(long, default_value_t = 0)]
+        start: u64,
+        /// Write to a file instead of stdout
+        #[arg(long, short)]
+        out: Option<PathBuf>,
+    },
+    /// Write the topology as a diagram with groups as clusters, optionally styled with the
+    /// state of a turn
+    Diagram {
+        #[arg(long, short, value_enum, default_value_t = DiagramFormat::Dot)]
+        format: DiagramFormat,
+        /// Run this many turns and colour nodes by health and size edges by load
+        #[arg(long, short)]
+        turns: Option<usize>,
+        /// Stop at the first turn a node fails, within `--turns` (100 by default)
+        #[arg(long)]
+        at_failure: bool,
+        /// Write to a file instead of stdout
+        #[arg(long, short)]
+        out: Option<PathBuf>,
+    },
+    /// Run the scenario headless and write a self-contained HTML report with the topology,
+    /// group charts, failures and operator actions
+    Report {
+        #[arg(long, short, default_value_t = 100)]
+        turns: usize,
+        /// Throttle a group at a turn, e.g. `Database@40`
+        #[arg(long, value_name = "GROUP@TURN")]
+        throttle: Vec<String>,
+        /// Boost a group at a turn, e.g. `Cache@40`
+        #[arg(long, value_name = "GROUP@TURN")]
+        boost: Vec<String>,
+        #[arg(long, short)]
+        out: PathBuf,
+    },
+    /// Derive a topology file from exported OpenTelemetry or Jaeger traces
+    ImportTraces {
+        #[arg(required = true)]
+        files: Vec<PathBuf>,
+        #[arg(long, value_enum, default_value_t = TraceFormat::Auto)]
+        format: TraceFormat,
+        #[arg(long, short)]
+        out: PathBuf,
+    },
+    /// Derive a topology file from Kubernetes manifests and docker-compose files
+    ImportManifests {
+        #[arg(required = true)]
+        files: Vec<PathBuf>,
+        #[arg(long, short)]
+        out: PathBuf,
+    },
+    /// Fit gains and edge weights to observed request rates and report the fit per node
+    Calibrate {
+        /// CSV with a time column and one rps column per service, e.g. `time,api,db`
+        rates: PathBuf,
+        /// Write the `--topology` file with the fitted gains and weights
+        #[arg(long, short)]
+        out: Option<PathBuf>,
+    },
+    /// Run the scenario headless and write per-group metrics for every turn as CSV
+    Record {
+        #[arg(long, short, default_value_t = 100)]
+        turns: usize,
+        /// Throttle a group at a turn, e.g. `Database@40`
+        #[arg(long, value_name = "GROUP@TURN")]
+        throttle: Vec<String>,
+        /// Boost a group at a turn, e.g. `Cache@40`
+        #[arg(long, value_name = "GROUP@TURN")]
+        boost: Vec<String>,
+        #[arg(long, short)]
+        out: PathBuf,
+        /// Also write per-turn SLO attainment, burn rate and budget as CSV
+        #[arg(long)]
+        slo_out: Option<PathBuf>,
+    },
+    /// Drive the scenario with line-delimited JSON requests on stdin, replies on stdout; see the
+    /// README for the protocol
+    Remote {
+        /// Listen on a Unix socket at PATH instead, serving one client after the other
+        #[arg(long, value_name = "PATH")]
+        socket: Option<PathBuf>,
+    },
+    /// Serve the scenario over HTTP on localhost, with a server-sent-events stream of every
+    /// turn; see the README for the endpoints
+    Serve {
+        #[arg(long, short, default_value_t = 8080)]
+        port: u16,
+    },
+    /// Compare a run with scheduled actions against the untouched scenario, or two recorded runs
+    Compare {
+        #[arg(long, short, default_value_t = 100)]
+        turns: usize,
+        /// Throttle a group at a turn, e.g. `Database@40`
+        #[arg(long, value_name = "GROUP@TURN")]
+        throttle: Vec<String>,
+        /// Boost a group at a turn, e.g. `Cache@40`
+        #[arg(long, value_name = "GROUP@TURN")]
+        boost: Vec<String>,
+        /// Compare two files written by `record` instead of running the scenario
+        #[arg(long, num_args = 2, value_names = ["BASELINE", "VARIANT"], conflicts_with_all = ["throttle", "boost"])]
+        files: Option<Vec<PathBuf>>,
+    },
+}
+
+fn schedule(
+    engine: &SimulationEngine,
+    throttle: &[String],
+    boost: &[String],
+) -> io::Result<Schedule> {
+    Schedule::parse(engine.groups(), throttle, boost)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))
+}
+
+/// Noise of game mode, the Monte Carlo defaults.
+fn game_noise(seed: u64) -> Noise {
+    Noise::new(seed)
+        .with_load_jitter(0.1)
+        .with_capacity_noise(0.05)
+}
+
+/// Where `--emit` output goes, stdout without a path.
+fn emit_sink(path: Option<&Path>) -> io::Result<Box<dyn Write>> {
+    Ok(match path {
+        Some(path) => Box::new(BufWriter::new(File::create(path)?)),
+        None => Box::new(io::stdout()),
+    })
+}
+
+/// Appends the current turn of `app` and flushes, so the file can be followed while the TUI runs.
+fn emit_turn(app: &App, sink: &mut impl Write) -> io::Result<()> {
+    write_turn(
+        app.engine.graph(),
+        app.engine.groups(),
+        app.engine.slos(),
+        app.engine.slo_tracks(),
+        app.engine.previous_snapshot(),
+        app.engine.current_snapshot(),
+        sink,
+    )?;
+    sink.flush()
+}
+
+/// Replaces the served exposition with the current turn of `app`.
+fn publish_metrics(app: &App, latest: &Mutex<String>) -> io::Result<()> {
+    let mut exposition = vec![];
+    write_openmetrics(
+        app.engine.graph(),
+        app.engine.groups(),
+        app.engine.slos(),
+        app.engine.slo_tracks(),
+        &[app.engine.current_snapshot()],
+        None,
+        &mut exposition,
+    )?;
+    *latest.lock().unwrap() = String::from_utf8_lossy(&exposition).into_owned();
+    Ok(())
 }
 
-fn main() -> io::Result<()> {
+fn main() -> io::Result<ExitCode> {
     let args = Args::parse();
-    let mut terminal = ratatui::init();
-    let (graph, groups, initial_snapshot, scenario) = match args.scenario {
-        ScenarioKind::Basic => BasicScenario::build(),
-        ScenarioKind::Random => RandomStressScenario::build(12345),
-        ScenarioKind::Stress => StressScenario::build(),
+    let source = match &args.topology {
+        Some(path) => ScenarioSource::File(Topology::read(path)?),
+        None => ScenarioSource::Builtin(args.scenario),
+    };
+    let scenario_name = match &args.topology {
+        Some(path) => path
+            .file_stem()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .into_owned(),
+        None => format!("{:?}", args.scenario).to_lowercase(),
+    };
+    let mut engine = source.engine();
+    for spec in &args.slo {
+        let slo = Slo::parse(spec, engine.graph(), engine.groups())
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+        engine.add_slo(slo);
+    }
+
+    if let Some(command) = args.command {
+        let mut out = io::stdout().lock();
+        let result = match command {
+            Command::Bottlenecks { top } => {
+                print_bottlenecks(&engine, top, &mut out).map(|_| ExitCode::SUCCESS)
+            }
+            Command::BlastRadius { load, top } => {
+                print_blast_radius(&engine, load, top, &mut out).map(|_| ExitCode::SUCCESS)
+            }
+            Command::Cascade { turns } => {
+                print_cascade(&mut engine, turns, &mut out).map(|_| ExitCode::SUCCESS)
+            }
+            Command::Sensitivity {
+                percent,
+                kinds,
+                turns,
+                top,
+            } => print_sensitivity(&source, &kinds, percent, turns, top, &mut out)
+                .map(|_| ExitCode::SUCCESS),
+            Command::MonteCarlo {
+                runs,
+                seed,
+                turns,
+                load_jitter,
+                capacity_noise,
+                failure_rate,
+                outage,
+                by,
+                every,
+            } => {
+                let mc = run_monte_carlo(
+                    |seed| {
+                        source.engine().with_noise(
+                            Noise::new(seed)
+                                .with_load_jitter(load_jitter)
+                                .with_capacity_noise(capacity_noise)
+                                .with_transient_failures(failure_rate, outage),
+                        )
+                    },
+                    (seed..seed + runs).collect(),
+                    turns,
+                );
+                print_monte_carlo(&mc, turns, by.unwrap_or(turns), every, &mut out)
+                    .map(|_| ExitCode::SUCCESS)
+            }
+            Command::Episodes {
+                policy,
+                episodes,
+                seed,
+                turns,
+                load_jitter,
+                capacity_noise,
+            } => {
+                let mut env = Environment::new(
+                    move |seed| {
+                        source.engine().with_noise(
+                            Noise::new(seed)
+                                .with_load_jitter(load_jitter)
+                                .with_capacity_noise(capacity_noise),
+                        )
+                    },
+                    turns,
+                );
+                let policies = if policy.is_empty() {
+                    PolicyKind::value_variants().to_vec()
+                } else {
+                    policy
+                };
+                let seeds = (seed..seed + episodes).collect::<Vec<u64>>();
+                print_episodes(&mut env, &policies, &seeds, &mut out).map(|_| ExitCode::SUCCESS)
+            }
+            Command::Flows { turns, out: path } => {
+                (0..turns).for_each(|_| engine.step());
+                print_flows(&engine, &mut out)?;
+                if let Some(path) = path {
+                    FlowSeries::from_snapshots(
+                        engine.graph(),
+                        engine.groups(),
+                        &engine.snapshots(),
+                    )
+                    .write_csv(&mut File::create(path)?)?;
+                }
+                Ok(ExitCode::SUCCESS)
+            }
+            Command::Goodput { turns, every } => {
+                print_goodput(&mut engine, turns, every, &mut out).map(|_| ExitCode::SUCCESS)
+            }
+            Command::Latency { turns, every } => {
+                print_latency(&mut engine, turns, every, &mut out).map(|_| ExitCode::SUCCESS)
+            }
+            Command::Openmetrics {
+                turns,
+                start,
+                out: path,
+            } => {
+                (0..turns).for_each(|_| engine.step());
+                let snapshots = engine.snapshots();
+                match path {
+                    Some(path) => write_openmetrics(
+                        engine.graph(),
+                        engine.groups(),
+                        engine.slos(),
+                        engine.slo_tracks(),
+                        &snapshots,
+                        Some(start),
+                        &mut File::create(path)?,
+                    )?,
+                    None => write_openmetrics(
+                        engine.graph(),
+                        engine.groups(),
+                        engine.slos(),
+                        engine.slo_tracks(),
+                        &snapshots,
+                        Some(start),
+                        &mut out,
+                    )?,
+                }
+                Ok(ExitCode::SUCCESS)
+            }
+            Command::Diagram {
+                format,
+                turns,
+                at_failure,
+                out: path,
+            } => {
+                let styled = turns.is_some() || at_failure;
+                if styled {
+                    for _ in 0..turns.unwrap_or(100) {
+                        engine.step();
+                        let states = engine.current_snapshot().node_states();
+                        if at_failure && states.iter().any(|s| !s.is_healthy()) {
+                            break;
+                        }
+                    }
+                }
+                let snapshot = styled.then(|| engine.current_snapshot());
+                let mut sink: Box<dyn Write> = match path {
+                    Some(path) => Box::new(File::create(path)?),
+                    None => Box::new(&mut out),
+                };
+                match format {
+                    DiagramFormat::Dot => {
+                        write_dot(engine.graph(), engine.groups(), snapshot, &mut sink)?
+                    }
+                    DiagramFormat::Mermaid => {
+                        write_mermaid(engine.graph(), engine.groups(), snapshot, &mut sink)?
+                    }
+                }
+                Ok(ExitCode::SUCCESS)
+            }
+            Command::Report {
+                turns,
+                throttle,
+                boost,
+                out: path,
+            } => {
+                schedule(&engine, &throttle, &boost)?.run(&mut engine, turns);
+                let mut file = BufWriter::new(File::create(path)?);
+                write_report(
+                    engine.graph(),
+                    engine.groups(),
+                    &engine.snapshots(),
+                    &mut file,
+                )?;
+                file.flush()?;
+                Ok(ExitCode::SUCCESS)
+            }
+            Command::ImportTraces {
+                files,
+                format,
+                out: path,
+            } => {
+                let mut spans = vec![];
+                for file in &files {
+                    let text = std::fs::read_to_string(file)?;
+                    spans.extend(read_spans(&text, format).map_err(|e| {
+                        io::Error::new(
+                            io::ErrorKind::InvalidData,
+                            format!("{}: {}", file.display(), e),
+                        )
+                    })?);
+                }
+                let topology = topology_from_spans(&spans);
+                topology.write(&mut BufWriter::new(File::create(path)?))?;
+                writeln!(
+                    out,
+                    "Imported {} spans from {} files",
+                    spans.len(),
+                    files.len()
+                )?;
+                print_import(&topology, &mut out).map(|_| ExitCode::SUCCESS)
+            }
+            Command::ImportManifests { files, out: path } => {
+                let invalid = |e: String| io::Error::new(io::ErrorKind::InvalidData, e);
+                let mut docs = vec![];
+                for file in &files {
+                    let text = std::fs::read_to_string(file)?;
+                    docs.extend(
+                        yaml_documents(&text)
+                            .map_err(|e| invalid(format!("{}: {}", file.display(), e)))?,
+                    );
+                }
+                let topology = services_from_documents(&docs)
+                    .and_then(|services| topology_from_services(&services))
+                    .map_err(invalid)?;
+                topology.write(&mut BufWriter::new(File::create(path)?))?;
+                print_import(&topology, &mut out).map(|_| ExitCode::SUCCESS)
+            }
+            Command::Calibrate { rates, out: path } => {
+                let topology = match (&source, &path) {
+                    (ScenarioSource::File(topology), _) => Some(topology),
+                    (ScenarioSource::Builtin(_), Some(_)) => {
+                        return Err(io::Error::new(
+                            io::ErrorKind::InvalidInput,
+                            "--out needs --topology",
+                        ));
+                    }
+                    (ScenarioSource::Builtin(_), None) => None,
+                };
+                let rates = RateSeries::read_csv(BufReader::new(File::open(rates)?))?;
+                print_calibration(&mut engine, &rates, &mut out)?;
+                if let (Some(topology), Some(path)) = (topology, path) {
+                    topology
+                        .with_parameters(engine.graph())
+                        .write(&mut BufWriter::new(File::create(path)?))?;
+                }
+                Ok(ExitCode::SUCCESS)
+            }
+            Command::Record {
+                turns,
+                throttle,
+                boost,
+                out: path,
+                slo_out,
+            } => {
+                let schedule = schedule(&engine, &throttle, &boost)?;
+                let metrics = record_run(&mut engine, &schedule, turns);
+                metrics.write_csv(&mut File::create(path)?)?;
+                if let Some(path) = slo_out {
+                    write_slo_csv(&engine, &mut File::create(path)?)?;
+                }
+                Ok(ExitCode::SUCCESS)
+            }
+            Command::Play { policy, seed } => {
+                engine = engine.with_noise(game_noise(seed));
+                let mut game = Game::new(engine.scenario().objectives());
+                let mut player = policy.build(seed);
+                while !game.is_over() {
+                    act(player.as_mut(), &mut engine);
+                    engine.step();
+                    game.update(&engine);
+                }
+                let (scores, rank) = HighScores::record(
+                    &args.scores,
+                    &HighScores::key(&scenario_name, seed),
+                    game.high_score(player.name()),
+                )?;
+                print_game(&game, &scores, rank, &mut out).map(|_| match game.outcome() {
+                    Some(Outcome::Won) => ExitCode::SUCCESS,
+                    _ => ExitCode::FAILURE,
+                })
+            }
+            Command::Remote { socket } => match socket {
+                Some(path) => serve_socket(&mut engine, &path),
+                None => serve_lines(&mut engine, io::stdin().lock(), &mut out),
+            }
+            .map(|_| ExitCode::SUCCESS),
+            Command::Serve { port } => {
+                serve_http(&mut engine, ("127.0.0.1", port), &mut out).map(|_| ExitCode::SUCCESS)
+            }
+            Command::Slo { turns } => print_slos(&mut engine, turns, &mut out).map(|breached| {
+                if breached > 0 {
+                    ExitCode::FAILURE
+                } else {
+                    ExitCode::SUCCESS
+                }
+            }),
+            Command::Compare {
+                turns,
+                throttle,
+                boost,
+                files,
+            } => {
+                let (baseline, variant) = match files.as_deref() {
+                    Some([baseline, variant]) => (read_run(baseline)?, read_run(variant)?),
+                    _ => {
+                        let schedule = schedule(&engine, &throttle, &boost)?;
+                        let baseline = record_run(&mut source.engine(), &Schedule::new(), turns);
+                        (baseline, record_run(&mut engine, &schedule, turns))
+                    }
+                };
+                print_comparison(&baseline, &variant, &mut out).map(|_| ExitCode::SUCCESS)
+            }
+            Command::Cycles => print_feedback_loops(&engine, &mut out).map(|unstable| {
+                if unstable > 0 {
+                    ExitCode::FAILURE
+                } else {
+                    ExitCode::SUCCESS
+                }
+            }),
+            Command::Run { turns } => {
+                (0..turns).for_each(|_| engine.step());
+                Ok(ExitCode::SUCCESS)
+            }
+        };
+        drop(out);
+        if args.emit.is_some() {
+            write_run(
+                engine.graph(),
+                engine.groups(),
+                engine.slos(),
+                engine.slo_tracks(),
+                &engine.snapshots(),
+                &mut emit_sink(args.emit_out.as_deref())?,
+            )?;
+        }
+        return result;
+    }
+
+    let mut emit = match (args.emit, args.emit_out.as_deref()) {
+        (None, _) => None,
+        (Some(_), None) => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "--emit in the TUI needs --emit-out",
+            ));
+        }
+        (Some(_), Some(path)) => Some(emit_sink(Some(path))?),
     };
-    let engine = SimulationEngine::new(graph, groups, initial_snapshot, scenario);
 
-    let mut app = App::new(engine);
+    let latest_metrics = Arc::new(Mutex::new(String::new()));
+    if let Some(addr) = &args.metrics_addr {
+        serve_metrics(addr, latest_metrics.clone())?;
+    }
+
+    let mut terminal = ratatui::init();
+
+    let mut app = if args.game {
+        let engine = engine.with_noise(game_noise(args.seed));
+        let game = Game::new(engine.scenario().objectives());
+        App::new(engine)
+            .with_baseline(source.engine().with_noise(game_noise(args.seed)))
+            .with_game(game)
+    } else {
+        App::new(engine).with_baseline(source.engine())
+    };
+    if let Some(kind) = args.policy {
+        app = app.with_policy(kind.build(0));
+    }
+    if args.metrics_addr.is_some() {
+        publish_metrics(&app, &latest_metrics)?;
+    }
+    if let Some(sink) = emit.as_mut() {
+        emit_turn(&app, sink)?;
+    }
 
     loop {
         let _ = terminal.draw(|frame| draw_app(frame, &app));
@@ -56,8 +798,22 @@ fn main() -> io::Result<()> {
                 Event::Key(key)
                     if key.kind == KeyEventKind::Press && key.code == KeyCode::Char(' ') =>
                 {
-                    app.refresh_groups();
-                    app.engine.step();
+                    app.step();
+                    if let Some(sink) = emit.as_mut() {
+                        emit_turn(&app, sink)?;
+                    }
+                    if let Some(game) = &app.game
+                        && game.is_over()
+                        && app.ranking.is_none()
+                    {
+                        let player = app.policy.as_ref().map_or("human", |p| p.name());
+                        let ranking = HighScores::record(
+                            &args.scores,
+                            &HighScores::key(&scenario_name, args.seed),
+                            game.high_score(player),
+                        )?;
+                        app.finish_game(ranking);
+                    }
                 }
                 Event::Key(key)
                     if key.kind == KeyEventKind::Press && key.code == KeyCode::Char('t') =>
@@ -69,6 +825,33 @@ fn main() -> io::Result<()> {
                 {
                     app.engine.try_boost_group(app.selected_group_id());
                 }
+                Event::Key(key)
+                    if key.kind == KeyEventKind::Press && key.code == KeyCode::Char('c') =>
+                {
+                    app.toggle_view(View::Cascade);
+                }
+                Event::Key(key)
+                    if key.kind == KeyEventKind::Press && key.code == KeyCode::Char('v') =>
+                {
+                    app.toggle_view(View::Compare);
+                }
+                Event::Key(key)
+                    if key.kind == KeyEventKind::Press && key.code == KeyCode::Char('f') =>
+                {
+                    app.toggle_view(View::Flows);
+                }
+                Event::Key(key)
+                    if key.kind == KeyEventKind::Press && key.code == KeyCode::Char('g') =>
+                {
+                    app.toggle_view(View::Topology);
+                }
+                Event::Key(key)
+                    if key.kind == KeyEventKind::Press
+                        && key.code == KeyCode::Char('s')
+                        && app.game.is_some() =>
+                {
+                    app.toggle_view(View::Summary);
+                }
                 Event::Key(key) if key.kind == KeyEventKind::Press && key.code == Up => {
                     app.select_previous_group();
                 }
@@ -77,7 +860,10 @@ fn main() -> io::Result<()> {
                 }
                 _ => continue,
             }
+            if args.metrics_addr.is_some() {
+                publish_metrics(&app, &latest_metrics)?;
+            }
         }
     }
-    Ok(())
+    Ok(ExitCode::SUCCESS)
 }
@@ -0,0 +1,347 @@
+use crate::remote::protocol::{Request, current_turn, envelope, handle};
+use crate::simulation::engine::SimulationEngine;
+use serde::Deserialize;
+use serde_json::{Value, json};
+use std::io;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream, ToSocketAddrs};
+use std::time::Duration;
+
+/// how long a client may take to send its request before it is dropped
+pub const READ_TIMEOUT: Duration = Duration::from_secs(5);
+/// how long an event stream may block before its watcher is dropped
+const WRITE_TIMEOUT: Duration = Duration::from_secs(1);
+/// largest request body read, in bytes
+const MAX_BODY: usize = 256 * 1024;
+
+#[derive(Debug, PartialEq)]
+struct HttpRequest {
+    method: String,
+    /// without any query string
+    path: String,
+    /// lowercase, empty without a `Content-Type` header
+    content_type: String,
+    body: String,
+}
+
+#[derive(Deserialize)]
+struct StepBody {
+    turns: Option<usize>,
+}
+
+/// Reads the request line, the headers and a `Content-Length` body of at most `MAX_BODY` bytes.
+/// The query string is dropped, no endpoint takes one.
+fn read_request(mut reader: impl BufRead) -> io::Result<HttpRequest> {
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    let mut parts = request_line.split_whitespace();
+    let (method, path) = match (parts.next(), parts.next()) {
+        (Some(method), Some(target)) => {
+            let path = target.split_once('?').map_or(target, |(path, _)| path);
+            (method.to_string(), path.to_string())
+        }
+        _ => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("bad request line '{}'", request_line.trim()),
+            ));
+        }
+    };
+
+    let mut length = 0;
+    let mut content_type = String::new();
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header)? == 0 || header.trim().is_empty() {
+            break;
+        }
+        let Some((name, value)) = header.split_once(':') else {
+            continue;
+        };
+        if name.eq_ignore_ascii_case("content-length") {
+            length = value
+                .trim()
+                .parse()
+                .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "bad Content-Length"))?;
+        } else if name.eq_ignore_ascii_case("content-type") {
+            content_type = value.trim().to_ascii_lowercase();
+        }
+    }
+    if length > MAX_BODY {
+        return Err(io::Error::new(
+            io::ErrorKind::FileTooLarge,
+            format!("request body over {} bytes", MAX_BODY),
+        ));
+    }
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body)?;
+    Ok(HttpRequest {
+        method,
+        path,
+        content_type,
+        body: String::from_utf8_lossy(&body).into_owned(),
+    })
+}
+
+/// Nodes, groups, edges and entry nodes of the simulated graph.
+fn topology(engine: &SimulationEngine) -> Value {
+    let graph = engine.graph();
+    let node_name = |id: usize| graph.nodes()[id].name();
+    let groups = engine.groups().groups().iter().map(|g| {
+        json!({
+            "name": g.name(),
+            "nodes": g.nodes().iter().map(|id| {
+                let node = graph.node_by_id(*id);
+                json!({ "name": node.name(), "capacity": node.capacity(), "gain": node.gain() })
+            }).collect::<Vec<_>>(),
+        })
+    });
+    let edges = graph.edges().iter().map(|e| {
+        json!({
+            "from": node_name(e.from().index()),
+            "to": node_name(e.to().index()),
+            "weight": e.weight(),
+        })
+    });
+    let entry = engine
+        .scenario()
+        .entry_nodes()
+        .iter()
+        .map(|id| node_name(id.index()));
+    json!({
+        "groups": groups.collect::<Vec<_>>(),
+        "edges": edges.collect::<Vec<_>>(),
+        "entry": entry.collect::<Vec<_>>(),
+    })
+}
+
+/// Applies each throttle, boost or failure injection in order, one reply each.
+fn act(engine: &mut SimulationEngine, actions: Vec<Value>) -> Value {
+    let replies = actions.into_iter().map(|action| {
+        envelope(
+            serde_json::from_value::<Request>(action)
+                .map_err(|e| e.to_string())
+                .and_then(|request| match request {
+                    Request::Throttle { .. }
+                    | Request::Boost { .. }
+                    | Request::InjectFailure { .. } => handle(engine, request),
+                    _ => Err("only throttle, boost and inject_failure are actions".to_string()),
+                }),
+        )
+    });
+    Value::Array(replies.collect())
+}
+
+/// Status line and JSON body of the reply to `request`, and whether it changed the run.
+fn route(engine: &mut SimulationEngine, request: &HttpRequest) -> (&'static str, Value, bool) {
+    let bad_request = |e: String| ("400 Bad Request", json!({ "error": e }), false);
+    // a browser only sends JSON cross-origin after a preflight, which gets no CORS headers
+    let json = request.content_type.split(';').next() == Some("application/json");
+    match (request.method.as_str(), request.path.as_str()) {
+        ("POST", "/step" | "/actions") if !json => (
+            "415 Unsupported Media Type",
+            json!({ "error": "POST bodies must be sent as application/json" }),
+            false,
+        ),
+        ("GET", "/snapshot") => ("200 OK", current_turn(engine), false),
+        ("GET", "/groups") => ("200 OK", current_turn(engine)["groups"].take(), false),
+        ("GET", "/topology") => ("200 OK", topology(engine), false),
+        ("POST", "/step") => {
+            let turns = match request.body.trim() {
+                "" => Ok(1),
+                body => serde_json::from_str::<StepBody>(body)
+                    .map(|b| b.turns.unwrap_or(1))
+                    .map_err(|e| e.to_string()),
+            };
+            match turns.and_then(|turns| handle(engine, Request::Step { turns })) {
+                Ok(reply) => ("200 OK", reply, true),
+                Err(e) => bad_request(e),
+            }
+        }
+        ("POST", "/actions") => match serde_json::from_str::<Vec<Value>>(&request.body) {
+            Ok(actions) => ("200 OK", act(engine, actions), true),
+            Err(e) => bad_request(e.to_string()),
+        },
+        (_, "/snapshot" | "/groups" | "/topology" | "/step" | "/actions" | "/events") => (
+            "405 Method Not Allowed",
+            json!({ "error": format!("{} {} is not supported", request.method, request.path) }),
+            false,
+        ),
+        _ => (
+            "404 Not Found",
+            json!({ "error": format!("no endpoint at {}", request.path) }),
+            false,
+        ),
+    }
+}
+
+/// Any page may read what `GET`s return, but only same-origin pages may read replies to changes.
+fn write_response(
+    mut stream: &TcpStream,
+    status: &str,
+    body: &Value,
+    cors: bool,
+) -> io::Result<()> {
+    let body = body.to_string();
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\n{}Connection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        if cors {
+            "Access-Control-Allow-Origin: *\r\n"
+        } else {
+            ""
+        },
+        body
+    )
+}
+
+/// Sends `turn` to a server-sent-events client.
+fn write_event(mut stream: &TcpStream, turn: &Value) -> io::Result<()> {
+    write!(stream, "event: turn\ndata: {}\n\n", turn)?;
+    stream.flush()
+}
+
+/// Serves the run over HTTP on `addr` until the process exits, one request after the other:
+/// `GET /snapshot`, `/groups` and `/topology`, `POST /step` and `/actions`, and `GET /events`,
+/// a server-sent-events stream of the current turn, sent again after every change.
+pub fn serve_http(
+    engine: &mut SimulationEngine,
+    addr: impl ToSocketAddrs,
+    out: &mut impl Write,
+) -> io::Result<()> {
+    let listener = TcpListener::bind(addr)?;
+    writeln!(out, "Serving on http://{}", listener.local_addr()?)?;
+    out.flush()?;
+
+    let mut watchers: Vec<TcpStream> = vec![];
+    for stream in listener.incoming().flatten() {
+        let _ = stream.set_read_timeout(Some(READ_TIMEOUT));
+        let request = match read_request(BufReader::new(&stream)) {
+            Ok(request) => request,
+            Err(e) if e.kind() == io::ErrorKind::FileTooLarge => {
+                let body = json!({ "error": e.to_string() });
+                let _ = write_response(&stream, "413 Payload Too Large", &body, false);
+                continue;
+            }
+            // a client that hangs up or sends garbage is not our problem
+            Err(_) => continue,
+        };
+        if request.method == "GET" && request.path == "/events" {
+            let headers = "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nCache-Control: no-cache\r\nAccess-Control-Allow-Origin: *\r\n\r\n";
+            // a watcher that stops reading would block every other client
+            if stream.set_write_timeout(Some(WRITE_TIMEOUT)).is_ok()
+                && (&stream).write_all(headers.as_bytes()).is_ok()
+                && write_event(&stream, &current_turn(engine)).is_ok()
+            {
+                watchers.push(stream);
+            }
+            continue;
+        }
+
+        let (status, body, changed) = route(engine, &request);
+        let _ = write_response(&stream, status, &body, request.method == "GET");
+        if changed {
+            let turn = current_turn(engine);
+            watchers.retain(|watcher| write_event(watcher, &turn).is_ok());
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scenario::topology::Topology;
+
+    /// POSTs as JSON
+    fn request(method: &str, path: &str, body: &str) -> HttpRequest {
+        HttpRequest {
+            method: method.to_string(),
+            path: path.to_string(),
+            content_type: if method == "POST" {
+                "application/json".to_string()
+            } else {
+                String::new()
+            },
+            body: body.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_read_request() {
+        let raw = "POST /step HTTP/1.1\r\nHost: localhost\r\nContent-Type: application/json\r\ncontent-length: 13\r\n\r\n{\"turns\": 2}\n";
+        assert_eq!(
+            request("POST", "/step", "{\"turns\": 2}\n"),
+            read_request(raw.as_bytes()).unwrap()
+        );
+        let raw = "GET /groups?pretty=1 HTTP/1.1\r\n\r\n";
+        assert_eq!(
+            request("GET", "/groups", ""),
+            read_request(raw.as_bytes()).unwrap()
+        );
+        assert!(read_request("\r\n".as_bytes()).is_err());
+        let raw = format!(
+            "POST /actions HTTP/1.1\r\nContent-Length: {}\r\n\r\n",
+            MAX_BODY + 1
+        );
+        assert_eq!(
+            io::ErrorKind::FileTooLarge,
+            read_request(raw.as_bytes()).unwrap_err().kind()
+        );
+    }
+
+    #[test]
+    fn test_routes() {
+        let mut engine = Topology::two_tier(200.0, 100.0, 150.0).engine();
+        let (status, body, changed) = route(&mut engine, &request("POST", "/step", ""));
+        assert_eq!(
+            ("200 OK", json!({"turn": 1}), true),
+            (status, body, changed)
+        );
+        let (_, body, _) = route(&mut engine, &request("POST", "/step", r#"{"turns": 2}"#));
+        assert_eq!(json!({"turn": 3}), body);
+
+        let actions = r#"[{"cmd": "boost", "group": "back"}, {"cmd": "step"}, {"cmd": "throttle", "group": "back"}]"#;
+        let (status, body, _) = route(&mut engine, &request("POST", "/actions", actions));
+        assert_eq!("200 OK", status);
+        assert_eq!(true, body[0]["applied"]);
+        assert_eq!(false, body[1]["ok"]);
+        assert_eq!(false, body[2]["applied"]);
+
+        let (_, body, _) = route(&mut engine, &request("GET", "/snapshot", ""));
+        assert_eq!(3, body["turn"]);
+        assert_eq!(1.5, body["capacity_modifiers"][1]["factor"]);
+        let (_, body, _) = route(&mut engine, &request("GET", "/groups", ""));
+        assert_eq!("back", body[1]["name"]);
+        let (_, body, _) = route(&mut engine, &request("GET", "/topology", ""));
+        assert_eq!(json!(["api"]), body["entry"]);
+        assert_eq!(
+            json!({"from": "api", "to": "db", "weight": 1.0}),
+            body["edges"][0]
+        );
+
+        let status = |method: &str, path: &str, body: &str| {
+            route(
+                &mut Topology::two_tier(200.0, 100.0, 150.0).engine(),
+                &request(method, path, body),
+            )
+            .0
+        };
+        assert_eq!("400 Bad Request", status("POST", "/step", "two"));
+        assert_eq!(
+            "400 Bad Request",
+            status("POST", "/step", r#"{"turns": 1000000}"#)
+        );
+        assert_eq!("400 Bad Request", status("POST", "/actions", "{}"));
+        assert_eq!("405 Method Not Allowed", status("POST", "/snapshot", ""));
+        assert_eq!("404 Not Found", status("GET", "/", ""));
+        let mut form = request("POST", "/step", "");
+        form.content_type = "text/plain".to_string();
+        assert_eq!(
+            "415 Unsupported Media Type",
+            route(&mut Topology::two_tier(200.0, 100.0, 150.0).engine(), &form).0
+        );
+    }
+}
@@ -0,0 +1,255 @@
+use crate::export::jsonl::turn_value;
+use crate::graph::node::NodeId;
+use crate::simulation::action::Action;
+use crate::simulation::engine::SimulationEngine;
+use serde::Deserialize;
+use serde_json::{Map, Value, json};
+use std::io;
+use std::io::{BufRead, BufReader, Write};
+use std::path::Path;
+
+/// most turns one step request may run
+pub const MAX_STEP_TURNS: usize = 10_000;
+
+/// One line of the protocol, e.g. `{"cmd": "throttle", "group": "Database"}`.
+#[derive(Debug, Deserialize, PartialEq)]
+#[serde(tag = "cmd", rename_all = "snake_case")]
+pub enum Request {
+    Step {
+        #[serde(default = "one")]
+        turns: usize,
+    },
+    Throttle {
+        group: String,
+    },
+    Boost {
+        group: String,
+    },
+    /// the current turn as a `--emit jsonl` record
+    Snapshot,
+    /// the group summaries of that record
+    Groups,
+    /// down for `turns` turns, for good without
+    InjectFailure {
+        node: String,
+        turns: Option<usize>,
+    },
+    Rewind {
+        #[serde(default = "one")]
+        turns: usize,
+    },
+}
+
+fn one() -> usize {
+    1
+}
+
+fn group_id(engine: &SimulationEngine, name: &str) -> Result<usize, String> {
+    engine
+        .groups()
+        .group_by_name(name)
+        .ok_or_else(|| format!("unknown group {}", name))
+}
+
+fn node_id(engine: &SimulationEngine, name: &str) -> Result<NodeId, String> {
+    engine
+        .graph()
+        .nodes()
+        .iter()
+        .find(|n| n.name() == name)
+        .map(|n| *n.id())
+        .ok_or_else(|| format!("unknown node {}", name))
+}
+
+fn apply(engine: &mut SimulationEngine, action: Action) -> Value {
+    let ops = engine.remaining_ops();
+    engine.apply(action);
+    json!({
+        "applied": engine.remaining_ops() < ops,
+        "remaining_ops": engine.remaining_ops(),
+    })
+}
+
+/// Runs `request` against `engine` and returns the fields of its reply.
+pub fn handle(engine: &mut SimulationEngine, request: Request) -> Result<Value, String> {
+    Ok(match request {
+        Request::Step { turns } if turns > MAX_STEP_TURNS => {
+            return Err(format!("at most {} turns per step", MAX_STEP_TURNS));
+        }
+        Request::Step { turns } => {
+            (0..turns).for_each(|_| engine.step());
+            json!({ "turn": engine.current_snapshot().turn() })
+        }
+        Request::Throttle { group } => {
+            let group_id = group_id(engine, &group)?;
+            apply(engine, Action::Throttle(group_id))
+        }
+        Request::Boost { group } => {
+            let group_id = group_id(engine, &group)?;
+            apply(engine, Action::Boost(group_id))
+        }
+        Request::Snapshot => json!({ "snapshot": current_turn(engine) }),
+        Request::Groups => json!({ "groups": current_turn(engine)["groups"].take() }),
+        Request::InjectFailure { node, turns } => {
+            let node_id = node_id(engine, &node)?;
+            engine.inject_failure(node_id, turns);
+            json!({ "turn": engine.current_snapshot().turn() })
+        }
+        Request::Rewind { turns } => {
+            let rewound = engine.rewind(turns);
+            json!({ "rewound": rewound, "turn": engine.current_snapshot().turn() })
+        }
+    })
+}
+
+/// the current turn as a `--emit jsonl` record
+pub fn current_turn(engine: &SimulationEngine) -> Value {
+    turn_value(
+        engine.graph(),
+        engine.groups(),
+        engine.slos(),
+        engine.slo_tracks(),
+        engine.previous_snapshot(),
+        engine.current_snapshot(),
+    )
+}
+
+/// Parses and runs one request line.
+pub fn reply(engine: &mut SimulationEngine, line: &str) -> Value {
+    envelope(
+        serde_json::from_str::<Request>(line)
+            .map_err(|e| e.to_string())
+            .and_then(|request| handle(engine, request)),
+    )
+}
+
+/// The reply to a request, `"ok": true` and its fields or `"ok": false` and an `error`.
+pub fn envelope(result: Result<Value, String>) -> Value {
+    let mut reply = Map::new();
+    match result {
+        Ok(fields) => {
+            reply.insert("ok".to_string(), Value::Bool(true));
+            if let Value::Object(fields) = fields {
+                reply.extend(fields);
+            }
+        }
+        Err(e) => {
+            reply.insert("ok".to_string(), Value::Bool(false));
+            reply.insert("error".to_string(), Value::String(e));
+        }
+    }
+    Value::Object(reply)
+}
+
+/// Answers every line of `input` with one line on `out` until `input` ends. Blank lines are
+/// skipped.
+pub fn serve_lines(
+    engine: &mut SimulationEngine,
+    input: impl BufRead,
+    out: &mut impl Write,
+) -> io::Result<()> {
+    for line in input.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        serde_json::to_writer(&mut *out, &reply(engine, &line))?;
+        writeln!(out)?;
+        out.flush()?;
+    }
+    Ok(())
+}
+
+/// Serves one client after the other on a Unix socket at `path` until the process exits, all
+/// driving the same run.
+#[cfg(unix)]
+pub fn serve_socket(engine: &mut SimulationEngine, path: &Path) -> io::Result<()> {
+    use std::os::unix::net::UnixListener;
+
+    let listener = UnixListener::bind(path)?;
+    for stream in listener.incoming().flatten() {
+        // a client that hangs up early is not our problem
+        let _ = serve_lines(engine, BufReader::new(&stream), &mut &stream);
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+pub fn serve_socket(_engine: &mut SimulationEngine, _path: &Path) -> io::Result<()> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "Unix sockets are not available on this platform",
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scenario::topology::Topology;
+
+    #[test]
+    fn test_parse_requests() {
+        let parse = |line: &str| serde_json::from_str::<Request>(line).unwrap();
+        assert_eq!(Request::Step { turns: 1 }, parse(r#"{"cmd": "step"}"#));
+        assert_eq!(
+            Request::InjectFailure {
+                node: "db".to_string(),
+                turns: Some(3)
+            },
+            parse(r#"{"cmd": "inject_failure", "node": "db", "turns": 3}"#)
+        );
+        assert_eq!(Request::Groups, parse(r#"{"cmd": "groups"}"#));
+        assert!(serde_json::from_str::<Request>(r#"{"cmd": "restart"}"#).is_err());
+    }
+
+    #[test]
+    fn test_session() {
+        let mut engine = Topology::two_tier(200.0, 100.0, 150.0).engine();
+        let input = [
+            r#"{"cmd": "step", "turns": 3}"#,
+            r#"{"cmd": "boost", "group": "back"}"#,
+            r#"{"cmd": "throttle", "group": "front"}"#,
+            "",
+            r#"{"cmd": "throttle", "group": "middle"}"#,
+            r#"{"cmd": "inject_failure", "node": "db"}"#,
+            r#"{"cmd": "step"}"#,
+            r#"{"cmd": "groups"}"#,
+            r#"{"cmd": "rewind", "turns": 10}"#,
+            r#"{"cmd": "snapshot"}"#,
+            "not json",
+            r#"{"cmd": "step", "turns": 1000000}"#,
+        ]
+        .join("\n");
+        let mut out = vec![];
+        serve_lines(&mut engine, input.as_bytes(), &mut out).unwrap();
+        let replies = String::from_utf8(out)
+            .unwrap()
+            .lines()
+            .map(|l| serde_json::from_str::<Value>(l).unwrap())
+            .collect::<Vec<_>>();
+
+        assert_eq!(11, replies.len());
+        assert_eq!(json!({"ok": true, "turn": 3}), replies[0]);
+        assert_eq!(
+            json!({"ok": true, "applied": true, "remaining_ops": 0}),
+            replies[1]
+        );
+        assert_eq!(
+            json!({"ok": true, "applied": false, "remaining_ops": 0}),
+            replies[2]
+        );
+        assert_eq!(
+            json!({"ok": false, "error": "unknown group middle"}),
+            replies[3]
+        );
+        assert_eq!(json!({"ok": true, "turn": 3}), replies[4]);
+        assert_eq!("failed", replies[6]["groups"][1]["status"]);
+        assert_eq!(json!({"ok": true, "rewound": 4, "turn": 0}), replies[7]);
+        assert_eq!(0, replies[8]["snapshot"]["turn"]);
+        assert_eq!(false, replies[9]["ok"]);
+        assert_eq!(
+            json!({"ok": false, "error": "at most 10000 turns per step"}),
+            replies[10]
+        );
+    }
+}
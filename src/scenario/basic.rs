@@ -1,4 +1,5 @@
 use crate::analysis::groups::{Group, GroupSet};
+use crate::analysis::slo::{Indicator, Slo, SloScope};
 use crate::graph::edge::{Edge, EdgeId};
 use crate::graph::graph::Graph;
 use crate::graph::node::{Node, NodeId};
@@ -18,18 +19,32 @@ pub struct BasicScenario {
 impl BasicScenario {
     pub fn build() -> (Graph, GroupSet, Snapshot, Box<dyn Scenario>) {
         let nodes = vec![
-            Node::new(NodeId(0), "api-1".into(), 200.0, 1.8),
-            Node::new(NodeId(1), "api-2".into(), 200.0, 1.6),
-            Node::new(NodeId(2), "auth".into(), 80.0, 1.0),
-            Node::new(NodeId(3), "orders-1".into(), 100.0, 1.2),
-            Node::new(NodeId(4), "orders-2".into(), 100.0, 1.2),
-            Node::new(NodeId(5), "cache-1".into(), 300.0, 0.7),
-            Node::new(NodeId(6), "cache-2".into(), 300.0, 0.7),
-            Node::new(NodeId(7), "cache-3".into(), 300.0, 0.7),
-            Node::new(NodeId(8), "cache-4".into(), 300.0, 0.7),
-            Node::new(NodeId(9), "db-1".into(), 60.0, 0.0),
-            Node::new(NodeId(10), "db-2".into(), 60.0, 0.0),
-            Node::new(NodeId(11), "db-3".into(), 60.0, 0.0),
+            Node::new(NodeId(0), "api-1".into(), 200.0, 1.8).with_service_time(4.0),
+            Node::new(NodeId(1), "api-2".into(), 200.0, 1.6).with_service_time(4.0),
+            Node::new(NodeId(2), "auth".into(), 80.0, 1.0).with_service_time(12.0),
+            Node::new(NodeId(3), "orders-1".into(), 100.0, 1.2).with_service_time(15.0),
+            Node::new(NodeId(4), "orders-2".into(), 100.0, 1.2).with_service_time(15.0),
+            Node::new(NodeId(5), "cache-1".into(), 300.0, 0.7)
+                .with_service_time(1.0)
+                .with_cost(0.005),
+            Node::new(NodeId(6), "cache-2".into(), 300.0, 0.7)
+                .with_service_time(1.0)
+                .with_cost(0.005),
+            Node::new(NodeId(7), "cache-3".into(), 300.0, 0.7)
+                .with_service_time(1.0)
+                .with_cost(0.005),
+            Node::new(NodeId(8), "cache-4".into(), 300.0, 0.7)
+                .with_service_time(1.0)
+                .with_cost(0.005),
+            Node::new(NodeId(9), "db-1".into(), 60.0, 0.0)
+                .with_service_time(25.0)
+                .with_cost(0.05),
+            Node::new(NodeId(10), "db-2".into(), 60.0, 0.0)
+                .with_service_time(25.0)
+                .with_cost(0.05),
+            Node::new(NodeId(11), "db-3".into(), 60.0, 0.0)
+                .with_service_time(25.0)
+                .with_cost(0.05),
         ];
 
         let mut edges = Vec::new();
@@ -55,21 +70,21 @@ impl BasicScenario {
         }
 
         for cache in [5, 6, 7, 8] {
-            edges.push(Edge::new(EdgeId(eid), NodeId(cache), NodeId(9), 1.0));
-            eid += 1;
-            edges.push(Edge::new(EdgeId(eid), NodeId(cache), NodeId(10), 1.0));
-            eid += 1;
-            edges.push(Edge::new(EdgeId(eid), NodeId(cache), NodeId(11), 0.8));
-            eid += 1;
+            for (db, weight) in [(9, 1.0), (10, 1.0), (11, 0.8)] {
+                edges.push(
+                    Edge::new(EdgeId(eid), NodeId(cache), NodeId(db), weight).with_latency(2.0),
+                );
+                eid += 1;
+            }
         }
 
         for orders in [3, 4] {
-            edges.push(Edge::new(EdgeId(eid), NodeId(orders), NodeId(9), 1.0));
-            eid += 1;
-            edges.push(Edge::new(EdgeId(eid), NodeId(orders), NodeId(10), 1.0));
-            eid += 1;
-            edges.push(Edge::new(EdgeId(eid), NodeId(orders), NodeId(11), 1.0));
-            eid += 1;
+            for db in [9, 10, 11] {
+                edges.push(
+                    Edge::new(EdgeId(eid), NodeId(orders), NodeId(db), 1.0).with_latency(2.0),
+                );
+                eid += 1;
+            }
         }
 
         let graph = Graph::new(nodes, edges);
@@ -129,4 +144,11 @@ impl Scenario for BasicScenario {
     fn ops_per_turn(&self) -> u8 {
         1
     }
+
+    fn slos(&self) -> Vec<Slo> {
+        vec![
+            Slo::new(SloScope::Group(0), Indicator::Availability, 0.995, 20),
+            Slo::new(SloScope::Group(4), Indicator::BacklogBelow(50.0), 0.99, 20),
+        ]
+    }
 }
@@ -0,0 +1,41 @@
+use crate::scenario::basic::BasicScenario;
+use crate::scenario::random::RandomStressScenario;
+use crate::scenario::stress::StressScenario;
+use crate::scenario::topology::Topology;
+use crate::simulation::engine::SimulationEngine;
+use clap::ValueEnum;
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+#[value(rename_all = "lowercase")]
+pub enum ScenarioKind {
+    Basic,
+    Random,
+    Stress,
+}
+
+impl ScenarioKind {
+    pub fn engine(self) -> SimulationEngine {
+        let (graph, groups, initial_snapshot, scenario) = match self {
+            ScenarioKind::Basic => BasicScenario::build(),
+            ScenarioKind::Random => RandomStressScenario::build(12345),
+            ScenarioKind::Stress => StressScenario::build(),
+        };
+        SimulationEngine::new(graph, groups, initial_snapshot, scenario)
+    }
+}
+
+/// Where engines come from, a built-in scenario or a topology file.
+pub enum ScenarioSource {
+    Builtin(ScenarioKind),
+    File(Topology),
+}
+
+impl ScenarioSource {
+    /// a fresh engine at turn 0
+    pub fn engine(&self) -> SimulationEngine {
+        match self {
+            ScenarioSource::Builtin(kind) => kind.engine(),
+            ScenarioSource::File(topology) => topology.engine(),
+        }
+    }
+}
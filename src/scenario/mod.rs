@@ -1,4 +1,6 @@
 pub mod basic;
+pub mod kind;
 pub mod random;
 pub mod scenario;
 pub mod stress;
+pub mod topology;
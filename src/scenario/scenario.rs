@@ -1,7 +1,52 @@
+use crate::analysis::slo::Slo;
 use crate::graph::node::NodeId;
+use crate::simulation::game::Objectives;
 
 pub trait Scenario {
     fn load(&self, node_id: NodeId, turn: usize) -> f64;
     fn entry_nodes(&self) -> &[NodeId];
     fn ops_per_turn(&self) -> u8;
+
+    /// objectives the scenario is expected to hold
+    fn slos(&self) -> Vec<Slo> {
+        vec![]
+    }
+
+    /// what it takes to win the scenario in game mode
+    fn objectives(&self) -> Objectives {
+        Objectives::default()
+    }
+}
+
+/// Loads `loads[turn]` into every entry node, holding the last load after the list ends.
+#[cfg(test)]
+pub struct TestScenario {
+    entry: Vec<NodeId>,
+    loads: Vec<f64>,
+}
+
+#[cfg(test)]
+impl TestScenario {
+    pub fn new(entry: Vec<NodeId>, loads: Vec<f64>) -> Self {
+        Self { entry, loads }
+    }
+}
+
+#[cfg(test)]
+impl Scenario for TestScenario {
+    fn load(&self, node_id: NodeId, turn: usize) -> f64 {
+        if self.entry.contains(&node_id) {
+            self.loads[turn.min(self.loads.len() - 1)]
+        } else {
+            0.0
+        }
+    }
+
+    fn entry_nodes(&self) -> &[NodeId] {
+        &self.entry
+    }
+
+    fn ops_per_turn(&self) -> u8 {
+        1
+    }
 }
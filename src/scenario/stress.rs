@@ -3,6 +3,7 @@ use crate::graph::edge::{Edge, EdgeId};
 use crate::graph::graph::Graph;
 use crate::graph::node::{Node, NodeId};
 use crate::scenario::scenario::Scenario;
+use crate::simulation::game::Objectives;
 use crate::simulation::modifiers::CapacityModifier;
 use crate::state::edge_state::EdgeState;
 use crate::state::node_state::NodeState;
@@ -204,4 +205,12 @@ impl Scenario for StressScenario {
     fn ops_per_turn(&self) -> u8 {
         1
     }
+
+    /// plenty of capacity, but the load spikes have to be absorbed
+    fn objectives(&self) -> Objectives {
+        Objectives {
+            survive_turns: 100,
+            min_goodput: Some(0.9),
+        }
+    }
 }
@@ -0,0 +1,266 @@
+use crate::analysis::groups::{Group, GroupSet};
+use crate::graph::edge::{Edge, EdgeId};
+use crate::graph::graph::Graph;
+use crate::graph::node::{Node, NodeId};
+use crate::scenario::scenario::Scenario;
+use crate::simulation::engine::SimulationEngine;
+use crate::simulation::modifiers::CapacityModifier;
+use crate::state::edge_state::EdgeState;
+use crate::state::node_state::NodeState;
+use crate::state::snapshot::Snapshot;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io;
+use std::io::{BufReader, Write};
+use std::path::Path;
+
+/// capacity of a node whose capacity is not filled in yet
+pub const DEFAULT_CAPACITY: f64 = 100.0;
+
+fn default_ops_per_turn() -> u8 {
+    1
+}
+
+/// A topology file: groups of nodes, weighted edges between them by name and constant entry
+/// loads. Importers write these with the capacities left empty for a person to fill in.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Topology {
+    pub groups: Vec<GroupSpec>,
+    pub edges: Vec<EdgeSpec>,
+    pub entry: Vec<EntrySpec>,
+    #[serde(default = "default_ops_per_turn")]
+    pub ops_per_turn: u8,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct GroupSpec {
+    pub name: String,
+    pub nodes: Vec<NodeSpec>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct NodeSpec {
+    pub name: String,
+    /// rps, `DEFAULT_CAPACITY` when empty
+    pub capacity: Option<f64>,
+    #[serde(default)]
+    pub gain: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub service_time: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cost: Option<f64>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct EdgeSpec {
+    pub from: String,
+    pub to: String,
+    pub weight: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub latency: Option<f64>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct EntrySpec {
+    pub node: String,
+    /// constant load in rps
+    pub load: f64,
+}
+
+impl Topology {
+    /// Reads and checks a topology file.
+    pub fn read(path: &Path) -> io::Result<Self> {
+        let context = |e: String| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{}: {}", path.display(), e),
+            )
+        };
+        let file = File::open(path)
+            .map_err(|e| io::Error::new(e.kind(), format!("{}: {}", path.display(), e)))?;
+        let topology: Self =
+            serde_json::from_reader(BufReader::new(file)).map_err(|e| context(e.to_string()))?;
+        topology.build().map_err(context)?;
+        Ok(topology)
+    }
+
+    pub fn write(&self, out: &mut impl Write) -> io::Result<()> {
+        serde_json::to_writer_pretty(&mut *out, self)?;
+        writeln!(out)
+    }
+
+    /// names of the nodes whose capacity is not filled in
+    pub fn unset_capacities(&self) -> Vec<&str> {
+        self.groups
+            .iter()
+            .flat_map(|g| &g.nodes)
+            .filter(|n| n.capacity.is_none())
+            .map(|n| n.name.as_str())
+            .collect()
+    }
+
+    /// This topology with the gains and edge weights of `graph`, which must have been built
+    /// from it, rounded to 3 decimals.
+    pub fn with_parameters(&self, graph: &Graph) -> Self {
+        let round = |v: f64| (v * 1000.0).round() / 1000.0;
+        let mut topology = self.clone();
+        topology
+            .groups
+            .iter_mut()
+            .flat_map(|g| &mut g.nodes)
+            .zip(graph.nodes())
+            .for_each(|(spec, node)| spec.gain = round(node.gain()));
+        topology
+            .edges
+            .iter_mut()
+            .zip(graph.edges())
+            .for_each(|(spec, edge)| spec.weight = round(edge.weight()));
+        topology
+    }
+
+    pub fn build(&self) -> Result<(Graph, GroupSet, Snapshot, Box<dyn Scenario>), String> {
+        let mut ids = HashMap::new();
+        let mut nodes = vec![];
+        let mut groups = vec![];
+        for group in &self.groups {
+            let mut members = vec![];
+            for spec in &group.nodes {
+                let id = NodeId(nodes.len());
+                if ids.insert(spec.name.as_str(), id).is_some() {
+                    return Err(format!("duplicate node '{}'", spec.name));
+                }
+                let capacity = spec.capacity.unwrap_or(DEFAULT_CAPACITY);
+                if capacity <= 0.0 || spec.gain < 0.0 {
+                    return Err(format!(
+                        "node '{}' needs a capacity > 0 and a gain >= 0",
+                        spec.name
+                    ));
+                }
+                let mut node = Node::new(id, spec.name.clone(), capacity, spec.gain);
+                if let Some(ms) = spec.service_time {
+                    node = node.with_service_time(ms);
+                }
+                if let Some(cost) = spec.cost {
+                    node = node.with_cost(cost);
+                }
+                nodes.push(node);
+                members.push(id);
+            }
+            groups.push(Group::new(group.name.clone(), members));
+        }
+        let node_id = |name: &str| {
+            ids.get(name)
+                .copied()
+                .ok_or_else(|| format!("unknown node '{}'", name))
+        };
+
+        let mut edges = vec![];
+        for spec in &self.edges {
+            if spec.weight < 0.0 {
+                return Err(format!(
+                    "edge {} → {} needs a weight >= 0",
+                    spec.from, spec.to
+                ));
+            }
+            let mut edge = Edge::new(
+                EdgeId(edges.len()),
+                node_id(&spec.from)?,
+                node_id(&spec.to)?,
+                spec.weight,
+            );
+            if let Some(ms) = spec.latency {
+                edge = edge.with_latency(ms);
+            }
+            edges.push(edge);
+        }
+
+        let mut entry = vec![];
+        let mut loads = vec![0.0; nodes.len()];
+        for spec in &self.entry {
+            let id = node_id(&spec.node)?;
+            if !entry.contains(&id) {
+                entry.push(id);
+            }
+            loads[id.index()] += spec.load;
+        }
+
+        let graph = Graph::new(nodes, edges);
+        let groups = GroupSet::new(groups);
+        let snapshot = Snapshot::new(
+            0,
+            vec![NodeState::new(0.0, 0.0, 0.0, 1.0); graph.node_count()],
+            vec![EdgeState::new(true); graph.edges().len()],
+            vec![CapacityModifier::new(); groups.groups().len()],
+        );
+        let scenario = TopologyScenario {
+            entry,
+            loads,
+            ops_per_turn: self.ops_per_turn,
+        };
+        Ok((graph, groups, snapshot, Box::new(scenario)))
+    }
+
+    pub fn engine(&self) -> SimulationEngine {
+        let (graph, groups, snapshot, scenario) =
+            self.build().expect("topology is checked when read");
+        SimulationEngine::new(graph, groups, snapshot, scenario)
+    }
+
+    /// `load` rps into an api of `api` rps in group front, calling a db of `db` rps in group
+    /// back.
+    #[cfg(test)]
+    pub fn two_tier(api: f64, db: f64, load: f64) -> Self {
+        let node = |name: &str, capacity, gain| NodeSpec {
+            name: name.to_string(),
+            capacity: Some(capacity),
+            gain,
+            service_time: None,
+            cost: None,
+        };
+        Self {
+            groups: vec![
+                GroupSpec {
+                    name: "front".to_string(),
+                    nodes: vec![node("api", api, 1.0)],
+                },
+                GroupSpec {
+                    name: "back".to_string(),
+                    nodes: vec![node("db", db, 0.0)],
+                },
+            ],
+            edges: vec![EdgeSpec {
+                from: "api".to_string(),
+                to: "db".to_string(),
+                weight: 1.0,
+                latency: None,
+            }],
+            entry: vec![EntrySpec {
+                node: "api".to_string(),
+                load,
+            }],
+            ops_per_turn: 1,
+        }
+    }
+}
+
+/// Constant entry load from a topology file.
+pub struct TopologyScenario {
+    entry: Vec<NodeId>,
+    loads: Vec<f64>,
+    ops_per_turn: u8,
+}
+
+impl Scenario for TopologyScenario {
+    fn load(&self, node_id: NodeId, _turn: usize) -> f64 {
+        self.loads[node_id.index()]
+    }
+
+    fn entry_nodes(&self) -> &[NodeId] {
+        &self.entry
+    }
+
+    fn ops_per_turn(&self) -> u8 {
+        self.ops_per_turn
+    }
+}
@@ -0,0 +1,15 @@
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Action {
+    Throttle(usize),
+    Boost(usize),
+}
+
+impl Action {
+    /// every throttle then every boost, in group order
+    pub fn all(group_count: usize) -> Vec<Action> {
+        (0..group_count)
+            .map(Action::Throttle)
+            .chain((0..group_count).map(Action::Boost))
+            .collect()
+    }
+}
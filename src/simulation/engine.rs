@@ -1,17 +1,31 @@
 use crate::analysis::groups::GroupSet;
+use crate::analysis::slo::{Slo, SloTrack, track_slo};
 use crate::graph::graph::Graph;
 use crate::graph::node::NodeId;
 use crate::scenario::scenario::Scenario;
+use crate::simulation::action::Action;
+use crate::simulation::modifiers::CapacityModifier;
+use crate::simulation::noise::Noise;
 use crate::state::snapshot::Snapshot;
 use std::mem;
 
 pub struct SimulationEngine {
     graph: Graph,
     groups: GroupSet,
-    previous_snapshot: Option<Snapshot>,
+    /// every turn before the current one as it was played, with the ops spent before it
+    history: Vec<(Snapshot, usize)>,
     current_snapshot: Snapshot,
+    /// capacity modifiers the current turn was computed with, before this turn's actions
+    played_mods: Vec<CapacityModifier>,
+    /// ops spent before this turn's actions
+    played_ops_spent: usize,
     scenario: Box<dyn Scenario>,
     remaining_ops: u8,
+    ops_spent: usize,
+    noise: Option<Noise>,
+    slos: Vec<Slo>,
+    /// indexed like `slos`, one turn pushed per step
+    slo_tracks: Vec<SloTrack>,
 }
 
 impl SimulationEngine {
@@ -22,20 +36,42 @@ impl SimulationEngine {
         scenario: Box<dyn Scenario>,
     ) -> Self {
         let remaining_ops = scenario.ops_per_turn();
+        let slos = scenario.slos();
+        let slo_tracks = slos
+            .iter()
+            .map(|slo| track_slo(slo, &groups, &[&initial_snapshot]))
+            .collect();
+        let played_mods = initial_snapshot.capacity_mods().clone();
         Self {
             graph,
             groups,
-            previous_snapshot: None,
+            history: Vec::new(),
             current_snapshot: initial_snapshot,
+            played_mods,
+            played_ops_spent: 0,
             scenario,
             remaining_ops,
+            ops_spent: 0,
+            noise: None,
+            slos,
+            slo_tracks,
         }
     }
 
+    /// Makes the run stochastic. Without noise the engine is fully deterministic.
+    pub fn with_noise(mut self, noise: Noise) -> Self {
+        self.noise = Some(noise);
+        self
+    }
+
     pub fn graph(&self) -> &Graph {
         &self.graph
     }
 
+    pub fn graph_mut(&mut self) -> &mut Graph {
+        &mut self.graph
+    }
+
     pub fn groups(&self) -> &GroupSet {
         &self.groups
     }
@@ -44,21 +80,78 @@ impl SimulationEngine {
         &self.scenario
     }
 
+    /// the scenario's SLOs followed by any added ones
+    pub fn slos(&self) -> &[Slo] {
+        &self.slos
+    }
+
+    pub fn add_slo(&mut self, slo: Slo) {
+        self.slo_tracks
+            .push(track_slo(&slo, &self.groups, &self.snapshots()));
+        self.slos.push(slo);
+    }
+
+    /// attainment, burn rate and budget of every SLO over the run, indexed like `slos`
+    pub fn slo_tracks(&self) -> &[SloTrack] {
+        &self.slo_tracks
+    }
+
     pub fn remaining_ops(&self) -> u8 {
         self.remaining_ops
     }
 
+    /// throttles and boosts applied over the whole run
+    pub fn ops_spent(&self) -> usize {
+        self.ops_spent
+    }
+
     pub fn step(&mut self) {
-        self.current_snapshot.tick();
-        let node_states = self.current_snapshot.node_states();
-        let edge_states = self.current_snapshot.edge_states();
+        let mut snapshot = self.current_snapshot.clone();
+        snapshot.tick();
+        let mut noise = self.noise.take();
+        let next_snapshot = self.next_snapshot(&snapshot, noise.as_mut());
+        self.noise = noise;
+
+        // an action shows in the turn it first acts on, not in the one it was taken in, so
+        // every turn in the history carries the modifiers its load was computed with
+        let next_mods = next_snapshot.capacity_mods().clone();
+        let mut old_snapshot = mem::replace(&mut self.current_snapshot, next_snapshot);
+        old_snapshot.set_capacity_mods(mem::replace(&mut self.played_mods, next_mods));
+        self.history.push((old_snapshot, self.played_ops_spent));
+        self.played_ops_spent = self.ops_spent;
+        self.remaining_ops = self.scenario.ops_per_turn();
+        self.slo_tracks
+            .iter_mut()
+            .zip(&self.slos)
+            .for_each(|(track, slo)| track.push(slo, &self.groups, &self.current_snapshot));
+    }
+
+    /// The snapshot of the next turn if `actions` were applied now, without noise and without
+    /// touching the engine, e.g. to look one turn ahead before acting.
+    pub fn preview(&self, actions: &[Action]) -> Snapshot {
+        let mut snapshot = self.current_snapshot.clone();
+        let mut remaining_ops = self.remaining_ops;
+        for action in actions {
+            let (group_id, factor) = modifier(*action);
+            if remaining_ops > 0 && snapshot.update_capacity(group_id, factor) {
+                remaining_ops -= 1;
+            }
+        }
+        snapshot.tick();
+        self.next_snapshot(&snapshot, None)
+    }
+
+    /// Propagates load through `snapshot`, whose modifiers have already ticked.
+    fn next_snapshot(&self, snapshot: &Snapshot, mut noise: Option<&mut Noise>) -> Snapshot {
+        let node_states = snapshot.node_states();
+        let edge_states = snapshot.edge_states();
         let mut prop = vec![0.0; self.graph.node_count()];
 
         node_states
             .iter()
             .enumerate()
             .map(|(n_id, _)| self.graph.node_by_id(NodeId(n_id)))
-            .filter(|n| node_states[n.id().index()].is_healthy())
+            .filter(|n| node_states[n.id().index()].is_up())
             .for_each(|n| {
                 self.graph
                     .outgoing(*n.id())
@@ -66,12 +159,16 @@ impl SimulationEngine {
                     .map(|e_id| self.graph.edge_by_id(*e_id))
                     .for_each(|e| {
                         let t_id = e.to().index();
-                        prop[t_id] += self.current_snapshot.edge_load(e.id(), self.graph());
+                        prop[t_id] += snapshot.edge_load(e.id(), self.graph());
                     })
             });
 
         self.scenario.entry_nodes().iter().for_each(|id| {
-            prop[id.index()] += self.scenario.load(*id, self.current_snapshot.turn())
+            let load = self.scenario.load(*id, snapshot.turn());
+            prop[id.index()] += match noise.as_deref_mut() {
+                Some(noise) => noise.jitter_load(load),
+                None => load,
+            };
         });
 
         let mut new_node_states = node_states.clone();
@@ -83,11 +180,26 @@ impl SimulationEngine {
                 return;
             }
 
-            let throttle = self
-                .current_snapshot
+            let mut outage = n.outage().saturating_sub(1);
+            if outage == 0
+                && let Some(noise) = noise.as_deref_mut()
+            {
+                outage = noise.draw_outage();
+            }
+            n.set_outage(outage);
+            if outage > 0 {
+                n.set_served(0.0);
+                n.set_backlog(0.0);
+                return;
+            }
+
+            let throttle = snapshot
                 .capacity_mod(self.groups.group_by_node_id(i))
                 .factor();
-            let capacity = self.graph.node_by_id(NodeId(i)).capacity() * throttle;
+            let mut capacity = self.graph.node_by_id(NodeId(i)).capacity() * throttle;
+            if let Some(noise) = noise.as_deref_mut() {
+                capacity = noise.perturb_capacity(capacity);
+            }
             let outgoing_edges = self.graph.outgoing(NodeId(i));
             let total = prop[i] + n.backlog();
 
@@ -117,17 +229,10 @@ impl SimulationEngine {
             }
         });
 
-        let turn = self.current_snapshot.turn() + 1;
+        let turn = snapshot.turn() + 1;
         let new_edge_states = edge_states.clone();
-        let new_capacity_mods = self.current_snapshot.capacity_mods().clone();
-
-        let old_snapshot = mem::replace(
-            &mut self.current_snapshot,
-            Snapshot::new(turn, new_node_states, new_edge_states, new_capacity_mods),
-        );
-
-        self.previous_snapshot = Some(old_snapshot);
-        self.remaining_ops = self.scenario.ops_per_turn();
+        let new_capacity_mods = snapshot.capacity_mods().clone();
+        Snapshot::new(turn, new_node_states, new_edge_states, new_capacity_mods)
     }
 
     pub fn current_snapshot(&self) -> &Snapshot {
@@ -135,66 +240,97 @@ impl SimulationEngine {
     }
 
     pub fn previous_snapshot(&self) -> &Snapshot {
-        self.previous_snapshot
-            .as_ref()
-            .unwrap_or(&self.current_snapshot)
+        self.history
+            .last()
+            .map_or(&self.current_snapshot, |(snapshot, _)| snapshot)
     }
 
-    fn try_capacity_modifier(&mut self, group_id: usize, factor: f64) {
+    /// every snapshot of the run so far, oldest first, ending with the current one
+    pub fn snapshots(&self) -> Vec<&Snapshot> {
+        self.history
+            .iter()
+            .map(|(snapshot, _)| snapshot)
+            .chain(std::iter::once(&self.current_snapshot))
+            .collect()
+    }
+
+    /// Takes `node` down for `outage_turns` turns like a transient failure, or for good with
+    /// `None`, as of the current turn.
+    pub fn inject_failure(&mut self, node: NodeId, outage_turns: Option<usize>) {
+        let state = self.current_snapshot.node_state_mut(node);
+        match outage_turns {
+            Some(turns) => state.set_outage(turns),
+            None => state.set_health(0.0),
+        }
+        state.set_served(0.0);
+        state.set_backlog(0.0);
+    }
+
+    /// Goes back up to `turns` turns and returns how many it went back. The turn gone back to
+    /// is as it was played, before its actions, which are undone along with their ops. The
+    /// noise keeps its draws, so a noisy run does not replay the same way.
+    pub fn rewind(&mut self, turns: usize) -> usize {
+        let turns = turns.min(self.history.len());
+        let kept = self.history.len() - turns;
+        let Some((snapshot, ops_spent)) = self.history.drain(kept..).next() else {
+            return 0;
+        };
+        self.played_mods = snapshot.capacity_mods().clone();
+        self.current_snapshot = snapshot;
+        self.played_ops_spent = ops_spent;
+        self.ops_spent = ops_spent;
+        self.remaining_ops = self.scenario.ops_per_turn();
+        let snapshots = self.snapshots();
+        self.slo_tracks = self
+            .slos
+            .iter()
+            .map(|slo| track_slo(slo, &self.groups, &snapshots))
+            .collect();
+        turns
+    }
+
+    fn try_capacity_modifier(&mut self, action: Action) {
+        let (group_id, factor) = modifier(action);
         if self.remaining_ops > 0 && self.current_snapshot.update_capacity(group_id, factor) {
             self.remaining_ops -= 1;
+            self.ops_spent += 1;
         }
     }
 
     pub fn try_throttle_group(&mut self, group_id: usize) {
-        self.try_capacity_modifier(group_id, 0.5);
+        self.try_capacity_modifier(Action::Throttle(group_id));
     }
 
     pub fn try_boost_group(&mut self, group_id: usize) {
-        self.try_capacity_modifier(group_id, 1.5);
+        self.try_capacity_modifier(Action::Boost(group_id));
+    }
+
+    pub fn apply(&mut self, action: Action) {
+        self.try_capacity_modifier(action);
+    }
+}
+
+/// group and capacity factor of an operator action
+fn modifier(action: Action) -> (usize, f64) {
+    match action {
+        Action::Throttle(group_id) => (group_id, 0.5),
+        Action::Boost(group_id) => (group_id, 1.5),
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::analysis::analysis::calc_cost;
     use crate::analysis::groups::Group;
     use crate::graph::edge::{Edge, EdgeId};
     use crate::graph::node::Node;
+    use crate::scenario::scenario::TestScenario;
     use crate::simulation::modifiers::CapacityModifier;
     use crate::state::edge_state::EdgeState;
     use crate::state::node_state::NodeState;
     use approx::assert_relative_eq;
 
-    pub struct TestScenario {
-        entry: Vec<NodeId>,
-        loads: Vec<f64>,
-    }
-
-    impl TestScenario {
-        pub fn new(entry: Vec<NodeId>, loads: Vec<f64>) -> Self {
-            Self { entry, loads }
-        }
-    }
-
-    impl Scenario for TestScenario {
-        fn load(&self, node_id: NodeId, turn: usize) -> f64 {
-            if self.entry.contains(&node_id) {
-                self.loads[turn]
-            } else {
-                0.0
-            }
-        }
-
-        fn entry_nodes(&self) -> &[NodeId] {
-            &self.entry
-        }
-
-        fn ops_per_turn(&self) -> u8 {
-            1
-        }
-    }
-
     fn snapshot(graph: &Graph, group_cnt: usize) -> Snapshot {
         Snapshot::new(
             0,
@@ -634,6 +770,32 @@ mod tests {
         assert_relative_eq!(110.0, node_states[1].backlog());
     }
 
+    #[test]
+    fn test_boost_is_billed_for_three_turns() {
+        let api = Node::new(NodeId(0), "api".to_string(), 100.0, 0.0);
+        let graph = Graph::new(vec![api], vec![]);
+        let initial_snapshot = snapshot(&graph, 1);
+        let groups = GroupSet::new(vec![Group::new("group1".to_string(), vec![NodeId(0)])]);
+        let mut engine = SimulationEngine::new(
+            graph,
+            groups,
+            initial_snapshot,
+            Box::new(TestScenario::new(vec![NodeId(0)], vec![50.0; 8])),
+        );
+        engine.step();
+        engine.try_boost_group(0);
+        (0..6).for_each(|_| engine.step());
+
+        let group = &engine.groups().groups()[0];
+        let costs = engine
+            .snapshots()
+            .iter()
+            .map(|s| calc_cost(s, group, engine.graph(), 0))
+            .collect::<Vec<f64>>();
+        // 100 rps at the default cost, half again while boosted
+        assert_eq!(vec![1.0, 1.0, 1.5, 1.5, 1.5, 1.0, 1.0, 1.0], costs);
+    }
+
     #[test]
     fn test_load_splitting() {
         let api1 = Node::new(NodeId(0), "api1".to_string(), 100.0, 1.0);
@@ -729,4 +891,138 @@ mod tests {
         assert_relative_eq!(0.0, node_states[3].served());
         assert_relative_eq!(0.0, node_states[4].served());
     }
+
+    #[test]
+    fn test_transient_outage_stops_forwarding() {
+        let api = Node::new(NodeId(0), "api".to_string(), 100.0, 1.0);
+        let db = Node::new(NodeId(1), "db".to_string(), 60.0, 1.0);
+        let link = Edge::new(EdgeId(0), NodeId(0), NodeId(1), 1.0);
+
+        let graph = Graph::new(vec![api, db], vec![link]);
+        let initial_snapshot = snapshot(&graph, 1);
+        let groups = GroupSet::new(vec![Group::new(
+            "group1".to_string(),
+            vec![NodeId(0), NodeId(1)],
+        )]);
+
+        let mut engine = SimulationEngine::new(
+            graph,
+            groups,
+            initial_snapshot,
+            Box::new(TestScenario::new(vec![NodeId(0)], vec![10.0, 20.0, 30.0])),
+        )
+        .with_noise(Noise::new(7).with_transient_failures(1.0, 2));
+        engine.step();
+
+        let node_states = engine.current_snapshot.node_states();
+        assert_eq!(2, node_states[0].outage());
+        assert!(!node_states[0].is_up());
+        assert!(node_states[0].is_healthy());
+        assert_relative_eq!(0.0, node_states[0].served());
+
+        engine.step();
+
+        let node_states = engine.current_snapshot.node_states();
+        assert_relative_eq!(0.0, node_states[1].demand());
+        assert_relative_eq!(1.0, node_states[0].health());
+    }
+
+    #[test]
+    fn test_noise_is_reproducible_per_seed() {
+        let run = |seed: u64| {
+            let api = Node::new(NodeId(0), "api".to_string(), 100.0, 1.0);
+            let db = Node::new(NodeId(1), "db".to_string(), 60.0, 1.0);
+            let link = Edge::new(EdgeId(0), NodeId(0), NodeId(1), 1.0);
+
+            let graph = Graph::new(vec![api, db], vec![link]);
+            let initial_snapshot = snapshot(&graph, 1);
+            let groups = GroupSet::new(vec![Group::new(
+                "group1".to_string(),
+                vec![NodeId(0), NodeId(1)],
+            )]);
+            let mut engine = SimulationEngine::new(
+                graph,
+                groups,
+                initial_snapshot,
+                Box::new(TestScenario::new(vec![NodeId(0)], vec![50.0, 50.0, 50.0])),
+            )
+            .with_noise(
+                Noise::new(seed)
+                    .with_load_jitter(0.2)
+                    .with_capacity_noise(0.1),
+            );
+            engine.step();
+            engine.step();
+            let states = engine.current_snapshot.node_states();
+            (states[0].served(), states[1].demand())
+        };
+
+        assert_eq!(run(1), run(1));
+        assert_ne!(run(1), run(2));
+        let (served, _) = run(3);
+        assert!((40.0..=60.0).contains(&served));
+    }
+
+    #[test]
+    fn test_inject_failure_and_rewind() {
+        let api = Node::new(NodeId(0), "api".to_string(), 100.0, 1.0);
+        let db = Node::new(NodeId(1), "db".to_string(), 60.0, 1.0);
+        let link = Edge::new(EdgeId(0), NodeId(0), NodeId(1), 1.0);
+
+        let graph = Graph::new(vec![api, db], vec![link]);
+        let initial_snapshot = snapshot(&graph, 2);
+        let groups = GroupSet::new(vec![
+            Group::new("group1".to_string(), vec![NodeId(0)]),
+            Group::new("group2".to_string(), vec![NodeId(1)]),
+        ]);
+        let mut engine = SimulationEngine::new(
+            graph,
+            groups,
+            initial_snapshot,
+            Box::new(TestScenario::new(vec![NodeId(0)], vec![50.0; 6])),
+        );
+        engine.step();
+        engine.try_throttle_group(1);
+        engine.step();
+        let served = |engine: &SimulationEngine| {
+            let states = engine.current_snapshot.node_states();
+            (states[0].served(), states[1].served())
+        };
+        let played = (0..3)
+            .map(|_| {
+                engine.step();
+                served(&engine)
+            })
+            .collect::<Vec<_>>();
+
+        // the throttle replays with the turns it had left
+        assert_eq!(3, engine.rewind(3));
+        assert_eq!(2, engine.current_snapshot.turn());
+        let replayed = (0..3)
+            .map(|_| {
+                engine.step();
+                served(&engine)
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(played, replayed);
+        assert_eq!(1, engine.ops_spent());
+        // back to the turn the throttle was taken in, before it was taken
+        assert_eq!(4, engine.rewind(4));
+        assert_eq!(1, engine.current_snapshot.turn());
+        assert!(!engine.current_snapshot.capacity_mod(1).is_active());
+        assert_eq!(0, engine.ops_spent());
+        assert_eq!(1, engine.rewind(10));
+        assert_eq!(0, engine.current_snapshot.turn());
+
+        engine.inject_failure(NodeId(0), Some(2));
+        engine.step();
+        assert_relative_eq!(0.0, engine.current_snapshot.node_states()[1].demand());
+        assert!(!engine.current_snapshot.node_states()[0].is_up());
+        engine.step();
+        assert!(engine.current_snapshot.node_states()[0].is_up());
+
+        engine.inject_failure(NodeId(1), None);
+        engine.step();
+        assert!(!engine.current_snapshot.node_states()[1].is_healthy());
+    }
 }
@@ -0,0 +1,188 @@
+use crate::analysis::analysis::{aggregate_groups, calc_health};
+use crate::analysis::goodput::goodput;
+use crate::analysis::groups::GroupSummary;
+use crate::simulation::action::Action;
+use crate::simulation::engine::SimulationEngine;
+use crate::state::snapshot::Snapshot;
+
+/// features per group in an observation
+pub const GROUP_FEATURES: usize = 5;
+/// features of the whole run, after the groups
+pub const RUN_FEATURES: usize = 3;
+
+/// A control loop over one scenario for benchmarking mitigation policies: `reset` to a seed,
+/// `observe`, `act` within the turn's ops budget, then `step` and collect the reward, until
+/// `is_done`.
+pub struct Environment {
+    build: Box<dyn Fn(u64) -> SimulationEngine>,
+    engine: SimulationEngine,
+    max_turns: usize,
+}
+
+impl Environment {
+    /// `build` makes the engine of an episode from its seed, episodes end after `max_turns`.
+    pub fn new(build: impl Fn(u64) -> SimulationEngine + 'static, max_turns: usize) -> Self {
+        let engine = build(0);
+        Self {
+            build: Box::new(build),
+            engine,
+            max_turns,
+        }
+    }
+
+    pub fn engine(&self) -> &SimulationEngine {
+        &self.engine
+    }
+
+    /// Starts a new episode and returns its first observation.
+    pub fn reset(&mut self, seed: u64) -> Vec<f64> {
+        self.engine = (self.build)(seed);
+        self.observe()
+    }
+
+    /// length of every observation, `GROUP_FEATURES` per group followed by `RUN_FEATURES`
+    pub fn observation_size(&self) -> usize {
+        self.engine.groups().groups().len() * GROUP_FEATURES + RUN_FEATURES
+    }
+
+    /// Per group: health, average utilisation, share of healthy nodes, capacity factor and
+    /// turns left on its modifier. Then the success rate of the entry load, the ops left this
+    /// turn and the share of the episode played.
+    pub fn observe(&self) -> Vec<f64> {
+        let engine = &self.engine;
+        let snapshot = engine.current_snapshot();
+        let mut features = Vec::with_capacity(self.observation_size());
+        for (group_id, summary) in self.summaries().iter().enumerate() {
+            let modifier = snapshot.capacity_mod(group_id);
+            features.extend([
+                summary.raw_health(),
+                summary.avg_utilization(),
+                summary.healthy_nodes() as f64 / summary.node_count().max(1) as f64,
+                modifier.factor(),
+                modifier.remaining_turns() as f64,
+            ]);
+        }
+        let result = goodput(engine.graph(), snapshot, engine.scenario().entry_nodes());
+        features.extend([
+            result.success_rate(),
+            engine.remaining_ops() as f64,
+            snapshot.turn() as f64 / self.max_turns.max(1) as f64,
+        ]);
+        features
+    }
+
+    /// group summaries of the current turn, in group order
+    pub fn summaries(&self) -> Vec<GroupSummary> {
+        aggregate_groups(
+            self.engine.groups(),
+            self.engine.current_snapshot(),
+            self.engine.previous_snapshot(),
+            self.engine.graph(),
+        )
+    }
+
+    /// every operator action, throttle then boost for each group, whether allowed now or not
+    pub fn actions(&self) -> Vec<Action> {
+        Action::all(self.engine.groups().groups().len())
+    }
+
+    /// Applies `action` if there are ops left this turn and its group has no modifier active,
+    /// returns whether it was applied.
+    pub fn act(&mut self, action: Action) -> bool {
+        let ops = self.engine.remaining_ops();
+        self.engine.apply(action);
+        self.engine.remaining_ops() < ops
+    }
+
+    /// Advances one turn and returns its reward.
+    pub fn step(&mut self) -> f64 {
+        self.engine.step();
+        reward(&self.engine, self.engine.current_snapshot())
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.engine.current_snapshot().turn() >= self.max_turns
+    }
+}
+
+/// Mean of the average group health and the share of entry load served end to end in
+/// `snapshot`, in [0, 1].
+pub fn reward(engine: &SimulationEngine, snapshot: &Snapshot) -> f64 {
+    let groups = engine.groups().groups();
+    let health =
+        groups.iter().map(|g| calc_health(snapshot, g)).sum::<f64>() / groups.len().max(1) as f64;
+    let result = goodput(engine.graph(), snapshot, engine.scenario().entry_nodes());
+    (health + result.success_rate()) / 2.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scenario::topology::Topology;
+    use crate::simulation::noise::Noise;
+    use approx::assert_relative_eq;
+
+    fn environment() -> Environment {
+        let topology = Topology::two_tier(100.0, 50.0, 150.0);
+        Environment::new(
+            move |seed| {
+                topology
+                    .engine()
+                    .with_noise(Noise::new(seed).with_load_jitter(0.1))
+            },
+            5,
+        )
+    }
+
+    #[test]
+    fn test_reset_is_reproducible() {
+        let mut env = environment();
+        let first = env.reset(7);
+        assert_eq!(2 * GROUP_FEATURES + RUN_FEATURES, first.len());
+        assert_eq!(env.observation_size(), first.len());
+
+        let mut rewards = vec![];
+        while !env.is_done() {
+            rewards.push(env.step());
+        }
+        assert_eq!(5, rewards.len());
+        assert!(rewards.iter().all(|r| (0.0..=1.0).contains(r)));
+        let last = env.observe();
+
+        assert_eq!(first, env.reset(7));
+        let replay = (0..5).map(|_| env.step()).collect::<Vec<f64>>();
+        assert_eq!(rewards, replay);
+        assert_eq!(last, env.observe());
+
+        env.reset(8);
+        (0..5).for_each(|_| {
+            env.step();
+        });
+        assert_ne!(last, env.observe());
+    }
+
+    #[test]
+    fn test_actions_respect_the_ops_budget() {
+        let mut env = environment();
+        env.reset(0);
+        assert_eq!(
+            vec![
+                Action::Throttle(0),
+                Action::Throttle(1),
+                Action::Boost(0),
+                Action::Boost(1)
+            ],
+            env.actions()
+        );
+
+        assert!(env.act(Action::Boost(1)));
+        // one op per turn
+        assert!(!env.act(Action::Throttle(0)));
+        env.step();
+        // still boosted
+        assert!(!env.act(Action::Boost(1)));
+        let observation = env.observe();
+        assert_relative_eq!(1.5, observation[GROUP_FEATURES + 3]);
+        assert_relative_eq!(1.0, observation[2 * GROUP_FEATURES + 1]);
+    }
+}
@@ -0,0 +1,331 @@
+use crate::analysis::analysis::calc_health;
+use crate::analysis::goodput::goodput;
+use crate::analysis::groups::GroupHealth;
+use crate::simulation::engine::SimulationEngine;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io;
+use std::io::{BufReader, BufWriter, Write};
+use std::path::Path;
+
+/// turns in a row goodput may stay below the objective before the game is lost
+pub const GRACE_TURNS: usize = 5;
+/// points per turn for full health, and again for serving all entry load
+pub const POINTS_PER_TURN: f64 = 50.0;
+/// points lost per throttle or boost
+pub const POINTS_PER_OP: f64 = 25.0;
+/// high scores kept per scenario and seed
+pub const HIGH_SCORES: usize = 10;
+
+/// What a scenario asks of the player.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Objectives {
+    /// won after this many turns without any group `Failed`
+    pub survive_turns: usize,
+    /// lost once the share of entry load served end to end stays below this for
+    /// `GRACE_TURNS` turns in a row
+    pub min_goodput: Option<f64>,
+}
+
+impl Default for Objectives {
+    fn default() -> Self {
+        Self {
+            survive_turns: 50,
+            min_goodput: Some(0.5),
+        }
+    }
+}
+
+impl Objectives {
+    pub fn describe(&self) -> Vec<String> {
+        let mut lines = vec![format!(
+            "Survive {} turns with no group Failed",
+            self.survive_turns
+        )];
+        if let Some(min) = self.min_goodput {
+            lines.push(format!(
+                "Keep goodput above {:.0}% of the entry load, at most {} turns in a row below",
+                min * 100.0,
+                GRACE_TURNS - 1
+            ));
+        }
+        lines
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Outcome {
+    Won,
+    Lost(String),
+}
+
+/// Scores a run turn by turn against `Objectives` until it is won or lost.
+pub struct Game {
+    objectives: Objectives,
+    turns: usize,
+    health_points: f64,
+    goodput_points: f64,
+    ops_spent: usize,
+    turns_below: usize,
+    outcome: Option<Outcome>,
+}
+
+impl Game {
+    pub fn new(objectives: Objectives) -> Self {
+        Self {
+            objectives,
+            turns: 0,
+            health_points: 0.0,
+            goodput_points: 0.0,
+            ops_spent: 0,
+            turns_below: 0,
+            outcome: None,
+        }
+    }
+
+    pub fn objectives(&self) -> &Objectives {
+        &self.objectives
+    }
+
+    /// Scores the turn `engine` just played and checks the objectives. Does nothing once the
+    /// game is over.
+    pub fn update(&mut self, engine: &SimulationEngine) -> Option<&Outcome> {
+        if self.outcome.is_some() {
+            return self.outcome.as_ref();
+        }
+        let snapshot = engine.current_snapshot();
+        let groups = engine.groups().groups();
+        let health = groups
+            .iter()
+            .map(|g| calc_health(snapshot, g))
+            .collect::<Vec<f64>>();
+        let success =
+            goodput(engine.graph(), snapshot, engine.scenario().entry_nodes()).success_rate();
+
+        self.turns = snapshot.turn();
+        self.health_points +=
+            POINTS_PER_TURN * health.iter().sum::<f64>() / health.len().max(1) as f64;
+        self.goodput_points += POINTS_PER_TURN * success;
+        self.ops_spent = engine.ops_spent();
+
+        let failed = health
+            .iter()
+            .position(|h| GroupHealth::classify(*h) == GroupHealth::Failed);
+        self.turns_below = match self.objectives.min_goodput {
+            Some(min) if success < min => self.turns_below + 1,
+            _ => 0,
+        };
+        self.outcome = if let Some(g_id) = failed {
+            Some(Outcome::Lost(format!(
+                "{} failed at turn {}",
+                groups[g_id].name(),
+                self.turns
+            )))
+        } else if self.turns_below >= GRACE_TURNS {
+            Some(Outcome::Lost(format!(
+                "goodput below the objective for {} turns at turn {}",
+                GRACE_TURNS, self.turns
+            )))
+        } else if self.turns >= self.objectives.survive_turns {
+            Some(Outcome::Won)
+        } else {
+            None
+        };
+        self.outcome.as_ref()
+    }
+
+    pub fn outcome(&self) -> Option<&Outcome> {
+        self.outcome.as_ref()
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.outcome.is_some()
+    }
+
+    pub fn turns(&self) -> usize {
+        self.turns
+    }
+
+    /// points for the average group health of every turn
+    pub fn health_points(&self) -> f64 {
+        self.health_points
+    }
+
+    /// points for the share of entry load served end to end every turn
+    pub fn goodput_points(&self) -> f64 {
+        self.goodput_points
+    }
+
+    pub fn ops_spent(&self) -> usize {
+        self.ops_spent
+    }
+
+    pub fn score(&self) -> f64 {
+        self.health_points + self.goodput_points - POINTS_PER_OP * self.ops_spent as f64
+    }
+
+    pub fn high_score(&self, player: &str) -> HighScore {
+        HighScore {
+            player: player.to_string(),
+            score: self.score(),
+            turns: self.turns,
+            won: self.outcome == Some(Outcome::Won),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct HighScore {
+    /// `human` or the name of the policy that played
+    pub player: String,
+    pub score: f64,
+    pub turns: usize,
+    pub won: bool,
+}
+
+/// The best scores per scenario and seed, kept in a local JSON file.
+#[derive(Default, Serialize, Deserialize)]
+pub struct HighScores {
+    scores: BTreeMap<String, Vec<HighScore>>,
+}
+
+impl HighScores {
+    /// key of the scores of a scenario played with a seed
+    pub fn key(scenario: &str, seed: u64) -> String {
+        format!("{}#{}", scenario, seed)
+    }
+
+    /// Reads the file at `path`, empty when it does not exist yet.
+    pub fn read(path: &Path) -> io::Result<Self> {
+        match File::open(path) {
+            Ok(file) => serde_json::from_reader(BufReader::new(file)).map_err(|e| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("{}: {}", path.display(), e),
+                )
+            }),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e),
+        }
+    }
+
+    pub fn write(&self, path: &Path) -> io::Result<()> {
+        let mut out = BufWriter::new(File::create(path)?);
+        serde_json::to_writer_pretty(&mut out, self)?;
+        writeln!(out)?;
+        out.flush()
+    }
+
+    /// Adds `score` to the file at `path`, returns the scores under `key` and the rank of
+    /// `score` among them.
+    pub fn record(
+        path: &Path,
+        key: &str,
+        score: HighScore,
+    ) -> io::Result<(Vec<HighScore>, Option<usize>)> {
+        let mut scores = Self::read(path)?;
+        let rank = scores.insert(key, score);
+        scores.write(path)?;
+        Ok((scores.get(key).to_vec(), rank))
+    }
+
+    /// best first
+    pub fn get(&self, key: &str) -> &[HighScore] {
+        self.scores.get(key).map_or(&[], |s| s.as_slice())
+    }
+
+    /// Adds `score` and returns its 0-based rank, `None` when it did not make the table.
+    pub fn insert(&mut self, key: &str, score: HighScore) -> Option<usize> {
+        let scores = self.scores.entry(key.to_string()).or_default();
+        let rank = scores
+            .iter()
+            .position(|s| s.score < score.score)
+            .unwrap_or(scores.len());
+        if rank >= HIGH_SCORES {
+            return None;
+        }
+        scores.insert(rank, score);
+        scores.truncate(HIGH_SCORES);
+        Some(rank)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scenario::topology::Topology;
+    use crate::simulation::action::Action;
+    use approx::assert_relative_eq;
+
+    fn play(engine: &mut SimulationEngine, game: &mut Game) {
+        while !game.is_over() {
+            engine.step();
+            game.update(engine);
+        }
+    }
+
+    #[test]
+    fn test_win_and_score() {
+        let mut engine = Topology::two_tier(100.0, 100.0, 80.0).engine();
+        let mut game = Game::new(Objectives {
+            survive_turns: 10,
+            min_goodput: Some(0.9),
+        });
+        engine.apply(Action::Boost(1));
+        play(&mut engine, &mut game);
+
+        assert_eq!(Some(&Outcome::Won), game.outcome());
+        assert_eq!(10, game.turns());
+        assert_eq!(1, game.ops_spent());
+        assert_relative_eq!(500.0, game.health_points());
+        assert_relative_eq!(500.0, game.goodput_points());
+        assert_relative_eq!(975.0, game.score());
+
+        // a finished game stays finished
+        engine.step();
+        game.update(&engine);
+        assert_eq!(10, game.turns());
+    }
+
+    #[test]
+    fn test_lose_and_rank() {
+        let mut engine = Topology::two_tier(100.0, 10.0, 80.0).engine();
+        let mut game = Game::new(Objectives::default());
+        play(&mut engine, &mut game);
+        assert_eq!(
+            Some(&Outcome::Lost("back failed at turn 3".to_string())),
+            game.outcome()
+        );
+
+        // the db keeps up with 70 of the 80 rps long enough to lose on goodput first
+        let mut engine = Topology::two_tier(100.0, 70.0, 80.0).engine();
+        let mut game = Game::new(Objectives {
+            survive_turns: 50,
+            min_goodput: Some(0.95),
+        });
+        play(&mut engine, &mut game);
+        assert_eq!(
+            Some(&Outcome::Lost(
+                "goodput below the objective for 5 turns at turn 6".to_string()
+            )),
+            game.outcome()
+        );
+
+        let mut scores = HighScores::default();
+        let key = HighScores::key("basic", 7);
+        assert_eq!(Some(0), scores.insert(&key, game.high_score("human")));
+        let better = HighScore {
+            score: game.score() + 1.0,
+            ..game.high_score("boost")
+        };
+        assert_eq!(Some(0), scores.insert(&key, better));
+        let players = scores.get(&key).iter().map(|s| s.player.as_str());
+        assert_eq!(vec!["boost", "human"], players.collect::<Vec<_>>());
+        for _ in 0..HIGH_SCORES {
+            scores.insert(&key, game.high_score("noop"));
+        }
+        assert_eq!(None, scores.insert(&key, game.high_score("noop")));
+        assert!(scores.get("basic#8").is_empty());
+    }
+}
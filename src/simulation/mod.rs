@@ -1,2 +1,8 @@
+pub mod action;
 pub mod engine;
+pub mod environment;
+pub mod game;
 pub mod modifiers;
+pub mod noise;
+pub mod policy;
+pub mod schedule;
@@ -0,0 +1,65 @@
+use rand::{Rng, SeedableRng, rngs::StdRng};
+
+/// Seeded randomness layered over the deterministic engine. All amplitudes are relative, e.g.
+/// a `load_jitter` of 0.1 scales each entry load by a uniform factor in [0.9, 1.1].
+pub struct Noise {
+    rng: StdRng,
+    load_jitter: f64,
+    capacity_noise: f64,
+    failure_rate: f64,
+    outage_turns: usize,
+}
+
+impl Noise {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            rng: StdRng::seed_from_u64(seed),
+            load_jitter: 0.0,
+            capacity_noise: 0.0,
+            failure_rate: 0.0,
+            outage_turns: 0,
+        }
+    }
+
+    pub fn with_load_jitter(mut self, jitter: f64) -> Self {
+        self.load_jitter = jitter.clamp(0.0, 1.0);
+        self
+    }
+
+    pub fn with_capacity_noise(mut self, noise: f64) -> Self {
+        self.capacity_noise = noise.clamp(0.0, 1.0);
+        self
+    }
+
+    /// each up node goes down for `outage_turns` with probability `rate` per turn
+    pub fn with_transient_failures(mut self, rate: f64, outage_turns: usize) -> Self {
+        self.failure_rate = rate.clamp(0.0, 1.0);
+        self.outage_turns = outage_turns;
+        self
+    }
+
+    fn scale(&mut self, amplitude: f64) -> f64 {
+        if amplitude > 0.0 {
+            1.0 + self.rng.gen_range(-amplitude..=amplitude)
+        } else {
+            1.0
+        }
+    }
+
+    pub fn jitter_load(&mut self, load: f64) -> f64 {
+        load * self.scale(self.load_jitter)
+    }
+
+    pub fn perturb_capacity(&mut self, capacity: f64) -> f64 {
+        capacity * self.scale(self.capacity_noise)
+    }
+
+    /// length of the outage starting this turn, 0 for none
+    pub fn draw_outage(&mut self) -> usize {
+        if self.failure_rate > 0.0 && self.rng.gen_bool(self.failure_rate) {
+            self.outage_turns
+        } else {
+            0
+        }
+    }
+}
@@ -0,0 +1,231 @@
+use crate::analysis::analysis::aggregate_groups;
+use crate::analysis::groups::{GroupSummary, GroupTrend};
+use crate::simulation::action::Action;
+use crate::simulation::engine::SimulationEngine;
+use crate::simulation::environment::reward;
+use clap::ValueEnum;
+use rand::{Rng, SeedableRng, rngs::StdRng};
+
+/// An automatic operator, asked for its actions before every step.
+pub trait Policy {
+    fn name(&self) -> &'static str;
+
+    /// Actions to apply before the next step, given the summaries of the current turn in group
+    /// order. Actions beyond the ops budget or on a group with an active modifier are ignored.
+    fn decide(&mut self, engine: &SimulationEngine, groups: &[GroupSummary]) -> Vec<Action>;
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, ValueEnum)]
+#[value(rename_all = "lowercase")]
+pub enum PolicyKind {
+    /// never act
+    Noop,
+    /// pick uniformly among every action and doing nothing
+    Random,
+    /// boost the group with the most demand and backlog for its capacity, when above 1
+    Boost,
+    /// throttle the entry groups while a downstream group's health is falling
+    Throttle,
+    /// try every action one turn ahead and take the one with the best reward
+    Lookahead,
+}
+
+impl PolicyKind {
+    /// `seed` only matters to `Random`
+    pub fn build(self, seed: u64) -> Box<dyn Policy> {
+        match self {
+            PolicyKind::Noop => Box::new(Noop),
+            PolicyKind::Random => Box::new(RandomPolicy(StdRng::seed_from_u64(seed))),
+            PolicyKind::Boost => Box::new(BoostPressured),
+            PolicyKind::Throttle => Box::new(ThrottleIngress),
+            PolicyKind::Lookahead => Box::new(Lookahead),
+        }
+    }
+}
+
+/// Asks `policy` for its actions on the current turn of `engine` and applies them.
+pub fn act(policy: &mut dyn Policy, engine: &mut SimulationEngine) {
+    let groups = aggregate_groups(
+        engine.groups(),
+        engine.current_snapshot(),
+        engine.previous_snapshot(),
+        engine.graph(),
+    );
+    for action in policy.decide(engine, &groups) {
+        engine.apply(action);
+    }
+}
+
+fn is_free(engine: &SimulationEngine, group_id: usize) -> bool {
+    !engine.current_snapshot().capacity_mod(group_id).is_active()
+}
+
+/// Demand and backlog of the group's healthy nodes over their capacity, as in the TUI.
+fn pressure(engine: &SimulationEngine, group_id: usize) -> f64 {
+    let snapshot = engine.current_snapshot();
+    let factor = snapshot.capacity_mod(group_id).factor();
+    let (load, capacity) = engine.groups().groups()[group_id]
+        .nodes()
+        .iter()
+        .map(|id| {
+            (
+                engine.graph().node_by_id(*id),
+                &snapshot.node_states()[id.index()],
+            )
+        })
+        .filter(|(_, state)| state.is_healthy())
+        .fold((0.0, 0.0), |(load, capacity), (node, state)| {
+            (
+                load + state.demand() + state.backlog(),
+                capacity + node.capacity() * factor,
+            )
+        });
+    if capacity > 0.0 { load / capacity } else { 0.0 }
+}
+
+struct Noop;
+
+impl Policy for Noop {
+    fn name(&self) -> &'static str {
+        "noop"
+    }
+
+    fn decide(&mut self, _engine: &SimulationEngine, _groups: &[GroupSummary]) -> Vec<Action> {
+        vec![]
+    }
+}
+
+struct RandomPolicy(StdRng);
+
+impl Policy for RandomPolicy {
+    fn name(&self) -> &'static str {
+        "random"
+    }
+
+    fn decide(&mut self, engine: &SimulationEngine, _groups: &[GroupSummary]) -> Vec<Action> {
+        let actions = Action::all(engine.groups().groups().len());
+        // the last choice is to do nothing
+        let choice = self.0.gen_range(0..=actions.len());
+        actions.get(choice).copied().into_iter().collect()
+    }
+}
+
+struct BoostPressured;
+
+impl Policy for BoostPressured {
+    fn name(&self) -> &'static str {
+        "boost"
+    }
+
+    fn decide(&mut self, engine: &SimulationEngine, groups: &[GroupSummary]) -> Vec<Action> {
+        (0..groups.len())
+            .filter(|g_id| is_free(engine, *g_id))
+            .map(|g_id| (g_id, pressure(engine, g_id)))
+            .filter(|(_, pressure)| *pressure > 1.0)
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(g_id, _)| Action::Boost(g_id))
+            .into_iter()
+            .collect()
+    }
+}
+
+struct ThrottleIngress;
+
+impl Policy for ThrottleIngress {
+    fn name(&self) -> &'static str {
+        "throttle"
+    }
+
+    fn decide(&mut self, engine: &SimulationEngine, groups: &[GroupSummary]) -> Vec<Action> {
+        let ingress = engine.groups().groups_of(engine.scenario().entry_nodes());
+        let dropping = groups
+            .iter()
+            .enumerate()
+            .filter(|(g_id, _)| !ingress.contains(g_id))
+            .any(|(_, summary)| *summary.health_trend() == GroupTrend::Down);
+        if !dropping {
+            return vec![];
+        }
+        ingress
+            .into_iter()
+            .filter(|g_id| is_free(engine, *g_id))
+            .map(Action::Throttle)
+            .collect()
+    }
+}
+
+struct Lookahead;
+
+impl Policy for Lookahead {
+    fn name(&self) -> &'static str {
+        "lookahead"
+    }
+
+    fn decide(&mut self, engine: &SimulationEngine, _groups: &[GroupSummary]) -> Vec<Action> {
+        if engine.remaining_ops() == 0 {
+            return vec![];
+        }
+        let mut best = (vec![], reward(engine, &engine.preview(&[])));
+        for action in Action::all(engine.groups().groups().len()) {
+            let value = reward(engine, &engine.preview(&[action]));
+            if value > best.1 {
+                best = (vec![action], value);
+            }
+        }
+        best.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scenario::topology::Topology;
+
+    fn decide(policy: &mut dyn Policy, engine: &SimulationEngine) -> Vec<Action> {
+        let groups = aggregate_groups(
+            engine.groups(),
+            engine.current_snapshot(),
+            engine.previous_snapshot(),
+            engine.graph(),
+        );
+        policy.decide(engine, &groups)
+    }
+
+    #[test]
+    fn test_rule_based_policies() {
+        let mut engine = Topology::two_tier(200.0, 100.0, 150.0).engine();
+        let mut boost = PolicyKind::Boost.build(0);
+        let mut throttle = PolicyKind::Throttle.build(0);
+        assert!(decide(boost.as_mut(), &engine).is_empty());
+
+        engine.step();
+        engine.step();
+        // the db gets 150 rps for 100 and starts losing health
+        assert_eq!(vec![Action::Boost(1)], decide(boost.as_mut(), &engine));
+        assert_eq!(
+            vec![Action::Throttle(0)],
+            decide(throttle.as_mut(), &engine)
+        );
+        assert!(decide(PolicyKind::Noop.build(0).as_mut(), &engine).is_empty());
+
+        engine.apply(Action::Boost(1));
+        engine.step();
+        assert!(decide(boost.as_mut(), &engine).is_empty());
+    }
+
+    #[test]
+    fn test_lookahead_prefers_the_best_next_turn() {
+        let mut engine = Topology::two_tier(200.0, 100.0, 150.0).engine();
+        engine.step();
+        engine.step();
+        let mut lookahead = PolicyKind::Lookahead.build(0);
+        let actions = decide(lookahead.as_mut(), &engine);
+        assert_eq!(vec![Action::Boost(1)], actions);
+
+        // previewing leaves the engine alone
+        let turn = engine.current_snapshot().turn();
+        engine.preview(&actions);
+        assert_eq!(turn, engine.current_snapshot().turn());
+        assert_eq!(1, engine.remaining_ops());
+    }
+}
@@ -0,0 +1,58 @@
+use crate::analysis::groups::GroupSet;
+use crate::simulation::action::Action;
+use crate::simulation::engine::SimulationEngine;
+
+/// Operator actions to apply at given turns of a headless run.
+pub struct Schedule {
+    actions: Vec<(usize, Action)>,
+}
+
+impl Schedule {
+    pub fn new() -> Self {
+        Self { actions: vec![] }
+    }
+
+    /// Parses `specs` of the form `Group@turn`, e.g. `Database@40`.
+    pub fn parse(
+        groups: &GroupSet,
+        throttles: &[String],
+        boosts: &[String],
+    ) -> Result<Self, String> {
+        let mut schedule = Self::new();
+        for (specs, action) in [
+            (throttles, Action::Throttle as fn(usize) -> Action),
+            (boosts, Action::Boost),
+        ] {
+            for spec in specs {
+                let (name, turn) = spec
+                    .rsplit_once('@')
+                    .ok_or_else(|| format!("expected Group@turn, got '{spec}'"))?;
+                let group_id = groups
+                    .group_by_name(name)
+                    .ok_or_else(|| format!("unknown group '{name}'"))?;
+                let turn = turn
+                    .parse::<usize>()
+                    .map_err(|_| format!("invalid turn in '{spec}'"))?;
+                schedule.actions.push((turn, action(group_id)));
+            }
+        }
+        schedule.actions.sort_by_key(|(turn, _)| *turn);
+        Ok(schedule)
+    }
+
+    /// Applies the actions due at the engine's current turn.
+    pub fn apply(&self, engine: &mut SimulationEngine) {
+        let turn = engine.current_snapshot().turn();
+        self.actions
+            .iter()
+            .filter(|(t, _)| *t == turn)
+            .for_each(|(_, action)| engine.apply(*action));
+    }
+
+    pub fn run(&self, engine: &mut SimulationEngine, turns: usize) {
+        for _ in 0..turns {
+            self.apply(engine);
+            engine.step();
+        }
+    }
+}
@@ -8,6 +8,8 @@ pub struct NodeState {
     backlog: f64,
     /// health [0.0, 1.0]
     health: f64,
+    /// remaining turns of a transient outage
+    outage: usize,
 }
 
 impl NodeState {
@@ -17,6 +19,7 @@ impl NodeState {
             served,
             backlog,
             health,
+            outage: 0,
         }
     }
 
@@ -52,7 +55,20 @@ impl NodeState {
         self.health = health.clamp(0.0, 1.0)
     }
 
+    pub fn outage(&self) -> usize {
+        self.outage
+    }
+
+    pub fn set_outage(&mut self, turns: usize) {
+        self.outage = turns;
+    }
+
     pub fn is_healthy(&self) -> bool {
         self.health > 0.0
     }
+
+    /// healthy and not in a transient outage
+    pub fn is_up(&self) -> bool {
+        self.is_healthy() && self.outage == 0
+    }
 }
@@ -1,9 +1,11 @@
 use crate::graph::edge::EdgeId;
 use crate::graph::graph::Graph;
+use crate::graph::node::NodeId;
 use crate::simulation::modifiers::CapacityModifier;
 use crate::state::edge_state::EdgeState;
 use crate::state::node_state::NodeState;
 
+#[derive(Clone)]
 pub struct Snapshot {
     turn: usize,
     node_states: Vec<NodeState>,
@@ -26,6 +28,11 @@ impl Snapshot {
         }
     }
 
+    /// Replaces every capacity modifier, e.g. with the ones the turn was computed with.
+    pub fn set_capacity_mods(&mut self, capacity_mods: Vec<CapacityModifier>) {
+        self.capacity_mods = capacity_mods;
+    }
+
     pub fn tick(&mut self) {
         self.capacity_mods.iter_mut().for_each(|m| m.tick())
     }
@@ -38,6 +45,10 @@ impl Snapshot {
         &self.node_states
     }
 
+    pub fn node_state_mut(&mut self, id: NodeId) -> &mut NodeState {
+        &mut self.node_states[id.index()]
+    }
+
     pub fn edge_states(&self) -> &Vec<EdgeState> {
         &self.edge_states
     }
@@ -57,7 +68,7 @@ impl Snapshot {
     pub fn edge_load(&self, edged_id: EdgeId, graph: &Graph) -> f64 {
         let edge = graph.edge_by_id(edged_id);
         let f_id = edge.from().index();
-        if !self.node_states[f_id].is_healthy()
+        if !self.node_states[f_id].is_up()
             || !self.edge_states[edged_id.index()].is_enabled()
             || self.node_states[f_id].served() == 0.0
         {
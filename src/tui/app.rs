@@ -1,24 +1,109 @@
 use crate::analysis::analysis::aggregate_groups;
+use crate::analysis::cascade::{CascadeTracker, CascadeTree};
+use crate::analysis::comparison::{Comparison, compare_runs};
+use crate::analysis::cycles::{FeedbackLoop, find_feedback_loops};
+use crate::analysis::flow::FlowSeries;
+use crate::analysis::group_graph::GroupGraph;
 use crate::analysis::groups::GroupSummary;
+use crate::analysis::metrics::RunMetrics;
 use crate::simulation::engine::SimulationEngine;
+use crate::simulation::game::{Game, HighScore};
+use crate::simulation::policy::{Policy, act};
+
+#[derive(PartialEq)]
+pub enum View {
+    Nodes,
+    Cascade,
+    Compare,
+    Flows,
+    Summary,
+    Topology,
+}
 
 pub struct App {
     pub engine: SimulationEngine,
     pub aggregations: Vec<(usize, GroupSummary)>,
     pub selected_index: usize,
+    pub feedback_loops: Vec<FeedbackLoop>,
+    pub group_graph: GroupGraph,
+    pub view: View,
+    pub cascade: Option<CascadeTree>,
+    /// follows the run one step at a time so the cascade view does not replay the history
+    cascade_tracker: CascadeTracker,
+    /// the same scenario stepped in lockstep without operator actions
+    pub baseline: Option<SimulationEngine>,
+    pub comparison: Option<Comparison>,
+    /// metrics of the baseline and of this run, one turn added per step
+    run_metrics: Option<(RunMetrics, RunMetrics)>,
+    /// group-to-group flows of the previous and the current turn, in the flows and topology views
+    pub flows: Option<FlowSeries>,
+    /// acts before every step alongside the keys
+    pub policy: Option<Box<dyn Policy>>,
+    /// scores the run and stops it once won or lost
+    pub game: Option<Game>,
+    /// high scores of the scenario and seed once the game is over, and the rank of this run
+    pub ranking: Option<(Vec<HighScore>, Option<usize>)>,
 }
 
 impl App {
     pub fn new(engine: SimulationEngine) -> Self {
+        let feedback_loops = find_feedback_loops(engine.graph());
+        let group_graph = GroupGraph::new(
+            engine.graph(),
+            engine.groups(),
+            engine.scenario().entry_nodes(),
+        );
+        let mut cascade_tracker = CascadeTracker::new(engine.graph());
+        engine
+            .snapshots()
+            .iter()
+            .for_each(|s| cascade_tracker.push(engine.graph(), engine.groups(), s));
         let mut app = Self {
             engine,
             aggregations: vec![],
             selected_index: 0,
+            feedback_loops,
+            group_graph,
+            view: View::Nodes,
+            cascade: None,
+            cascade_tracker,
+            baseline: None,
+            comparison: None,
+            run_metrics: None,
+            flows: None,
+            policy: None,
+            game: None,
+            ranking: None,
         };
         app.refresh_groups();
         app
     }
 
+    pub fn with_baseline(mut self, baseline: SimulationEngine) -> Self {
+        let metrics = |engine: &SimulationEngine| {
+            RunMetrics::from_snapshots(engine.graph(), engine.groups(), &engine.snapshots())
+        };
+        self.run_metrics = Some((metrics(&baseline), metrics(&self.engine)));
+        self.baseline = Some(baseline);
+        self
+    }
+
+    pub fn with_policy(mut self, policy: Box<dyn Policy>) -> Self {
+        self.policy = Some(policy);
+        self
+    }
+
+    pub fn with_game(mut self, game: Game) -> Self {
+        self.game = Some(game);
+        self
+    }
+
+    /// Shows the summary with the high scores of the finished game.
+    pub fn finish_game(&mut self, ranking: (Vec<HighScore>, Option<usize>)) {
+        self.ranking = Some(ranking);
+        self.view = View::Summary;
+    }
+
     pub fn refresh_groups(&mut self) {
         let group_id = if self.aggregations.is_empty() {
             0
@@ -47,6 +132,68 @@ impl App {
             .unwrap_or(self.selected_index)
     }
 
+    pub fn step(&mut self) {
+        if self.game.as_ref().is_some_and(|g| g.is_over()) {
+            return;
+        }
+        if let Some(policy) = self.policy.as_mut() {
+            act(policy.as_mut(), &mut self.engine);
+        }
+        self.refresh_groups();
+        self.engine.step();
+        if let Some(game) = self.game.as_mut() {
+            game.update(&self.engine);
+        }
+        if let Some(baseline) = self.baseline.as_mut() {
+            baseline.step();
+        }
+        if let (Some(baseline), Some((baseline_metrics, metrics))) =
+            (self.baseline.as_ref(), self.run_metrics.as_mut())
+        {
+            let push = |metrics: &mut RunMetrics, engine: &SimulationEngine| {
+                metrics.push(engine.graph(), engine.groups(), engine.current_snapshot())
+            };
+            push(baseline_metrics, baseline);
+            push(metrics, &self.engine);
+        }
+        self.cascade_tracker.push(
+            self.engine.graph(),
+            self.engine.groups(),
+            self.engine.current_snapshot(),
+        );
+        self.refresh_view();
+    }
+
+    pub fn toggle_view(&mut self, view: View) {
+        self.view = if self.view == view { View::Nodes } else { view };
+        self.refresh_view();
+    }
+
+    fn refresh_view(&mut self) {
+        match self.view {
+            View::Nodes | View::Summary => {}
+            View::Cascade => {
+                self.cascade = Some(self.cascade_tracker.tree(self.engine.graph()));
+            }
+            View::Compare => {
+                self.comparison = self
+                    .run_metrics
+                    .as_ref()
+                    .map(|(baseline, metrics)| compare_runs(baseline, metrics));
+            }
+            View::Flows | View::Topology => {
+                self.flows = Some(FlowSeries::from_snapshots(
+                    self.engine.graph(),
+                    self.engine.groups(),
+                    &[
+                        self.engine.previous_snapshot(),
+                        self.engine.current_snapshot(),
+                    ],
+                ));
+            }
+        }
+    }
+
     pub fn select_next_group(&mut self) {
         if self.selected_index + 1 < self.engine.groups().groups().len() {
             self.selected_index += 1;
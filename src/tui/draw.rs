@@ -1,12 +1,21 @@
+use crate::analysis::comparison::Outcome;
+use crate::analysis::cycles::FeedbackLoop;
+use crate::analysis::goodput::goodput;
 use crate::analysis::groups::{GroupHealth, GroupTrend};
+use crate::analysis::latency::node_latencies;
+use crate::analysis::slo::SloVerdict;
 use crate::graph::node::{Node, NodeId};
+use crate::simulation::game;
+use crate::simulation::game::POINTS_PER_OP;
 use crate::state::node_state::NodeState;
-use crate::tui::app::App;
+use crate::tui::app::{App, View};
 use ratatui::Frame;
-use ratatui::layout::{Constraint, Layout, Margin};
+use ratatui::layout::{Constraint, Layout, Margin, Rect};
 use ratatui::style::Color::{Black, Gray, LightGreen, White};
 use ratatui::style::{Color, Style, Stylize};
+use ratatui::symbols::Marker;
 use ratatui::text::{Line, Span, Text};
+use ratatui::widgets::canvas::{Canvas, Context, Line as CanvasLine};
 use ratatui::widgets::{Block, Cell, Padding, Paragraph, Row, Table};
 
 fn find_pressure(app: &App) -> Vec<f64> {
@@ -66,13 +75,42 @@ pub fn draw_app(frame: &mut Frame, app: &App) {
     frame.render_widget(build_details_stats(app), details[0]);
     frame.render_widget(build_details_pressure(app), details[1]);
     frame.render_widget(build_details_most_pressured(app), details[2]);
-    frame.render_widget(build_node_table(app), body[2]);
+    match app.view {
+        View::Nodes => frame.render_widget(build_node_table(app), body[2]),
+        View::Cascade => frame.render_widget(build_cascade(app), body[2]),
+        View::Compare => frame.render_widget(build_comparison(app), body[2]),
+        View::Flows => frame.render_widget(build_flows(app), body[2]),
+        View::Summary => frame.render_widget(build_summary(app), body[2]),
+        View::Topology => {
+            let group_id = app.selected_group_id();
+            let neighbours = app.group_graph.upstream(group_id).len().max(1)
+                + app.group_graph.downstream(group_id).len().max(1);
+            let topology = Layout::vertical([
+                Constraint::Fill(1),
+                Constraint::Length((neighbours + 4).min(14) as u16),
+            ])
+            .split(body[2]);
+            frame.render_widget(build_topology(app, topology[0]), topology[0]);
+            frame.render_widget(build_neighbours(app), topology[1]);
+        }
+    }
 
+    frame.render_widget(build_warnings(app), main[3]);
     frame.render_widget(build_status(app), main[4]);
 }
 
-fn build_title(_app: &'_ App) -> Paragraph<'_> {
-    Paragraph::new(" FAULTGRAPH ").bold().cyan()
+fn build_title(app: &'_ App) -> Paragraph<'_> {
+    let mut spans = vec![Span::from(" FAULTGRAPH ").bold().cyan()];
+    if let Some(policy) = &app.policy {
+        spans.push(Span::from(" Policy "));
+        spans.push(Span::from(policy.name()).bold());
+    }
+    if let Some(game) = &app.game {
+        spans.push(Span::from(" Score "));
+        spans.push(Span::from(format!("{:.0}", game.score())).bold());
+        spans.push(Span::from(format!(" / {} turns", game.objectives().survive_turns)).dim());
+    }
+    Paragraph::new(Line::from(spans))
 }
 
 fn build_turn(app: &'_ App) -> Paragraph<'_> {
@@ -143,10 +181,27 @@ fn build_indicators(app: &'_ App) -> Paragraph<'_> {
         Style::default().fg(Color::Green)
     };
 
+    let success_rate = goodput(
+        app.engine.graph(),
+        app.engine.current_snapshot(),
+        entry_nodes,
+    )
+    .success_rate();
+    let goodput_style = if success_rate < 0.5 {
+        Style::default().fg(Color::Red)
+    } else if success_rate < 0.95 {
+        Style::default().fg(Color::Yellow)
+    } else {
+        Style::default().fg(Color::Green)
+    };
+
     Paragraph::new(Line::from(vec![
         Span::from(" L ").bold(),
         Span::from(format!(" {}rps ", incoming_load as usize)),
         Span::from(" | ").dim(),
+        Span::from(" G ").bold().style(goodput_style),
+        Span::from(format!(" {}% ", (success_rate * 100.0).round() as usize)).style(goodput_style),
+        Span::from(" | ").dim(),
         Span::from(" U ").bold(),
         Span::from(format!(" {}%  ", (avg_util * 100.0).round() as usize)),
         Span::from(" | ").dim(),
@@ -156,8 +211,41 @@ fn build_indicators(app: &'_ App) -> Paragraph<'_> {
     .right_aligned()
 }
 
-fn build_status(_app: &'_ App) -> Paragraph<'_> {
-    Paragraph::new(Line::from(vec![
+fn build_warnings(app: &'_ App) -> Paragraph<'_> {
+    let unstable = app
+        .feedback_loops
+        .iter()
+        .filter(|l| l.is_unstable())
+        .collect::<Vec<&FeedbackLoop>>();
+    let Some(worst) = unstable.first() else {
+        return Paragraph::default();
+    };
+
+    let groups = app.engine.groups();
+    let group_names = groups
+        .groups_of(worst.nodes())
+        .iter()
+        .map(|g_id| groups.groups()[*g_id].name())
+        .collect::<Vec<&str>>()
+        .join(", ");
+
+    let mut spans = vec![
+        Span::from(" ⚠ Unstable feedback loop ").bold().red(),
+        Span::from(format!(
+            " gain {:.2} across {} ({} nodes) ",
+            worst.loop_gain(),
+            group_names,
+            worst.nodes().len()
+        )),
+    ];
+    if unstable.len() > 1 {
+        spans.push(Span::from(format!(" +{} more ", unstable.len() - 1)).dim());
+    }
+    Paragraph::new(Line::from(spans))
+}
+
+fn build_status(app: &'_ App) -> Paragraph<'_> {
+    let mut spans = vec![
         Span::from(" [Q]"),
         Span::from(" Quit ").bold(),
         Span::from(" [Space]"),
@@ -166,7 +254,20 @@ fn build_status(_app: &'_ App) -> Paragraph<'_> {
         Span::from(" Throttle ").bold(),
         Span::from(" [B]"),
         Span::from(" Boost ").bold(),
-    ]))
+        Span::from(" [C]"),
+        Span::from(" Cascade ").bold(),
+        Span::from(" [V]"),
+        Span::from(" Compare ").bold(),
+        Span::from(" [F]"),
+        Span::from(" Flows ").bold(),
+        Span::from(" [G]"),
+        Span::from(" Graph ").bold(),
+    ];
+    if app.game.is_some() {
+        spans.push(Span::from(" [S]"));
+        spans.push(Span::from(" Summary ").bold());
+    }
+    Paragraph::new(Line::from(spans))
 }
 
 fn dots(turns: u8) -> String {
@@ -203,6 +304,31 @@ fn mods(app: &'_ App, group_id: usize) -> Line<'_> {
     mods
 }
 
+/// remaining error budget of the group's most depleted SLO
+fn slo_budget(app: &'_ App, group_id: usize) -> Span<'_> {
+    let groups = app.engine.groups();
+    let worst = app
+        .engine
+        .slos()
+        .iter()
+        .zip(app.engine.slo_tracks())
+        .filter(|(slo, _)| slo.group_id(groups) == group_id)
+        .filter_map(|(_, track)| track.last().map(|t| (t, track.verdict())))
+        .min_by(|a, b| a.0.budget_remaining().total_cmp(&b.0.budget_remaining()));
+
+    match worst {
+        None => Span::from(format!("{:>6}", "-")).dim(),
+        Some((turn, verdict)) => {
+            let text = format!("{:>5.0}%", turn.budget_remaining().max(-9.99) * 100.0);
+            match verdict {
+                SloVerdict::Met => Span::from(text).green(),
+                SloVerdict::AtRisk => Span::from(text).yellow(),
+                SloVerdict::Breached => Span::from(text).red().bold(),
+            }
+        }
+    }
+}
+
 fn build_group_table(app: &'_ App) -> Table<'_> {
     Table::new(
         app.aggregations.iter().map(|(g_id, summary)| {
@@ -248,15 +374,17 @@ fn build_group_table(app: &'_ App) -> Table<'_> {
                     Span::styled(format!("{:>9}", summary.health()), health_style),
                     Span::from(health_trend),
                 ])),
+                Cell::from(slo_budget(app, *g_id)),
                 Cell::from(mods(app, *g_id)),
             ])
             .style(row_style)
         }),
         [
             Constraint::Length(15),
-            Constraint::Length(10),
+            Constraint::Length(9),
             Constraint::Length(8),
-            Constraint::Length(15),
+            Constraint::Length(11),
+            Constraint::Length(6),
             Constraint::Fill(1),
         ],
     )
@@ -266,6 +394,7 @@ fn build_group_table(app: &'_ App) -> Table<'_> {
             Cell::from("   Util %"),
             Cell::from("   Nodes"),
             Cell::from("    Status"),
+            Cell::from("Budget"),
             Cell::from(" Mods"),
         ])
         .style(Style::default().bg(Color::DarkGray).fg(White)),
@@ -486,6 +615,15 @@ fn build_node_table(app: &'_ App) -> Table<'_> {
         })
         .collect::<Vec<(usize, (f64, f64))>>();
     rows.sort_by(|a, b| b.1.0.partial_cmp(&a.1.0).unwrap());
+    let latencies = node_latencies(graph, app.engine.groups(), app.engine.current_snapshot());
+    // imported names such as `namespace/service` get as much room as they need
+    let name_width = graph
+        .nodes()
+        .iter()
+        .map(|n| n.name().chars().count())
+        .max()
+        .unwrap_or(0)
+        .max(14);
 
     Table::new(
         rows.iter().map(|(i, (_, utilization))| {
@@ -499,16 +637,22 @@ fn build_node_table(app: &'_ App) -> Table<'_> {
                 Cell::from(format!("{:>8.1}", state.demand())),
                 Cell::from(format!("{:>6.1}", node.capacity())),
                 Cell::from(format!("{:>6.1}", state.health() * 100.0)),
+                Cell::from(match latencies[*i] {
+                    Some(ms) if ms < 100_000.0 => format!("{:>6.0}", ms),
+                    Some(_) => format!("{:>6}", "∞"),
+                    None => format!("{:>6}", "-"),
+                }),
                 Cell::from(mods(app, app.engine.groups().group_by_node_id(*i))),
             ])
         }),
         [
             Constraint::Length(4),
-            Constraint::Length(20),
+            Constraint::Length(name_width as u16),
             Constraint::Length(8),
             Constraint::Length(9),
             Constraint::Length(8),
             Constraint::Length(8),
+            Constraint::Length(7),
             Constraint::Length(8),
         ],
     )
@@ -520,6 +664,7 @@ fn build_node_table(app: &'_ App) -> Table<'_> {
             Cell::from(" Load rps"),
             Cell::from("  Cap"),
             Cell::from("Health %"),
+            Cell::from(" Lat ms"),
             Cell::from(" Mods"),
         ])
         .style(Style::default().bg(Color::DarkGray).fg(White)),
@@ -530,3 +675,432 @@ fn build_node_table(app: &'_ App) -> Table<'_> {
             .padding(Padding::horizontal(1)),
     )
 }
+
+fn build_cascade(app: &'_ App) -> Paragraph<'_> {
+    let graph = app.engine.graph();
+    let groups = app.engine.groups();
+    let block = Block::bordered()
+        .title(" Cascade ".bold())
+        .padding(Padding::horizontal(1));
+
+    let Some(root) = app.cascade.as_ref().and_then(|tree| tree.root_overload()) else {
+        return Paragraph::new("No failures yet").block(block);
+    };
+    let tree = app.cascade.as_ref().unwrap();
+
+    let mut lines: Vec<Line> = vec![
+        Line::from(vec![
+            Span::from("Root overload: ").bold(),
+            Span::from(format!(
+                "{} ({}) since turn {}",
+                graph.node_by_id(root.node_id()).name(),
+                groups.groups()[groups.group_by_node_id(root.node_id().index())].name(),
+                root.overloaded_at()
+            ))
+            .red(),
+        ]),
+        "".into(),
+        Line::from(format!(
+            "{:>5}  {:<30} {}",
+            "Turn", "Failed", "Pushed over by"
+        ))
+        .dim(),
+    ];
+
+    for (depth, event) in tree.walk() {
+        let indent = if depth == 0 {
+            String::new()
+        } else {
+            format!("{}└ ", "  ".repeat(depth - 1))
+        };
+        let cause = match (event.trigger(), event.trigger_group()) {
+            (Some(id), Some((g_id, g_share))) => format!(
+                "{} {}% ({} {}%)",
+                graph.node_by_id(id).name(),
+                (event.trigger_share() * 100.0).round() as usize,
+                groups.groups()[g_id].name(),
+                (g_share * 100.0).round() as usize
+            ),
+            (Some(id), None) => graph.node_by_id(id).name().to_string(),
+            (None, _) if event.trigger_share() > 0.0 => "entry traffic".to_string(),
+            (None, _) => "backlog".to_string(),
+        };
+        lines.push(Line::from(vec![
+            Span::from(format!("{:>5}  ", event.failed_at())),
+            Span::from(format!(
+                "{:<30}",
+                format!("{}{}", indent, graph.node_by_id(event.node_id()).name())
+            ))
+            .red(),
+            Span::from(format!(" {}", cause)),
+        ]));
+    }
+
+    Paragraph::new(Text::from(lines)).block(block)
+}
+
+fn build_summary(app: &'_ App) -> Paragraph<'_> {
+    let block = Block::bordered()
+        .title(" Game ".bold())
+        .padding(Padding::horizontal(1));
+    let Some(game) = &app.game else {
+        return Paragraph::new("Not in game mode, start with --game").block(block);
+    };
+
+    let mut lines: Vec<Line> = vec![match game.outcome() {
+        Some(game::Outcome::Won) => Line::from("You won").bold().green(),
+        Some(game::Outcome::Lost(reason)) => {
+            Line::from(format!("You lost: {}", reason)).bold().red()
+        }
+        None => Line::from(format!("Turn {} of the game", game.turns())).bold(),
+    }];
+    lines.push("".into());
+    lines.push(Line::from("Objectives").dim());
+    for objective in game.objectives().describe() {
+        lines.push(format!("· {}", objective).into());
+    }
+    lines.push("".into());
+    lines.push(format!("{:<20} {:>8.0}", "Health", game.health_points()).into());
+    lines.push(format!("{:<20} {:>8.0}", "Goodput", game.goodput_points()).into());
+    lines.push(
+        format!(
+            "{:<20} {:>8.0}",
+            format!("Ops spent ({})", game.ops_spent()),
+            0.0 - POINTS_PER_OP * game.ops_spent() as f64
+        )
+        .into(),
+    );
+    lines.push(Line::from(format!("{:<20} {:>8.0}", "Score", game.score())).bold());
+
+    if let Some((scores, rank)) = &app.ranking {
+        lines.push("".into());
+        lines.push(
+            Line::from(format!(
+                "{:>3}  {:<12} {:>8} {:>6} {:>5}",
+                "#", "Player", "Score", "Turns", "Won"
+            ))
+            .dim(),
+        );
+        for (pos, score) in scores.iter().enumerate() {
+            let line = Line::from(format!(
+                "{:>3}  {:<12} {:>8.0} {:>6} {:>5}",
+                pos + 1,
+                score.player,
+                score.score,
+                score.turns,
+                if score.won { "yes" } else { "no" }
+            ));
+            lines.push(if Some(pos) == *rank {
+                line.bold().yellow()
+            } else {
+                line
+            });
+        }
+        if rank.is_none() {
+            lines.push(Line::from("This run did not make the high scores").dim());
+        }
+    }
+
+    Paragraph::new(Text::from(lines)).block(block)
+}
+
+fn build_comparison(app: &'_ App) -> Table<'_> {
+    let title = match app.comparison.as_ref().map(|c| c.diverged_at()) {
+        Some(Some(turn)) => format!(" Compare vs no actions · diverged at turn {} ", turn),
+        Some(None) => " Compare vs no actions · identical ".to_string(),
+        None => " Compare vs no actions ".to_string(),
+    };
+    let diffs = app.comparison.as_ref().map_or(&[][..], |c| c.groups());
+
+    Table::new(
+        diffs.iter().map(|diff| {
+            let (b, v) = (diff.baseline(), diff.variant());
+            let delta = |d: f64| {
+                let text = format!("{:>+6.1}", d);
+                if d > 0.05 {
+                    Span::from(text).green()
+                } else if d < -0.05 {
+                    Span::from(text).red()
+                } else {
+                    Span::from(text)
+                }
+            };
+            let outcome = match diff.outcome() {
+                Outcome::Saved => Span::from("saved").green().bold(),
+                Outcome::Lost => Span::from("lost").red().bold(),
+                Outcome::Unchanged => Span::from(""),
+            };
+            Row::new(vec![
+                Cell::from(diff.name()),
+                Cell::from(format!(
+                    "{:>5.1} {:>5.1}",
+                    b.health() * 100.0,
+                    v.health() * 100.0
+                )),
+                Cell::from(delta(diff.health_diff() * 100.0)),
+                Cell::from(format!(
+                    "{:>5.1} {:>5.1}",
+                    b.utilization() * 100.0,
+                    v.utilization() * 100.0
+                )),
+                Cell::from(format!("{:>5.0} {:>5.0}", b.backlog(), v.backlog())),
+                Cell::from(format!("{:>7.1}", diff.max_health_gap() * 100.0)),
+                Cell::from(format!("{:>+7.1}", diff.spend_diff())),
+                Cell::from(outcome),
+            ])
+        }),
+        [
+            Constraint::Length(12),
+            Constraint::Length(11),
+            Constraint::Length(6),
+            Constraint::Length(11),
+            Constraint::Length(11),
+            Constraint::Length(7),
+            Constraint::Length(7),
+            Constraint::Length(5),
+        ],
+    )
+    .header(
+        Row::new([
+            Cell::from("Group"),
+            Cell::from("Health b/r"),
+            Cell::from("     Δ"),
+            Cell::from(" Util b/r"),
+            Cell::from("Backlog b/r"),
+            Cell::from("Max gap"),
+            Cell::from("Spend Δ"),
+            Cell::from(""),
+        ])
+        .style(Style::default().bg(Color::DarkGray).fg(White)),
+    )
+    .block(
+        Block::bordered()
+            .title(title.bold())
+            .padding(Padding::horizontal(1)),
+    )
+}
+
+fn flow_trend(load: f64, before: f64) -> &'static str {
+    if load > before * 1.02 {
+        "↗"
+    } else if load < before * 0.98 {
+        "↘"
+    } else {
+        "→"
+    }
+}
+
+fn build_flows(app: &'_ App) -> Table<'_> {
+    let Some(series) = app.flows.as_ref() else {
+        return Table::default().block(Block::bordered().title(" Flows ".bold()));
+    };
+    let (previous, current) = match series.turns() {
+        [previous, current] => (Some(previous), current),
+        [current] => (None, current),
+        _ => return Table::default().block(Block::bordered().title(" Flows ".bold())),
+    };
+    let max = current
+        .matrix()
+        .iter()
+        .flatten()
+        .fold(0.0, |acc: f64, l| acc.max(*l));
+
+    let short = |name: &str| name.chars().take(8).collect::<String>();
+    let rows = current.matrix().iter().enumerate().map(|(from, row)| {
+        let mut cells = vec![Cell::from(series.groups()[from].as_str()).bold()];
+        cells.extend(row.iter().enumerate().map(|(to, load)| {
+            if *load == 0.0 {
+                return Cell::from(format!("{:>7}", "·")).dim();
+            }
+            let before = previous.map_or(*load, |p| p.matrix()[from][to]);
+            let trend = flow_trend(*load, before);
+            let share = load / max;
+            let style = if share > 0.66 {
+                Style::default().bg(Color::Red).fg(Black)
+            } else if share > 0.33 {
+                Style::default().bg(Color::Yellow).fg(Black)
+            } else {
+                Style::default().bg(Color::DarkGray).fg(White)
+            };
+            Cell::from(format!("{:>6.0} {}", load, trend)).style(style)
+        }));
+        Row::new(cells)
+    });
+
+    let mut header = vec![Cell::from("From \\ To")];
+    header.extend(
+        series
+            .groups()
+            .iter()
+            .map(|name| Cell::from(format!("{:>8}", short(name)))),
+    );
+    let mut widths = vec![Constraint::Length(12)];
+    widths.extend(series.groups().iter().map(|_| Constraint::Length(8)));
+
+    Table::new(rows, widths)
+        .header(Row::new(header).style(Style::default().bg(Color::DarkGray).fg(White)))
+        .block(
+            Block::bordered()
+                .title(format!(" Flows rps · turn {} ", current.turn()).bold())
+                .padding(Padding::horizontal(1)),
+        )
+}
+
+fn health_color(health: &GroupHealth) -> Color {
+    match health {
+        GroupHealth::Ok => Color::Green,
+        GroupHealth::Degraded => Color::Yellow,
+        GroupHealth::Critical => Color::LightRed,
+        GroupHealth::Failed => Color::Red,
+    }
+}
+
+fn group_health(app: &App, group_id: usize) -> &GroupHealth {
+    app.aggregations
+        .iter()
+        .find(|(g_id, _)| *g_id == group_id)
+        .map_or(&GroupHealth::Ok, |(_, s)| s.health())
+}
+
+/// load from one group into another this turn and the turn before
+fn group_flow(app: &App, from: usize, to: usize) -> (f64, f64) {
+    match app.flows.as_ref().map(|series| series.turns()) {
+        Some([previous, current]) => (current.matrix()[from][to], previous.matrix()[from][to]),
+        Some([current]) => (current.matrix()[from][to], current.matrix()[from][to]),
+        _ => (0.0, 0.0),
+    }
+}
+
+/// Groups as boxes, layered by hops from the entry groups, and their links as arrows coloured
+/// by the health of the group called. Links without load this turn are grey.
+fn build_topology(app: &'_ App, area: Rect) -> Canvas<'_, impl Fn(&mut Context<'_>) + '_> {
+    // one canvas unit per cell, rows counted from the bottom
+    let width = area.width.saturating_sub(2).max(1) as f64;
+    let height = area.height.saturating_sub(2).max(1) as f64;
+    let layers = app.group_graph.layers();
+    let groups = app.engine.groups().groups();
+    let row = height / layers.len().max(1) as f64;
+
+    // centre and label of every group's box
+    let mut boxes = vec![(0.0, 0.0, String::new()); groups.len()];
+    for (depth, layer) in layers.iter().enumerate() {
+        let column = width / layer.len() as f64;
+        for (pos, g_id) in layer.iter().enumerate() {
+            let health = app
+                .aggregations
+                .iter()
+                .find(|(i, _)| i == g_id)
+                .map_or(1.0, |(_, s)| s.raw_health());
+            let label = format!("{} {:.0}%", groups[*g_id].name(), health * 100.0)
+                .chars()
+                .take((column as usize).saturating_sub(3).max(1))
+                .collect::<String>();
+            boxes[*g_id] = (
+                (column * (pos as f64 + 0.5)).floor(),
+                (height - 1.0 - row * (depth as f64 + 0.5)).round(),
+                label,
+            );
+        }
+    }
+
+    Canvas::default()
+        .block(Block::bordered().title(" Topology ".bold()))
+        .marker(Marker::Braille)
+        .x_bounds([0.0, width - 1.0])
+        .y_bounds([0.0, height - 1.0])
+        .paint(move |ctx| {
+            let mut arrows = vec![];
+            for (from, to) in app.group_graph.edges() {
+                let (fx, fy, flabel) = &boxes[from];
+                let (tx, ty, tlabel) = &boxes[to];
+                let color = if group_flow(app, from, to).0 > 0.0 {
+                    health_color(group_health(app, to))
+                } else {
+                    Color::DarkGray
+                };
+                let half = |label: &str| (label.chars().count() / 2 + 1) as f64;
+                // down into the top of the box, up into its bottom or across into its side
+                let (start, end, arrow) = if ty < fy {
+                    ((*fx, fy - 2.0), (*tx, ty + 2.0), "▼")
+                } else if ty > fy {
+                    ((*fx, fy + 2.0), (*tx, ty - 2.0), "▲")
+                } else if tx > fx {
+                    (
+                        (fx + half(flabel), *fy),
+                        (tx - half(tlabel) - 1.0, *ty),
+                        "▶",
+                    )
+                } else {
+                    (
+                        (fx - half(flabel) - 1.0, *fy),
+                        (tx + half(tlabel), *ty),
+                        "◀",
+                    )
+                };
+                ctx.draw(&CanvasLine::new(start.0, start.1, end.0, end.1, color));
+                arrows.push((end, arrow, color));
+            }
+            for ((x, y), arrow, color) in arrows {
+                ctx.print(x, y, Span::from(arrow).fg(color));
+            }
+
+            for (g_id, (x, y, label)) in boxes.iter().enumerate() {
+                let mut style = Style::default().fg(health_color(group_health(app, g_id)));
+                if g_id == app.selected_group_id() {
+                    style = style.reversed().bold();
+                }
+                let len = label.chars().count();
+                let left = x - (len / 2 + 1) as f64;
+                let border = "─".repeat(len);
+                ctx.print(left, y + 1.0, Span::styled(format!("┌{}┐", border), style));
+                ctx.print(left, *y, Span::styled(format!("│{}│", label), style));
+                ctx.print(left, y - 1.0, Span::styled(format!("└{}┘", border), style));
+            }
+        })
+}
+
+/// The selected group's upstream and downstream groups with the load on each link.
+fn build_neighbours(app: &'_ App) -> Paragraph<'_> {
+    let group_id = app.selected_group_id();
+    let name = |g_id: usize| app.engine.groups().groups()[g_id].name();
+    let neighbour = |g_id: usize| {
+        Span::from(format!("{:<14}", name(g_id))).fg(health_color(group_health(app, g_id)))
+    };
+    let load = |from: usize, to: usize| {
+        let (load, before) = group_flow(app, from, to);
+        format!("{:>8.0} rps {}", load, flow_trend(load, before))
+    };
+
+    let upstream = app.group_graph.upstream(group_id);
+    let downstream = app.group_graph.downstream(group_id);
+    let mut lines = vec![Line::from("Upstream").dim()];
+    for g_id in &upstream {
+        lines.push(Line::from(vec![
+            neighbour(*g_id),
+            Span::from(load(*g_id, group_id)),
+            Span::from(" ──▶ "),
+            Span::from(name(group_id)).bold(),
+        ]));
+    }
+    if upstream.is_empty() {
+        lines.push(Line::from("none, entry traffic only").dim());
+    }
+    lines.push(Line::from("Downstream").dim());
+    for g_id in &downstream {
+        lines.push(Line::from(vec![
+            Span::from(name(group_id)).bold(),
+            Span::from(" ──▶ "),
+            neighbour(*g_id),
+            Span::from(load(group_id, *g_id)),
+        ]));
+    }
+    if downstream.is_empty() {
+        lines.push(Line::from("none").dim());
+    }
+
+    Paragraph::new(Text::from(lines)).block(
+        Block::bordered()
+            .title(format!(" {} ", name(group_id)).bold())
+            .padding(Padding::horizontal(1)),
+    )
+}